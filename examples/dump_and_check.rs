@@ -3,7 +3,7 @@
 //! the behavior of cargo-criterion.
 
 use chrono::{DateTime, Local, TimeDelta};
-use criterion_cbor::Search;
+use criterion_cbor::{layout, Search};
 use std::path::PathBuf;
 
 fn main() {
@@ -20,35 +20,24 @@ fn main() {
         let bench_path = bench.path_from_data_root();
         println!(
             "\n=== Loading benchmark data from path {} ===\n",
-            bench_path.display()
+            bench_path.as_path().display()
         );
 
         let metadata = bench.metadata().expect("Failed to read benchmark metadata");
-        println!("id: {:#?}", metadata.id.decode());
-        println!(
-            "latest_local_datetime: {:#?}\n",
-            metadata.latest_local_datetime()
-        );
+        let id = metadata.id.decode().expect("Benchmark id should be valid");
+        println!("id: {id:#?}");
+        let metadata_datetime = metadata
+            .latest_local_datetime()
+            .expect("Latest record should point to a measurement file");
+        println!("latest_local_datetime: {metadata_datetime:#?}\n");
 
-        let make_filename_safe = |s: &str| {
-            s.replace(
-                &['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'][..],
-                "_",
-            )
-        };
-        let mut expected_path =
-            PathBuf::from(make_filename_safe(&metadata.id.group_or_function_id));
-        if let Some(function_id) = &metadata.id.function_id_in_group {
-            expected_path.push(make_filename_safe(function_id));
-        }
-        if let Some(value_str) = &metadata.id.value_str {
-            expected_path.push(make_filename_safe(value_str));
-        }
-        assert_eq!(bench_path, expected_path);
+        assert_eq!(bench_path.as_path(), layout::benchmark_dir(&metadata.id));
 
         let mut latest_datetime = None;
         for meas in bench.measurements() {
-            let datetime = meas.local_datetime();
+            let datetime = meas
+                .local_datetime()
+                .expect("Measurement file name should have the expected format");
             println!("--- Loading measurement from time {datetime:?} ---\n");
 
             match (datetime, latest_datetime) {
@@ -61,7 +50,7 @@ fn main() {
             println!("{data:?}\n");
 
             let date_close_to = |date: Option<DateTime<Local>>| {
-                date.map_or(false, |date| {
+                date.is_some_and(|date| {
                     data.datetime.date_naive() - date.date_naive() < TimeDelta::minutes(1)
                 })
             };
@@ -83,7 +72,7 @@ fn main() {
 
         assert_eq!(
             latest_datetime,
-            Some(metadata.latest_local_datetime()),
+            Some(metadata_datetime),
             "Latest date/time in benchmark.cbor doesn't match latest file date/time"
         );
     }
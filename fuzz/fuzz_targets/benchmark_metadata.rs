@@ -0,0 +1,14 @@
+//! Decode arbitrary bytes as a `benchmark.cbor` file, then use the result
+
+#![no_main]
+
+use criterion_cbor::BenchmarkMetadata;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(metadata) = BenchmarkMetadata::from_slice(bytes) {
+        let _ = metadata.id.decode();
+        let _ = metadata.id.full_id();
+        let _ = metadata.latest_local_datetime();
+    }
+});
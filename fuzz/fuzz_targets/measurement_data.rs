@@ -0,0 +1,12 @@
+//! Decode arbitrary bytes as a measurement file, then validate the result
+
+#![no_main]
+
+use criterion_cbor::{validation, MeasurementData};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(data) = MeasurementData::from_slice(bytes) {
+        let _ = validation::measurement_issues(&data);
+    }
+});
@@ -0,0 +1,115 @@
+//! Assertions on recorded benchmark data, for use in tests
+//!
+//! These functions let you encode hard performance budgets as `#[test]`
+//! functions, which check the latest data recorded by `cargo criterion`:
+//!
+//! ```no_run
+//! # use criterion_cbor::{assert::assert_latest_mean_below, Search};
+//! # use std::time::Duration;
+//! #[test]
+//! fn parser_is_fast_enough() {
+//!     let search = Search::in_cargo_root(env!("CARGO_MANIFEST_DIR"));
+//!     assert_latest_mean_below(search, "parser/small_input", Duration::from_micros(50));
+//! }
+//! ```
+//!
+//! Benchmarks are identified by their path from the Criterion data root, as
+//! returned by [`Benchmark::path_from_data_root()`]. All assertions panic if the
+//! benchmark cannot be found or its data cannot be read.
+
+use crate::{layout::DataPath, Benchmark, ChangeDirection, MeasurementData, Search, Statistic};
+use std::{path::Path, time::Duration};
+
+/// Assert that the latest mean execution time of a benchmark is below a limit
+///
+/// # Panics
+///
+/// If the latest mean execution time is not below `max`, or if the benchmark
+/// data cannot be loaded or does not represent execution times.
+#[track_caller]
+pub fn assert_latest_mean_below(search: Search, bench_path: impl AsRef<Path>, max: Duration) {
+    assert_latest_below(search, bench_path, Statistic::Mean, max)
+}
+
+/// Assert that the latest median execution time of a benchmark is below a limit
+///
+/// # Panics
+///
+/// If the latest median execution time is not below `max`, or if the benchmark
+/// data cannot be loaded or does not represent execution times.
+#[track_caller]
+pub fn assert_latest_median_below(search: Search, bench_path: impl AsRef<Path>, max: Duration) {
+    assert_latest_below(search, bench_path, Statistic::Median, max)
+}
+
+/// Assert that the latest execution time of a benchmark, according to a
+/// certain statistic, is below a limit
+///
+/// Use [`Statistic::Typical`] to check the same execution time that Criterion
+/// reports by default.
+///
+/// # Panics
+///
+/// If the latest execution time is not below `max`, or if the benchmark data
+/// cannot be loaded or does not represent execution times.
+#[track_caller]
+pub fn assert_latest_below(
+    search: Search,
+    bench_path: impl AsRef<Path>,
+    statistic: Statistic,
+    max: Duration,
+) {
+    let bench_path = bench_path.as_ref();
+    let (bench, data) = load_latest(search, bench_path);
+    assert!(
+        bench.value_unit().is_time(),
+        "Benchmark {} records values in {}, not execution times",
+        bench_path.display(),
+        bench.value_unit()
+    );
+    let actual_ns = data.estimates.get(statistic).point_estimate;
+    let max_ns = max.as_secs_f64() * 1e9;
+    assert!(
+        actual_ns < max_ns,
+        "Latest {statistic} execution time of benchmark {} is {}, which is above the limit of {}",
+        bench_path.display(),
+        bench.value_unit().format_value(actual_ns),
+        bench.value_unit().format_value(max_ns),
+    );
+}
+
+/// Assert that the latest measurement of a benchmark is not a regression
+///
+/// This relies on the change analysis that Criterion performed with respect to
+/// the previous measurement, if any.
+///
+/// # Panics
+///
+/// If Criterion detected a significant regression in the latest measurement,
+/// or if the benchmark data cannot be loaded.
+#[track_caller]
+pub fn assert_latest_not_regressed(search: Search, bench_path: impl AsRef<Path>) {
+    let bench_path = bench_path.as_ref();
+    let (_, data) = load_latest(search, bench_path);
+    assert_ne!(
+        data.change_direction,
+        Some(ChangeDirection::Regressed),
+        "Benchmark {} regressed in its latest measurement",
+        bench_path.display()
+    );
+}
+
+/// Load the latest measurement of a benchmark
+#[track_caller]
+fn load_latest(search: Search, bench_path: &Path) -> (Benchmark, MeasurementData) {
+    let bench = search
+        .find_in_paths(|dir| DataPath::new(bench_path).starts_with(dir.path_from_data_root()))
+        .map(|bench| bench.expect("Failed to walk the Criterion data directory"))
+        .find(|bench| bench.path_from_data_root() == DataPath::new(bench_path))
+        .unwrap_or_else(|| panic!("No data found for benchmark {}", bench_path.display()));
+    let data = bench
+        .latest_measurement()
+        .data()
+        .expect("Failed to read the latest measurement");
+    (bench, data)
+}
@@ -0,0 +1,452 @@
+//! Performance budgets tracked in a version-controlled file
+//!
+//! A performance budget file, conventionally named `perf-budgets.toml` and
+//! committed at the root of a Cargo project, maps benchmark path patterns to
+//! performance limits:
+//!
+//! ```toml
+//! # Every benchmark of the "parser" group must take less than 50µs
+//! [budgets."parser/*"]
+//! max_time_ns = 50000.0
+//!
+//! # The "decode" benchmark must process at least 1 GB/s
+//! [budgets."codec/decode"]
+//! min_throughput = 1.0e9
+//! ```
+//!
+//! Patterns are matched against the `/`-separated path of benchmarks from the
+//! Criterion data root, where `?` matches any single character and `*` matches
+//! any sequence of characters. Throughputs are expressed in bytes or elements
+//! per second, depending on what the benchmark's throughput is measured in.
+//!
+//! Budgets are checked against the latest typical execution time of each
+//! benchmark (see [`Estimates::typical()`](crate::Estimates::typical)). Another
+//! statistic can be selected with a top-level `statistic = "mean"` or
+//! `statistic = "median"` entry at the beginning of the budget file. Benchmarks
+//! whose values are not durations (see
+//! [`Search::value_unit()`](crate::Search::value_unit)) have no execution time
+//! or throughput, and thus fail any budget that applies to them.
+
+use crate::{
+    glob,
+    tags::Tags,
+    units::{ThroughputRate, ValueUnit},
+    Benchmark, Error, Search, Statistic,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    io,
+    path::Path,
+};
+
+/// Conventional name of the performance budget file
+pub const DEFAULT_FILE_NAME: &str = "perf-budgets.toml";
+
+/// Set of performance budgets, as stored in a budget file
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Budgets {
+    /// Statistic that benchmarks are checked with
+    ///
+    /// This is [`Statistic::Typical`] unless overridden in the budget file.
+    #[serde(default, skip_serializing_if = "is_typical")]
+    pub statistic: Statistic,
+
+    /// Performance budgets, indexed by benchmark path pattern
+    #[serde(default)]
+    pub budgets: BTreeMap<String, Budget>,
+}
+//
+impl Budgets {
+    /// Load performance budgets from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save performance budgets to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = toml::to_string_pretty(self)
+            .expect("Performance budgets should always be serializable to TOML");
+        std::fs::write(path, text)
+    }
+
+    /// Check the latest benchmark data against these budgets
+    ///
+    /// Every benchmark is checked against every budget whose pattern matches
+    /// its path. Benchmarks that do not match any budget are not checked.
+    pub fn check(&self, search: Search) -> Result<BudgetReport, Error> {
+        let mut checks = Vec::new();
+        for bench in search.find_all() {
+            let latest = LatestData::load(&bench?, self.statistic)?;
+            for (pattern, budget) in self.matching_budgets(&latest.path) {
+                if let Some(max_time_ns) = budget.max_time_ns {
+                    checks.push(BudgetCheck::new(
+                        &latest,
+                        pattern,
+                        BudgetLimit::MaxTime(max_time_ns),
+                        self.statistic,
+                    ));
+                }
+                if let Some(min_throughput) = budget.min_throughput {
+                    checks.push(BudgetCheck::new(
+                        &latest,
+                        pattern,
+                        BudgetLimit::MinThroughput(min_throughput),
+                        self.statistic,
+                    ));
+                }
+            }
+        }
+        Ok(BudgetReport { checks })
+    }
+
+    /// Regenerate budgets from the latest benchmark data
+    ///
+    /// Each existing budget is adjusted so that the slowest matching benchmark
+    /// passes with the specified relative `margin` (e.g. 0.1 for 10%). Budgets
+    /// which do not match any benchmark are left alone.
+    ///
+    /// If there is no budget yet, one budget is created for each benchmark
+    /// whose values are durations, with a time limit and, if the benchmark
+    /// measures throughput, a throughput limit.
+    pub fn update_from_latest(&mut self, search: Search, margin: f64) -> Result<(), Error> {
+        let latest = search
+            .find_all()
+            .map(|bench| LatestData::load(&bench?, self.statistic))
+            .collect::<Result<Vec<_>, Error>>()?;
+        if self.budgets.is_empty() {
+            for data in &latest {
+                let Some(time_ns) = data.time_ns() else {
+                    continue;
+                };
+                self.budgets.insert(
+                    data.path.clone(),
+                    Budget {
+                        max_time_ns: Some(time_ns),
+                        min_throughput: data.throughput,
+                    },
+                );
+            }
+        }
+        for (pattern, budget) in &mut self.budgets {
+            let matching = latest
+                .iter()
+                .filter(|data| glob::matches(pattern, &data.path))
+                .collect::<Vec<_>>();
+            if budget.max_time_ns.is_some() {
+                if let Some(slowest) = matching
+                    .iter()
+                    .filter_map(|data| data.time_ns())
+                    .reduce(f64::max)
+                {
+                    budget.max_time_ns = Some(slowest * (1.0 + margin));
+                }
+            }
+            if budget.min_throughput.is_some() {
+                if let Some(slowest) = matching
+                    .iter()
+                    .filter_map(|data| data.throughput)
+                    .reduce(f64::min)
+                {
+                    budget.min_throughput = Some(slowest / (1.0 + margin));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Enumerate the budgets that apply to a certain benchmark
+    fn matching_budgets<'self_>(
+        &'self_ self,
+        bench_path: &'self_ str,
+    ) -> impl Iterator<Item = (&'self_ str, &'self_ Budget)> + 'self_ {
+        self.budgets
+            .iter()
+            .filter(move |(pattern, _)| glob::matches(pattern, bench_path))
+            .map(|(pattern, budget)| (pattern.as_str(), budget))
+    }
+}
+
+/// Performance limits applying to a set of benchmarks
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Budget {
+    /// Maximal execution time, in nanoseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time_ns: Option<f64>,
+
+    /// Minimal throughput, in bytes or elements per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_throughput: Option<f64>,
+}
+
+/// Outcome of checking benchmark data against performance budgets
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BudgetReport {
+    /// Individual checks that were performed
+    pub checks: Vec<BudgetCheck>,
+}
+//
+impl BudgetReport {
+    /// Truth that all checks passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Enumerate the checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &BudgetCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+
+    /// Only keep the checks of benchmarks that have a certain tag
+    pub fn filter_tagged(&self, tags: &Tags, tag: &str) -> Self {
+        Self {
+            checks: self
+                .checks
+                .iter()
+                .filter(|check| tags.has(&check.benchmark, tag))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Group the checks that failed by tag of the affected benchmark
+    ///
+    /// Failures of benchmarks with several tags are listed under each of them,
+    /// and failures of benchmarks with no tag are not listed.
+    pub fn failures_by_tag<'report>(
+        &'report self,
+        tags: &'report Tags,
+    ) -> BTreeMap<&'report str, Vec<&'report BudgetCheck>> {
+        let mut result = BTreeMap::<_, Vec<_>>::new();
+        for check in self.failures() {
+            for tag in tags.of(&check.benchmark) {
+                result.entry(tag).or_default().push(check);
+            }
+        }
+        result
+    }
+}
+//
+impl Display for BudgetReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{check}")?;
+        }
+        let num_failures = self.failures().count();
+        write!(
+            f,
+            "{} budget checks, {} passed, {num_failures} failed",
+            self.checks.len(),
+            self.checks.len() - num_failures
+        )
+    }
+}
+
+/// Check of one benchmark against one performance limit
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetCheck {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    pub benchmark: String,
+
+    /// Pattern of the budget that the benchmark was checked against
+    pub pattern: String,
+
+    /// Performance limit that was checked
+    pub limit: BudgetLimit,
+
+    /// Statistic that the benchmark was checked with
+    pub statistic: Statistic,
+
+    /// Unit of the benchmark's values
+    pub unit: ValueUnit,
+
+    /// Measured value, if available
+    ///
+    /// This is `None` when checking a throughput limit against a benchmark
+    /// that does not measure throughput, or any limit against a benchmark
+    /// whose values are not durations, in which case the check fails.
+    pub actual: Option<f64>,
+
+    /// Truth that the benchmark is within the limit
+    pub passed: bool,
+}
+//
+impl BudgetCheck {
+    /// Check the latest data of a benchmark against a performance limit
+    fn new(latest: &LatestData, pattern: &str, limit: BudgetLimit, statistic: Statistic) -> Self {
+        let actual = match limit {
+            BudgetLimit::MaxTime(_) => latest.time_ns(),
+            BudgetLimit::MinThroughput(_) => latest.throughput,
+        };
+        let passed = actual.is_some_and(|actual| limit.is_met_by(actual));
+        Self {
+            benchmark: latest.path.clone(),
+            pattern: pattern.to_owned(),
+            limit,
+            statistic,
+            unit: latest.unit.clone(),
+            actual,
+            passed,
+        }
+    }
+
+    /// Perform the same check against another measured value
+    pub(crate) fn with_actual(&self, actual: f64) -> Self {
+        Self {
+            actual: Some(actual),
+            passed: self.limit.is_met_by(actual),
+            ..self.clone()
+        }
+    }
+
+    /// Perform the same check again against the latest data of a benchmark
+    pub(crate) fn recheck(&self, bench: &Benchmark) -> Result<Self, Error> {
+        let latest = LatestData::load(bench, self.statistic)?;
+        Ok(Self::new(
+            &latest,
+            &self.pattern,
+            self.limit,
+            self.statistic,
+        ))
+    }
+}
+//
+impl Display for BudgetCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let status = if self.passed { "PASS" } else { "FAIL" };
+        write!(f, "{status} {} ({}): ", self.benchmark, self.pattern)?;
+        match (self.limit, self.actual) {
+            (BudgetLimit::MaxTime(max), Some(actual)) => write!(
+                f,
+                "{} time {}, limit {}",
+                self.statistic,
+                self.unit.format_value(actual),
+                self.unit.format_value(max)
+            ),
+            (BudgetLimit::MinThroughput(min), Some(actual)) => {
+                write!(f, "throughput {actual:.4e}/s, limit {min:.4e}/s")
+            }
+            (_, None) if !self.unit.is_time() => {
+                write!(f, "values are measured in {}, not time", self.unit)
+            }
+            (BudgetLimit::MaxTime(_), None) => write!(f, "no execution time data"),
+            (BudgetLimit::MinThroughput(_), None) => write!(f, "no throughput data"),
+        }
+    }
+}
+
+/// Performance limit from a [`Budget`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BudgetLimit {
+    /// Maximal execution time, in nanoseconds
+    MaxTime(f64),
+
+    /// Minimal throughput, in bytes or elements per second
+    MinThroughput(f64),
+}
+//
+impl BudgetLimit {
+    /// Truth that a measured value is within this limit
+    fn is_met_by(self, actual: f64) -> bool {
+        match self {
+            Self::MaxTime(max_time_ns) => actual <= max_time_ns,
+            Self::MinThroughput(min_throughput) => actual >= min_throughput,
+        }
+    }
+}
+
+/// Latest data from a benchmark, as needed for budget checks
+struct LatestData {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    path: String,
+
+    /// Unit of the benchmark's values
+    unit: ValueUnit,
+
+    /// Value of the budgets' statistic, in `unit`
+    value: f64,
+
+    /// Throughput in bytes or elements per second, if measured
+    ///
+    /// This is only known for benchmarks whose values are durations.
+    throughput: Option<f64>,
+}
+//
+impl LatestData {
+    /// Load the latest data of a benchmark
+    fn load(bench: &Benchmark, statistic: Statistic) -> Result<Self, Error> {
+        let data = bench.latest_measurement().data()?;
+        let unit = bench.value_unit().clone();
+        let value = data.estimates.get(statistic).point_estimate;
+        let throughput = data
+            .throughput
+            .filter(|_| unit.is_time())
+            .map(|throughput| ThroughputRate::new(&throughput, value).per_second);
+        Ok(Self {
+            path: bench.slash_path(),
+            unit,
+            value,
+            throughput,
+        })
+    }
+
+    /// Execution time in nanoseconds, if the benchmark's values are durations
+    fn time_ns(&self) -> Option<f64> {
+        self.unit.is_time().then_some(self.value)
+    }
+}
+
+/// Truth that a statistic is the default one, which need not be serialized
+fn is_typical(statistic: &Statistic) -> bool {
+    *statistic == Statistic::Typical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latest(unit: ValueUnit, value: f64, throughput: Option<f64>) -> LatestData {
+        LatestData {
+            path: "alloc/parse".to_owned(),
+            unit,
+            value,
+            throughput,
+        }
+    }
+
+    #[test]
+    fn time_limits_apply_to_durations() {
+        let latest = latest(ValueUnit::Nanoseconds, 1500.0, Some(2.0e6));
+        let check = BudgetCheck::new(
+            &latest,
+            "alloc/*",
+            BudgetLimit::MaxTime(2000.0),
+            Statistic::Typical,
+        );
+        assert!(check.passed);
+        assert_eq!(
+            check.to_string(),
+            "PASS alloc/parse (alloc/*): typical time 1.5000 µs, limit 2.0000 µs"
+        );
+    }
+
+    #[test]
+    fn budgets_fail_for_other_units() {
+        let latest = latest(ValueUnit::Bytes, 1500.0, None);
+        assert_eq!(latest.time_ns(), None);
+        for limit in [
+            BudgetLimit::MaxTime(2000.0),
+            BudgetLimit::MinThroughput(1.0),
+        ] {
+            let check = BudgetCheck::new(&latest, "alloc/*", limit, Statistic::Typical);
+            assert!(!check.passed);
+            assert_eq!(check.actual, None);
+            assert_eq!(
+                check.to_string(),
+                "FAIL alloc/parse (alloc/*): values are measured in B, not time"
+            );
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Persistent cache of decoded measurement summaries
+//!
+//! Decoding a measurement file means deserializing all of its raw samples,
+//! even when only its estimates are needed, which adds up for command-line
+//! tools that are repeatedly run on large data directories. A [`SummaryCache`]
+//! keeps the estimates of each measurement in a single file of the `target`
+//! directory, so that later invocations only need to decode the measurement
+//! files that changed since the previous one.
+
+use crate::{decode, layout, writer, Estimates, Measurement};
+use chrono::{DateTime, Utc};
+use criterion::Throughput;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Version of the cache file format
+///
+/// Cache files with another version are discarded, so this must be bumped
+/// whenever the serialized form of [`CacheFile`] changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// Decoded summary of a measurement
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MeasurementSummary {
+    /// Date and time at which the measurement was saved
+    pub datetime: DateTime<Utc>,
+
+    /// Statistical estimates of the measurement
+    pub estimates: Estimates,
+
+    /// Throughput of the measurement, if any
+    pub throughput: Option<Throughput>,
+}
+
+/// Cache of measurement summaries, keyed by measurement file path, size and
+/// modification time
+#[derive(Debug)]
+pub struct SummaryCache {
+    /// Location of the cache file
+    path: PathBuf,
+
+    /// Cached summaries, indexed by measurement file path
+    entries: HashMap<PathBuf, CacheEntry>,
+
+    /// Truth that entries were added since the cache was opened or saved
+    modified: bool,
+}
+//
+impl SummaryCache {
+    /// Open the summary cache of a `target` directory
+    ///
+    /// The cache is stored at [`layout::summary_cache()`]. If the cache file
+    /// does not exist yet, or cannot be decoded because it was written by
+    /// another version of this library, an empty cache is used.
+    pub fn open(target_dir: impl AsRef<Path>) -> io::Result<Self> {
+        let path = layout::summary_cache(target_dir);
+        let entries = match decode::read_cbor::<CacheFile>(&path) {
+            Ok(file) if file.format_version == FORMAT_VERSION => file.entries,
+            Ok(_) => HashMap::new(),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::InvalidData
+                ) =>
+            {
+                HashMap::new()
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path,
+            entries,
+            modified: false,
+        })
+    }
+
+    /// Number of cached summaries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Truth that no summary is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Summary of a measurement
+    ///
+    /// If the measurement file has the same size and modification time as when
+    /// it was cached, the cached summary is returned. Otherwise the file is
+    /// decoded with [`Measurement::data()`], and its summary is added to the
+    /// cache.
+    pub fn summary(&mut self, measurement: &Measurement<'_>) -> io::Result<MeasurementSummary> {
+        let path = measurement.path();
+        let file_metadata = std::fs::metadata(path)?;
+        let size = file_metadata.len();
+        let modified = file_metadata.modified()?;
+        if let Some(entry) = self.entries.get(path) {
+            if entry.size == size && entry.modified == modified {
+                return Ok(entry.summary.clone());
+            }
+        }
+        let data = measurement.data()?;
+        let summary = MeasurementSummary {
+            datetime: data.datetime,
+            estimates: data.estimates,
+            throughput: data.throughput,
+        };
+        self.entries.insert(
+            path.to_owned(),
+            CacheEntry {
+                size,
+                modified,
+                summary: summary.clone(),
+            },
+        );
+        self.modified = true;
+        Ok(summary)
+    }
+
+    /// Forget the summaries of measurement files that no longer exist
+    pub fn prune(&mut self) {
+        let len = self.entries.len();
+        self.entries.retain(|path, _| path.exists());
+        self.modified |= self.entries.len() != len;
+    }
+
+    /// Write the cache back to its file, if it was modified
+    pub fn save(&mut self) -> io::Result<()> {
+        if !self.modified {
+            return Ok(());
+        }
+        let file = CacheFile {
+            format_version: FORMAT_VERSION,
+            entries: std::mem::take(&mut self.entries),
+        };
+        let bytes = serde_cbor::to_vec(&file).map_err(io::Error::other);
+        self.entries = file.entries;
+        std::fs::create_dir_all(self.path.parent().expect("Cache file should have a parent"))?;
+        writer::replace_file(&self.path, &bytes?)?;
+        self.modified = false;
+        Ok(())
+    }
+}
+
+/// Serialized form of a [`SummaryCache`]
+#[derive(Deserialize, Serialize)]
+struct CacheFile {
+    /// Version of the cache file format, see [`FORMAT_VERSION`]
+    format_version: u32,
+
+    /// Cached summaries, indexed by measurement file path
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+/// Summary of a measurement file, along with the file properties that tell
+/// whether it is still valid
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    /// Size of the measurement file, in bytes
+    size: u64,
+
+    /// Modification time of the measurement file
+    modified: SystemTime,
+
+    /// Summary of the measurement
+    summary: MeasurementSummary,
+}
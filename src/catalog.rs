@@ -0,0 +1,108 @@
+//! Shareable index of the benchmarks of a data directory
+//!
+//! A [`Search`] can only be walked once, which does not suit long-lived
+//! applications like dashboards or language servers, where many threads or
+//! async tasks need to look up benchmarks over and over. A [`Catalog`] decodes
+//! the metadata of every benchmark once, and can then be cloned cheaply and
+//! queried concurrently.
+
+use crate::{path_to_slash, BenchmarkId, BenchmarkMetadata, Error, Search};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Cloneable index of benchmarks and their metadata
+///
+/// Clones share the same underlying index, which is never modified. To take
+/// new benchmark data into account, build a new catalog.
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    /// Data root of the search that the catalog was built from
+    data_root: Arc<Path>,
+
+    /// Benchmarks, sorted by `/`-separated path
+    entries: Arc<[CatalogEntry]>,
+}
+//
+impl Catalog {
+    /// Index the benchmarks of a search
+    ///
+    /// Metadata files are decoded in parallel, as in
+    /// [`Search::decode_all_metadata()`], whose errors are propagated.
+    pub fn build(search: Search) -> Result<Self, Error> {
+        let data_root = Arc::from(search.data_root());
+        let mut entries = search
+            .decode_all_metadata(true)?
+            .into_iter()
+            .map(|(path_from_data_root, metadata)| CatalogEntry {
+                path: path_to_slash(&path_from_data_root),
+                path_from_data_root,
+                metadata,
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|entry1, entry2| entry1.path.cmp(&entry2.path));
+        Ok(Self {
+            data_root,
+            entries: entries.into(),
+        })
+    }
+
+    /// Location of the Criterion data root that the catalog was built from
+    pub fn data_root(&self) -> &Path {
+        &self.data_root
+    }
+
+    /// Number of benchmarks in the catalog
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Truth that the catalog contains no benchmark
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Enumerate the benchmarks of the catalog, sorted by path
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Look up a benchmark by `/`-separated path from the data root
+    pub fn get(&self, path: &str) -> Option<&CatalogEntry> {
+        self.entries
+            .binary_search_by(|entry| entry.path.as_str().cmp(path))
+            .ok()
+            .map(|idx| &self.entries[idx])
+    }
+
+    /// Enumerate the benchmarks of a benchmark group
+    ///
+    /// Like [`Dataset::filter_group()`](crate::dataset::Dataset::filter_group),
+    /// this also selects benchmarks that were recorded with
+    /// [`Criterion::bench_with_input()`](criterion::Criterion::bench_with_input)
+    /// and a function name equal to `group`.
+    pub fn group<'catalog>(
+        &'catalog self,
+        group: &'catalog str,
+    ) -> impl Iterator<Item = &'catalog CatalogEntry> + 'catalog {
+        self.entries.iter().filter(move |entry| {
+            let id = &entry.metadata.id;
+            id.group_or_function_id == group
+                && !matches!(id.decode(), Ok(BenchmarkId::BenchFunction(_)))
+        })
+    }
+}
+
+/// Benchmark of a [`Catalog`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatalogEntry {
+    /// `/`-separated path to the benchmark's data directory from the data root
+    pub path: String,
+
+    /// Relative path to the benchmark's data directory from the data root
+    pub path_from_data_root: PathBuf,
+
+    /// Decoded metadata of the benchmark
+    pub metadata: BenchmarkMetadata,
+}
@@ -0,0 +1,390 @@
+//! Low-level CBOR decoding utilities
+//!
+//! Most CBOR decoding is handled by `serde_cbor`, but some operations only need
+//! to look at the beginning of a file, and others need to see details of the
+//! encoding that `serde` abstracts away. This module takes care of them.
+
+use chrono::{DateTime, Utc};
+use std::io::{self, Read};
+
+/// Major type of CBOR unsigned integers
+const MAJOR_UNSIGNED: u8 = 0;
+
+/// Major type of CBOR negative integers
+const MAJOR_NEGATIVE: u8 = 1;
+
+/// Major type of CBOR byte strings
+const MAJOR_BYTES: u8 = 2;
+
+/// Major type of CBOR text strings
+const MAJOR_TEXT: u8 = 3;
+
+/// Major type of CBOR arrays
+const MAJOR_ARRAY: u8 = 4;
+
+/// Major type of CBOR maps
+const MAJOR_MAP: u8 = 5;
+
+/// Major type of CBOR semantic tags
+const MAJOR_TAG: u8 = 6;
+
+/// Major type of CBOR floating-point numbers and simple values
+const MAJOR_SIMPLE: u8 = 7;
+
+/// Initial byte of the "break" stop code of indefinite-length data items
+const BREAK: u8 = 0xff;
+
+/// CBOR semantic tag for RFC 3339 date/time strings
+const TAG_DATETIME: u64 = 0;
+
+/// Maximal length of the strings that we are willing to peek at
+const MAX_PEEKED_STRING: u64 = 64;
+
+/// Maximal nesting depth of the data items that are rendered in diagnostic
+/// notation, which protects against stack overflows on malicious input
+const MAX_DIAGNOSTIC_DEPTH: usize = 128;
+
+/// Decode the leading `datetime` field of a serialized [`MeasurementData`]
+///
+/// cargo-criterion serializes measurement data as a CBOR map whose first entry
+/// is the `datetime` field. This function decodes that entry without reading
+/// the rest of the file, which contains large sample arrays.
+///
+/// Returns `None` if the data does not start with a `datetime` field in the
+/// expected format, in which case the caller should fall back to decoding the
+/// full measurement data.
+///
+/// [`MeasurementData`]: crate::MeasurementData
+pub(crate) fn peek_datetime(mut reader: impl Read) -> io::Result<Option<DateTime<Utc>>> {
+    // Check for a map header, of either definite or indefinite length
+    let Some((MAJOR_MAP, _)) = read_header(&mut reader)? else {
+        return Ok(None);
+    };
+
+    // Check that the first key is "datetime"
+    if read_text(&mut reader)?.as_deref() != Some("datetime") {
+        return Ok(None);
+    }
+
+    // Read the value, skipping the standard date/time tag if present
+    let mut header = read_header(&mut reader)?;
+    if header == Some((MAJOR_TAG, Some(TAG_DATETIME))) {
+        header = read_header(&mut reader)?;
+    }
+    let Some((MAJOR_TEXT, Some(len))) = header else {
+        return Ok(None);
+    };
+    let Some(text) = read_text_body(&mut reader, len)? else {
+        return Ok(None);
+    };
+    Ok(DateTime::parse_from_rfc3339(&text)
+        .ok()
+        .map(|datetime| datetime.to_utc()))
+}
+
+/// Read a text string, if the next data item is a definite-length one
+fn read_text(reader: &mut impl Read) -> io::Result<Option<String>> {
+    match read_header(reader)? {
+        Some((MAJOR_TEXT, Some(len))) => read_text_body(reader, len),
+        _ => Ok(None),
+    }
+}
+
+/// Read the body of a text string of known length, unless it is too long
+fn read_text_body(reader: &mut impl Read, len: u64) -> io::Result<Option<String>> {
+    if len > MAX_PEEKED_STRING {
+        return Ok(None);
+    }
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(String::from_utf8(bytes).ok())
+}
+
+/// Read the header of a CBOR data item
+///
+/// Returns the major type and the argument of the data item, which is `None`
+/// for indefinite-length items. Returns `None` for reserved encodings.
+fn read_header(reader: &mut impl Read) -> io::Result<Option<(u8, Option<u64>)>> {
+    let [initial] = read_bytes(reader)?;
+    Ok(read_argument(reader, initial & 0x1f)?.map(|argument| (initial >> 5, argument)))
+}
+
+/// Read the argument of a CBOR data item, given the additional information
+/// from its initial byte
+///
+/// Returns `Some(None)` for indefinite-length items, and `None` for reserved
+/// encodings.
+fn read_argument(reader: &mut impl Read, additional_info: u8) -> io::Result<Option<Option<u64>>> {
+    Ok(Some(match additional_info {
+        short @ 0..=23 => Some(u64::from(short)),
+        24 => Some(u64::from(read_bytes::<1>(reader)?[0])),
+        25 => Some(u64::from(u16::from_be_bytes(read_bytes(reader)?))),
+        26 => Some(u64::from(u32::from_be_bytes(read_bytes(reader)?))),
+        27 => Some(u64::from_be_bytes(read_bytes(reader)?)),
+        31 => None,
+        _ => return Ok(None),
+    }))
+}
+
+/// Read a fixed number of bytes
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Render a CBOR data item in diagnostic notation, as defined by RFC 8949
+///
+/// Maps, and arrays that contain maps, are laid out over multiple lines with
+/// two spaces of indentation per nesting level. Other data items are rendered
+/// on a single line. Malformed data is reported as an
+/// [`io::ErrorKind::InvalidData`] error.
+pub(crate) fn diagnostic_notation(mut bytes: &[u8]) -> io::Result<String> {
+    let item = render_item(&mut bytes, 0).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            invalid_data("truncated CBOR data")
+        } else {
+            e
+        }
+    })?;
+    if !bytes.is_empty() {
+        return Err(invalid_data("trailing bytes after CBOR data item"));
+    }
+    Ok(item)
+}
+
+/// Render the next data item of a CBOR byte stream in diagnostic notation
+fn render_item(bytes: &mut &[u8], depth: usize) -> io::Result<String> {
+    if depth > MAX_DIAGNOSTIC_DEPTH {
+        return Err(invalid_data("CBOR data is nested too deeply"));
+    }
+    let [initial] = read_bytes(bytes)?;
+    let (major, additional_info) = (initial >> 5, initial & 0x1f);
+    let Some(argument) = read_argument(bytes, additional_info)? else {
+        return Err(invalid_data(format!(
+            "reserved additional information {additional_info}"
+        )));
+    };
+    match (major, argument) {
+        (MAJOR_UNSIGNED, Some(value)) => Ok(value.to_string()),
+        (MAJOR_NEGATIVE, Some(value)) => Ok((-1 - i128::from(value)).to_string()),
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => render_string(bytes, major, len),
+        (MAJOR_BYTES | MAJOR_TEXT, None) => {
+            let mut chunks = Vec::new();
+            while !read_break(bytes)? {
+                let [initial] = read_bytes(bytes)?;
+                let Some(Some(len)) = read_argument(bytes, initial & 0x1f)? else {
+                    return Err(invalid_data("malformed indefinite-length string chunk"));
+                };
+                if initial >> 5 != major {
+                    return Err(invalid_data("mismatched indefinite-length string chunk"));
+                }
+                chunks.push(render_string(bytes, major, len)?);
+            }
+            Ok(format!("(_ {})", chunks.join(", ")))
+        }
+        (MAJOR_ARRAY, len) => {
+            let mut items = Vec::new();
+            while !end_of_container(bytes, len, items.len())? {
+                items.push(render_item(bytes, depth + 1)?);
+            }
+            let marker = if len.is_none() { "_ " } else { "" };
+            if items.iter().any(|item| item.contains('\n')) {
+                Ok(format!("[{marker}\n{}\n]", indent_lines(&items)))
+            } else {
+                Ok(format!("[{marker}{}]", items.join(", ")))
+            }
+        }
+        (MAJOR_MAP, len) => {
+            let mut entries = Vec::new();
+            while !end_of_container(bytes, len, entries.len())? {
+                let key = render_item(bytes, depth + 1)?;
+                let value = render_item(bytes, depth + 1)?;
+                entries.push(format!("{key}: {value}"));
+            }
+            match (len, entries.is_empty()) {
+                (Some(_), true) => Ok("{}".to_owned()),
+                (None, true) => Ok("{_ }".to_owned()),
+                (Some(_), false) => Ok(format!("{{\n{}\n}}", indent_lines(&entries))),
+                (None, false) => Ok(format!("{{_\n{}\n}}", indent_lines(&entries))),
+            }
+        }
+        (MAJOR_TAG, Some(tag)) => Ok(format!("{tag}({})", render_item(bytes, depth + 1)?)),
+        (MAJOR_SIMPLE, argument) => match (additional_info, argument) {
+            (20, _) => Ok("false".to_owned()),
+            (21, _) => Ok("true".to_owned()),
+            (22, _) => Ok("null".to_owned()),
+            (23, _) => Ok("undefined".to_owned()),
+            (0..=19 | 24, Some(value)) => Ok(format!("simple({value})")),
+            (25, Some(bits)) => Ok(render_float(half_to_f64(bits as u16))),
+            (26, Some(bits)) => Ok(render_float(f64::from(f32::from_bits(bits as u32)))),
+            (27, Some(bits)) => Ok(render_float(f64::from_bits(bits))),
+            _ => Err(invalid_data("unexpected break stop code")),
+        },
+        (_, None) => Err(invalid_data(format!(
+            "major type {major} cannot have an indefinite length"
+        ))),
+        _ => unreachable!("CBOR major types are 3-bit numbers"),
+    }
+}
+
+/// Render the body of a definite-length byte or text string
+fn render_string(bytes: &mut &[u8], major: u8, len: u64) -> io::Result<String> {
+    // Check the length before allocating, as it comes from untrusted data
+    if len > bytes.len() as u64 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let (body, rest) = bytes.split_at(len as usize);
+    *bytes = rest;
+    if major == MAJOR_BYTES {
+        let hex = body
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        return Ok(format!("h'{hex}'"));
+    }
+    let text = std::str::from_utf8(body).map_err(invalid_data)?;
+    Ok(serde_json::to_string(text).expect("Strings should be serializable to JSON"))
+}
+
+/// Render a floating-point number
+fn render_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+/// Convert the bits of an IEEE 754 half-precision number to an `f64`
+fn half_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    sign * match exponent {
+        0 => mantissa * 2.0f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1024.0 + mantissa) * 2.0f64.powi(exponent - 25),
+    }
+}
+
+/// Truth that the end of an array or map has been reached
+///
+/// `len` is the number of items of definite-length containers, and `None` for
+/// indefinite-length ones, whose end is marked by a break stop code.
+fn end_of_container(bytes: &mut &[u8], len: Option<u64>, num_items: usize) -> io::Result<bool> {
+    match len {
+        Some(len) => Ok(num_items as u64 >= len),
+        None => read_break(bytes),
+    }
+}
+
+/// Consume the break stop code, if it is the next byte
+fn read_break(bytes: &mut &[u8]) -> io::Result<bool> {
+    match bytes.first() {
+        Some(&BREAK) => {
+            *bytes = &bytes[1..];
+            Ok(true)
+        }
+        Some(_) => Ok(false),
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+/// Lay out rendered items on separate lines, with one level of indentation
+fn indent_lines(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("  {}", item.replace('\n', "\n  ")))
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+/// Wrap an error into an [`io::ErrorKind::InvalidData`] error
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfidenceInterval, Estimate, Estimates, MeasurementData};
+
+    /// Encode a map whose first entry has a text key and a text value,
+    /// optionally tagged
+    fn map_with_text_entry(key: &str, tag: Option<u8>, value: &str) -> Vec<u8> {
+        let mut bytes = vec![0xa1, 0x60 + key.len() as u8];
+        bytes.extend_from_slice(key.as_bytes());
+        bytes.extend(tag);
+        bytes.extend([0x78, value.len() as u8]);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn peek_datetime_of_measurement_data() {
+        let estimate = Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: 0.95,
+                lower_bound: 1.0,
+                upper_bound: 1.0,
+            },
+            point_estimate: 1.0,
+            standard_error: 0.0,
+        };
+        let datetime = "2026-01-02T03:04:05.123456789Z".parse().unwrap();
+        let data = MeasurementData {
+            datetime,
+            iterations: vec![1.0; 100],
+            values: vec![1.0; 100],
+            avg_values: vec![1.0; 100],
+            estimates: Estimates {
+                mean: estimate,
+                median: estimate,
+                median_abs_dev: estimate,
+                slope: None,
+                std_dev: estimate,
+            },
+            throughput: None,
+            changes: None,
+            change_direction: None,
+            history_id: None,
+            history_description: None,
+        };
+        let bytes = serde_cbor::to_vec(&data).unwrap();
+        assert_eq!(peek_datetime(&bytes[..]).unwrap(), Some(datetime));
+        assert_eq!(peek_datetime(&bytes[..64]).unwrap(), Some(datetime));
+    }
+
+    #[test]
+    fn peek_tagged_datetime() {
+        let bytes = map_with_text_entry("datetime", Some(0xc0), "2026-01-02T03:04:05+02:00");
+        let expected = "2026-01-02T01:04:05Z".parse().unwrap();
+        assert_eq!(peek_datetime(&bytes[..]).unwrap(), Some(expected));
+    }
+
+    #[test]
+    fn peek_datetime_rejects_unexpected_headers() {
+        let other_key = map_with_text_entry("iterations", None, "2026-01-02T03:04:05Z");
+        assert_eq!(peek_datetime(&other_key[..]).unwrap(), None);
+        let other_tag = map_with_text_entry("datetime", Some(0xc1), "2026-01-02T03:04:05Z");
+        assert_eq!(peek_datetime(&other_tag[..]).unwrap(), None);
+        let not_a_datetime = map_with_text_entry("datetime", None, "yesterday");
+        assert_eq!(peek_datetime(&not_a_datetime[..]).unwrap(), None);
+        let too_long = map_with_text_entry("datetime", None, &"9".repeat(65));
+        assert_eq!(peek_datetime(&too_long[..]).unwrap(), None);
+        assert_eq!(peek_datetime(&[0x80][..]).unwrap(), None);
+    }
+
+    #[test]
+    fn peek_datetime_reports_truncated_input() {
+        let bytes = map_with_text_entry("datetime", None, "2026-01-02T03:04:05Z");
+        for len in [0, 1, 5, bytes.len() - 1] {
+            let error = peek_datetime(&bytes[..len]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Helpers for running performance checks in continuous integration
+//!
+//! Shared CI machines are noisy, so a benchmark that fails its
+//! [performance budget](crate::budgets) once may well pass if run again.
+//! [`verify_regressions()`] reruns the benchmarks that failed a budget check
+//! and only confirms the failures that reproduce, which avoids failing CI
+//! builds because of a single unlucky run.
+//!
+//! Both reruns and the [`Gate`] below can use robust statistics, selected with
+//! a [`RobustnessMode`], which greatly reduces false alarms on benchmarks with
+//! heavy-tailed noise.
+//!
+//! Alternatively, pull requests can be gated against a curated reference
+//! dataset that is committed to the repository, rather than against the noisy
+//! numbers of the previous CI run. Record it with [`record_reference()`] on a
+//! quiet machine, then check new data with [`compare_against_reference()`].
+//!
+//! When all benchmarks of a large suite are tested for regressions at once,
+//! some will appear to regress by chance alone. A [`Gate`] tests every
+//! benchmark against its baseline, and applies a multiple-comparison
+//! [`Correction`] so that the false-positive rate of the whole suite remains
+//! under control.
+//!
+//! In large repositories, regressions should be brought to the attention of
+//! the teams that own the affected benchmarks. [`route()`] groups regressions
+//! according to the owner [tags](crate::tags) of their benchmarks.
+
+use crate::{
+    budgets::{BudgetCheck, BudgetReport},
+    dataset::{Dataset, LoadReport},
+    history::{BaselineStrategy, RobustnessMode},
+    runner::{self, Selection},
+    snapshot::{Snapshot, SnapshotComparison},
+    stats::{self, EffectSize, MeasurementComparison},
+    tags::Tags,
+    Error, Search,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    io,
+    path::Path,
+};
+
+/// Conventional name of the reference dataset file
+pub const DEFAULT_REFERENCE_FILE_NAME: &str = "perf-reference.ccbor";
+
+/// Configuration of [`verify_regressions()`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RerunOptions {
+    /// Number of times failing benchmarks should be run again
+    ///
+    /// A failure is only confirmed if it reproduces in every rerun.
+    pub reruns: usize,
+
+    /// Extra arguments to be passed to `cargo criterion` on every rerun
+    ///
+    /// These must not contain a `--` separator, see
+    /// [`runner::spawn_selected()`].
+    pub cargo_args: Vec<String>,
+
+    /// How the outcome of the reruns is decided
+    ///
+    /// In [`RobustnessMode::Classic`] mode, a failure is dismissed as soon as
+    /// it passes a rerun. In [`RobustnessMode::Robust`] mode, every rerun is
+    /// performed, and the check is decided on the median of the values
+    /// measured by the original run and the reruns, so that neither a single
+    /// lucky rerun nor a single unlucky one decides the outcome.
+    pub robustness: RobustnessMode,
+}
+//
+impl Default for RerunOptions {
+    fn default() -> Self {
+        Self {
+            reruns: 2,
+            cargo_args: Vec::new(),
+            robustness: RobustnessMode::Classic,
+        }
+    }
+}
+
+/// Record the latest measurement of every benchmark as a reference dataset
+///
+/// Only the latest measurement of each benchmark is kept, so that the file
+/// remains small enough to be committed. It is saved in the portable dataset
+/// format of [`Dataset::save()`]. Files that cannot be decoded are reported as
+/// an error, rather than silently left out of the reference.
+pub fn record_reference(search: Search, path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let dataset = search.collect_all().map_err(LoadReport::into_first_error)?;
+    dataset
+        .latest_per_bench()
+        .save(path)
+        .map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+/// Compare the latest benchmark estimates with a reference dataset file
+///
+/// The file must have been saved with [`record_reference()`] or
+/// [`Dataset::save()`]. See [`snapshot::compare()`](crate::snapshot::compare)
+/// for the meaning of `tolerance` and the structure of the result.
+pub fn compare_against_reference(
+    search: Search,
+    path: impl AsRef<Path>,
+    tolerance: f64,
+) -> Result<SnapshotComparison, Error> {
+    let path = path.as_ref();
+    let reference = Dataset::load(path).map_err(|e| Error::reading(path, e))?;
+    let reference = Snapshot::of_dataset(&reference);
+    Ok(reference.compare(&Snapshot::take(search)?, tolerance))
+}
+
+/// Rerun the benchmarks that failed budget checks, and tell which failures
+/// reproduce
+///
+/// Benchmarks are rerun using [`runner::spawn_selected()`], so this must be
+/// called from the Cargo project whose benchmark data was checked. Once all
+/// reruns are done, each failed check of `gate_result` ends up either
+/// [confirmed](Verification::confirmed), if it failed again in every rerun
+/// (or, in [robust](RerunOptions::robustness) mode, at the median value of all
+/// runs), or [dismissed](Verification::dismissed) otherwise.
+///
+/// Failed checks whose benchmark could not be rerun (e.g. because it was
+/// removed from the benchmark suite) cannot be dismissed, and are thus
+/// confirmed.
+pub fn verify_regressions(
+    gate_result: &BudgetReport,
+    rerun_options: &RerunOptions,
+) -> io::Result<Verification> {
+    let robust = rerun_options.robustness == RobustnessMode::Robust;
+    // Successive states of each failed check, starting with the original one
+    let mut runs = gate_result
+        .failures()
+        .map(|check| vec![check.clone()])
+        .collect::<Vec<_>>();
+    let is_pending =
+        |runs: &Vec<BudgetCheck>| robust || !runs.last().is_some_and(|check| check.passed);
+    for _ in 0..rerun_options.reruns {
+        if !runs.iter().any(is_pending) {
+            break;
+        }
+        let mut selection = Selection::new();
+        for bench in Search::discover()?.find_all() {
+            let bench = bench?;
+            let path = bench.slash_path();
+            if runs
+                .iter()
+                .any(|runs| is_pending(runs) && runs[0].benchmark == path)
+            {
+                selection = selection.id(&bench.metadata()?.id);
+            }
+        }
+        let rerun = runner::spawn_selected(&rerun_options.cargo_args, &selection)?
+            .into_iter()
+            .map(|bench| (bench.slash_path(), bench))
+            .collect::<HashMap<_, _>>();
+        for runs in runs.iter_mut().filter(|runs| is_pending(runs)) {
+            if let Some(bench) = rerun.get(&runs[0].benchmark) {
+                let recheck = runs[0].recheck(bench)?;
+                runs.push(recheck);
+            }
+        }
+    }
+    let mut verification = Verification::default();
+    for runs in runs {
+        let outcome = if robust {
+            median_check(&runs)
+        } else {
+            runs.last()
+                .expect("Every check was run at least once")
+                .clone()
+        };
+        if outcome.passed {
+            verification.dismissed.push(outcome);
+        } else {
+            verification.confirmed.push(outcome);
+        }
+    }
+    Ok(verification)
+}
+
+/// State of a budget check at the median of the values measured by several
+/// runs, or at the latest run if some runs did not measure anything
+fn median_check(runs: &[BudgetCheck]) -> BudgetCheck {
+    let latest = runs.last().expect("Every check was run at least once");
+    match runs
+        .iter()
+        .map(|check| check.actual)
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(mut values) => latest.with_actual(stats::median(&mut values)),
+        None => latest.clone(),
+    }
+}
+
+/// Outcome of [`verify_regressions()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Verification {
+    /// Failed checks that reproduced in every rerun, in their latest state
+    pub confirmed: Vec<BudgetCheck>,
+
+    /// Failed checks that passed in some rerun, in the state of that rerun
+    ///
+    /// In [robust](RerunOptions::robustness) mode, both confirmed and dismissed
+    /// checks are in their state at the median value of all runs.
+    pub dismissed: Vec<BudgetCheck>,
+}
+//
+impl Verification {
+    /// Truth that no failure was confirmed
+    pub fn passed(&self) -> bool {
+        self.confirmed.is_empty()
+    }
+}
+//
+impl Display for Verification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for check in &self.dismissed {
+            writeln!(f, "DISMISSED after rerun: {check}")?;
+        }
+        for check in &self.confirmed {
+            writeln!(f, "CONFIRMED after rerun: {check}")?;
+        }
+        write!(
+            f,
+            "{} failures confirmed, {} dismissed",
+            self.confirmed.len(),
+            self.dismissed.len()
+        )
+    }
+}
+
+/// Statistical regression gate for a whole benchmark suite
+///
+/// Every benchmark's latest measurement is compared with a baseline chosen
+/// by [`baseline`](Self::baseline), using the statistics selected by
+/// [`robustness`](Self::robustness), as in [`stats::compare_with_baseline()`].
+/// The p-values of all comparisons are then adjusted with
+/// [`correction`](Self::correction), and a benchmark is reported as regressed
+/// if it got slower with an adjusted p-value below
+/// [`false_positive_rate`](Self::false_positive_rate), and an effect size of
+/// at least [`min_effect_size`](Self::min_effect_size).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gate {
+    /// Choice of the measurement that the latest one is compared with
+    pub baseline: BaselineStrategy,
+
+    /// Statistics that the measurements are compared with
+    ///
+    /// This defaults to [`RobustnessMode::Classic`], i.e. Welch's t-test on
+    /// the means. Benchmarks with heavy-tailed noise get far fewer spurious
+    /// regressions with [`RobustnessMode::Robust`], which compares medians.
+    pub robustness: RobustnessMode,
+
+    /// Correction applied to the p-values of all benchmarks
+    pub correction: Correction,
+
+    /// Acceptable rate of false positives
+    ///
+    /// Its meaning depends on the [`Correction`]: it applies to individual
+    /// benchmarks without correction, to the whole suite with
+    /// [`Bonferroni`](Correction::Bonferroni), and to the reported regressions
+    /// with [`BenjaminiHochberg`](Correction::BenjaminiHochberg).
+    pub false_positive_rate: f64,
+
+    /// Smallest effect size, according to Cohen's d, that is reported as a
+    /// regression
+    ///
+    /// Benchmarks with very little noise can have statistically significant
+    /// changes that are too small to matter, which this filters out.
+    pub min_effect_size: EffectSize,
+}
+//
+impl Gate {
+    /// Test the latest measurement of every benchmark of a search
+    ///
+    /// Malformed measurement files are handled according to the [decoding
+    /// error policy](Search::on_decode_error) of the search.
+    pub fn check(&self, search: Search) -> Result<GateReport, Error> {
+        let mut comparisons = Vec::new();
+        let mut untested = Vec::new();
+        for bench in search.find_all() {
+            let bench = bench?;
+            match stats::compare_with_baseline(&bench, self.baseline, self.robustness)? {
+                Some(comparison) => comparisons.push((bench.slash_path(), comparison)),
+                None => untested.push(bench.slash_path()),
+            }
+        }
+        let p_values = comparisons
+            .iter()
+            .map(|(_, comparison)| comparison.p_value)
+            .collect::<Vec<_>>();
+        let checks = comparisons
+            .into_iter()
+            .zip(self.correction.adjust(&p_values))
+            .map(|((benchmark, comparison), adjusted_p_value)| GateCheck {
+                regressed: comparison.change() > 0.0
+                    && adjusted_p_value < self.false_positive_rate
+                    && comparison.effect_size() >= self.min_effect_size,
+                benchmark,
+                comparison,
+                adjusted_p_value,
+            })
+            .collect();
+        Ok(GateReport { checks, untested })
+    }
+}
+//
+impl Default for Gate {
+    fn default() -> Self {
+        Self {
+            baseline: BaselineStrategy::default(),
+            robustness: RobustnessMode::Classic,
+            correction: Correction::default(),
+            false_positive_rate: 0.05,
+            min_effect_size: EffectSize::Negligible,
+        }
+    }
+}
+
+/// Multiple-comparison correction applied by a [`Gate`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Correction {
+    /// Use the p-value of each benchmark as is
+    ///
+    /// The false-positive rate is controlled for each benchmark, so a suite of
+    /// N benchmarks is expected to report about N times this rate of spurious
+    /// regressions.
+    None,
+
+    /// Bonferroni correction, which controls the probability that any
+    /// benchmark of the suite is falsely reported as regressed
+    ///
+    /// This is very conservative on large suites, where it can hide real
+    /// regressions.
+    Bonferroni,
+
+    /// Benjamini–Hochberg procedure, which controls the expected fraction of
+    /// false positives among the reported regressions
+    ///
+    /// This is the usual compromise for large suites.
+    #[default]
+    BenjaminiHochberg,
+}
+//
+impl Correction {
+    /// Adjust the p-values of a family of tests, keeping their order
+    pub fn adjust(self, p_values: &[f64]) -> Vec<f64> {
+        let num_tests = p_values.len() as f64;
+        match self {
+            Self::None => p_values.to_vec(),
+            Self::Bonferroni => p_values.iter().map(|p| (p * num_tests).min(1.0)).collect(),
+            Self::BenjaminiHochberg => {
+                let mut order = (0..p_values.len()).collect::<Vec<_>>();
+                order.sort_unstable_by(|&idx1, &idx2| p_values[idx1].total_cmp(&p_values[idx2]));
+                // Going from the highest p-value down keeps adjusted p-values
+                // monotonic, as required by the step-up procedure
+                let mut adjusted = vec![1.0; p_values.len()];
+                let mut running_min = 1.0f64;
+                for (rank, &idx) in order.iter().enumerate().rev() {
+                    running_min = running_min.min(p_values[idx] * num_tests / (rank + 1) as f64);
+                    adjusted[idx] = running_min;
+                }
+                adjusted
+            }
+        }
+    }
+}
+
+/// Outcome of [`Gate::check()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GateReport {
+    /// Benchmarks that were compared with their baseline
+    pub checks: Vec<GateCheck>,
+
+    /// Benchmarks that could not be tested, e.g. because they have no
+    /// measurement matching the baseline strategy
+    pub untested: Vec<String>,
+}
+//
+impl GateReport {
+    /// Truth that no benchmark regressed
+    pub fn passed(&self) -> bool {
+        self.regressions().next().is_none()
+    }
+
+    /// Enumerate the benchmarks that regressed
+    pub fn regressions(&self) -> impl Iterator<Item = &GateCheck> {
+        self.checks.iter().filter(|check| check.regressed)
+    }
+}
+//
+impl Display for GateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{check}")?;
+        }
+        for benchmark in &self.untested {
+            writeln!(f, "UNTESTED {benchmark}")?;
+        }
+        write!(
+            f,
+            "{} regressions among {} tested benchmarks",
+            self.regressions().count(),
+            self.checks.len()
+        )
+    }
+}
+
+/// Outcome of the test of one benchmark by a [`Gate`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GateCheck {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    pub benchmark: String,
+
+    /// Comparison of the latest measurement with the baseline
+    pub comparison: MeasurementComparison,
+
+    /// P-value of the comparison, after multiple-comparison correction
+    pub adjusted_p_value: f64,
+
+    /// Truth that the benchmark is reported as regressed
+    pub regressed: bool,
+}
+//
+impl Display for GateCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {:+.2}% (adjusted p = {:.3}, d = {:+.2})",
+            if self.regressed { "FAIL" } else { "PASS" },
+            self.benchmark,
+            self.comparison.change() * 100.0,
+            self.adjusted_p_value,
+            self.comparison.cohens_d
+        )
+    }
+}
+
+/// Group regressions by team, according to owner tags
+///
+/// Each regression is listed in the section of every [owner
+/// tag](Tags::owners) of its benchmark, along with the people to notify.
+/// Regressions of benchmarks without any owner tag are gathered in a final,
+/// unowned section, so that none goes unnoticed.
+///
+/// Regressions are typically the failures of a [`BudgetReport`], or the
+/// [confirmed](Verification::confirmed) failures of [`verify_regressions()`].
+pub fn route<'check>(
+    regressions: impl IntoIterator<Item = &'check BudgetCheck>,
+    ownership: &Tags,
+) -> Routing {
+    let mut owned = BTreeMap::<&str, Vec<BudgetCheck>>::new();
+    let mut unowned = Vec::new();
+    for check in regressions {
+        let owners = ownership
+            .of(&check.benchmark)
+            .into_iter()
+            .filter(|tag| ownership.owners.contains_key(*tag))
+            .collect::<Vec<_>>();
+        if owners.is_empty() {
+            unowned.push(check.clone());
+        }
+        for tag in owners {
+            owned.entry(tag).or_default().push(check.clone());
+        }
+    }
+    let mut sections = owned
+        .into_iter()
+        .map(|(tag, regressions)| RoutedSection {
+            owner: Some(tag.to_owned()),
+            mentions: ownership.owners[tag].clone(),
+            regressions,
+        })
+        .collect::<Vec<_>>();
+    if !unowned.is_empty() {
+        sections.push(RoutedSection {
+            owner: None,
+            mentions: Vec::new(),
+            regressions: unowned,
+        });
+    }
+    Routing { sections }
+}
+
+/// Outcome of [`route()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Routing {
+    /// Sections of owner tags with regressions, sorted by tag, followed by the
+    /// section of unowned regressions, if any
+    pub sections: Vec<RoutedSection>,
+}
+//
+impl Display for Routing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, section) in self.sections.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{section}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Regressions routed to one owner by [`route()`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutedSection {
+    /// Owner tag, or `None` for regressions of benchmarks without an owner
+    pub owner: Option<String>,
+
+    /// People to notify about these regressions
+    pub mentions: Vec<String>,
+
+    /// Regressions of the benchmarks owned by this owner
+    pub regressions: Vec<BudgetCheck>,
+}
+//
+impl Display for RoutedSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.owner.as_deref().unwrap_or("Unowned"))?;
+        if !self.mentions.is_empty() {
+            write!(f, " (cc {})", self.mentions.join(", "))?;
+        }
+        writeln!(f, ":")?;
+        for check in &self.regressions {
+            writeln!(f, "  {check}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        budgets::BudgetLimit,
+        testing::{bench_id, heavy_tailed, measurement, TempTarget},
+        units::ValueUnit,
+        Statistic,
+    };
+
+    #[test]
+    fn gate_robustness_changes_verdict_on_heavy_tailed_noise() {
+        let target = TempTarget::new("gate-robustness");
+        let write = |name: &str, before: &[f64], after: &[f64]| {
+            let before = measurement("2026-01-01T00:00:00Z", before);
+            let after = measurement("2026-01-02T00:00:00Z", after);
+            target.write_benchmark(&bench_id(name), &[before, after]);
+        };
+        // A few slow outliers, but no change of the typical value
+        write(
+            "outliers",
+            &heavy_tailed(100.0, &[]),
+            &heavy_tailed(100.0, &[130.0; 5]),
+        );
+        // A regression hidden from the mean by a few outliers of the baseline
+        write(
+            "shifted",
+            &heavy_tailed(100.0, &[1000.0; 2]),
+            &heavy_tailed(105.0, &[]),
+        );
+        let regressions = |robustness| {
+            let gate = Gate {
+                robustness,
+                correction: Correction::None,
+                ..Gate::default()
+            };
+            let report = gate.check(target.search()).unwrap();
+            assert_eq!(report.checks.len(), 2);
+            assert!(report.untested.is_empty());
+            report
+                .regressions()
+                .map(|check| check.benchmark.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(regressions(RobustnessMode::Classic), ["outliers"]);
+        assert_eq!(regressions(RobustnessMode::Robust), ["shifted"]);
+    }
+
+    #[test]
+    fn median_check_ignores_one_unlucky_run() {
+        let check = |actual| budget_check("parse", actual);
+        let outcome = median_check(&[check(150.0), check(90.0), check(95.0)]);
+        assert_eq!(outcome.actual, Some(95.0));
+        assert!(outcome.passed);
+        let outcome = median_check(&[check(150.0), check(90.0), check(120.0)]);
+        assert_eq!(outcome.actual, Some(120.0));
+        assert!(!outcome.passed);
+        assert_eq!(median_check(&[check(150.0)]), check(150.0));
+
+        let unmeasured = BudgetCheck {
+            actual: None,
+            passed: false,
+            ..check(90.0)
+        };
+        let outcome = median_check(&[check(150.0), unmeasured.clone()]);
+        assert_eq!(outcome, unmeasured);
+    }
+
+    /// Check of a benchmark against a time limit of 100ns
+    fn budget_check(benchmark: &str, actual: f64) -> BudgetCheck {
+        BudgetCheck {
+            benchmark: benchmark.to_owned(),
+            pattern: "*".to_owned(),
+            limit: BudgetLimit::MaxTime(100.0),
+            statistic: Statistic::Typical,
+            unit: ValueUnit::Nanoseconds,
+            actual: None,
+            passed: false,
+        }
+        .with_actual(actual)
+    }
+
+    #[test]
+    fn route_regressions_to_owners() {
+        let tags = |entries: &[(&str, &[&str])]| {
+            entries
+                .iter()
+                .map(|(key, values)| {
+                    let values = values.iter().map(|value| value.to_string()).collect();
+                    (key.to_string(), values)
+                })
+                .collect()
+        };
+        let ownership = Tags {
+            tags: tags(&[
+                ("parser/*", &["team-parsing", "hot"]),
+                ("parser/json", &["team-json"]),
+                ("render/*", &["slow"]),
+            ]),
+            owners: tags(&[
+                ("team-json", &["@bob", "@carol"]),
+                ("team-parsing", &["@alice"]),
+                ("team-idle", &["@dave"]),
+            ]),
+        };
+        let regressions = ["parser/json", "parser/xml", "render/scene", "misc"]
+            .map(|benchmark| budget_check(benchmark, 150.0));
+        let routing = route(&regressions, &ownership);
+        let sections = routing
+            .sections
+            .iter()
+            .map(|section| {
+                let benchmarks = section.regressions.iter().map(|check| &check.benchmark[..]);
+                (section.owner.as_deref(), benchmarks.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        // Sections follow tag order, owners without regressions are omitted,
+        // and tags that do not designate an owner are ignored
+        assert_eq!(
+            sections,
+            [
+                (Some("team-json"), vec!["parser/json"]),
+                (Some("team-parsing"), vec!["parser/json", "parser/xml"]),
+                (None, vec!["render/scene", "misc"]),
+            ]
+        );
+        assert_eq!(routing.sections[0].mentions, ["@bob", "@carol"]);
+        assert!(routing.sections[2].mentions.is_empty());
+        let text = routing.to_string();
+        assert!(text.starts_with("team-json (cc @bob, @carol):\n  FAIL parser/json"));
+        assert!(text.contains("\n\nUnowned:\n  FAIL render/scene"));
+
+        let owned_only = route(&regressions[..2], &ownership);
+        assert!(owned_only
+            .sections
+            .iter()
+            .all(|section| section.owner.is_some()));
+        assert_eq!(route([], &ownership), Routing::default());
+    }
+
+    fn assert_all_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn corrections_match_reference_adjustments() {
+        // Reference values from R's p.adjust()
+        let p_values = [0.01, 0.04, 0.03, 0.005, 0.2];
+        assert_eq!(Correction::None.adjust(&p_values), p_values);
+        assert_all_close(
+            &Correction::Bonferroni.adjust(&p_values),
+            &[0.05, 0.2, 0.15, 0.025, 1.0],
+        );
+        assert_all_close(
+            &Correction::BenjaminiHochberg.adjust(&p_values),
+            &[0.025, 0.05, 0.05, 0.025, 0.2],
+        );
+    }
+
+    #[test]
+    fn benjamini_hochberg_is_capped_and_monotonic() {
+        assert_all_close(
+            &Correction::BenjaminiHochberg.adjust(&[0.9, 0.95]),
+            &[0.95, 0.95],
+        );
+        assert_all_close(
+            &Correction::BenjaminiHochberg.adjust(&[0.02, 0.01, 0.03]),
+            &[0.03, 0.03, 0.03],
+        );
+        assert!(Correction::BenjaminiHochberg.adjust(&[]).is_empty());
+    }
+}
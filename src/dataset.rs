@@ -0,0 +1,779 @@
+//! In-memory collection of decoded benchmark data
+//!
+//! Walking the data directory with [`Search`] and decoding files one by one is
+//! the most flexible way to access benchmark data, but small analysis scripts
+//! usually just want to load everything into memory and look at it. This is
+//! what [`Search::collect_all()`] does, producing a [`Dataset`].
+//!
+//! Datasets can then be narrowed down or combined using combinators like
+//! [`Dataset::filter_group()`] or [`Dataset::join()`]. Decoded data is shared
+//! between the original dataset and the views produced by these combinators,
+//! so they are cheap even when measurements contain many samples.
+//!
+//! Benchmarks that were rerun within a single cargo-criterion
+//! [session] have several measurements for that session, of
+//! which [`Dataset::resolve_retries()`] keeps a single one according to a
+//! [`RetryPolicy`].
+//!
+//! A dataset can also be saved into a single portable file with
+//! [`Dataset::save()`], which is convenient for sharing benchmark data between
+//! machines and tools without copying a whole `target` directory around.
+
+use crate::{
+    decode::DecodeError,
+    history::{History, HistoryPoint},
+    renames::Renames,
+    session::{self, Session},
+    snapshot::{Snapshot, SnapshotComparison},
+    tags::Tags,
+    units::{ThroughputKind, ValueUnit},
+    BenchmarkId, BenchmarkMetadata, Error, MeasurementData, Search, DATA_ENV_VAR,
+};
+use chrono::{DateTime, Utc};
+use criterion::Throughput;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Compare the latest estimates from two portable dataset files
+///
+/// This loads both files with [`Dataset::load()`], then compares them as
+/// described in [`Dataset::compare()`].
+pub fn compare_files(
+    baseline: impl AsRef<Path>,
+    latest: impl AsRef<Path>,
+    tolerance: f64,
+) -> io::Result<SnapshotComparison> {
+    let baseline = Dataset::load(baseline)?;
+    let latest = Dataset::load(latest)?;
+    Ok(baseline.compare(&latest, tolerance))
+}
+
+/// Version of the portable dataset file format
+///
+/// This must be bumped whenever the serialized form of [`Dataset`] changes in
+/// a way that older versions of this library cannot read.
+const FORMAT_VERSION: u32 = 1;
+
+/// Decoded data from a set of benchmarks
+#[derive(Clone, Debug, Default)]
+pub struct Dataset {
+    /// Benchmarks, in data directory order
+    benchmarks: Vec<Arc<LoadedBenchmark>>,
+}
+//
+impl Dataset {
+    /// Load all benchmark data from a search
+    ///
+    /// See [`Search::collect_all()`].
+    pub(crate) fn collect(search: Search) -> Result<Self, LoadReport> {
+        let mut dataset = Self::default();
+        let mut errors = Vec::new();
+        let data_root = search.data_root().to_owned();
+        for bench in search.find_all() {
+            let bench = match bench {
+                Ok(bench) => bench,
+                Err(error) => {
+                    errors.push(DecodeError {
+                        path: error.path().unwrap_or(&data_root).to_owned(),
+                        error: error.into(),
+                    });
+                    continue;
+                }
+            };
+            let metadata = match bench.metadata() {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    errors.push(DecodeError {
+                        path: bench.metadata.path().to_owned(),
+                        error: error.into(),
+                    });
+                    continue;
+                }
+            };
+            let mut measurements = Vec::new();
+            for measurement in bench.measurements() {
+                let path = measurement.entry.path();
+                match bench.options.decode_error_policy.handle(measurement.data()) {
+                    Some(Ok(data)) => measurements.push(Arc::new(data)),
+                    Some(Err(error)) => errors.push(DecodeError {
+                        path: path.to_owned(),
+                        error: error.into(),
+                    }),
+                    None => {}
+                }
+            }
+            measurements.sort_by_key(|data| data.datetime);
+            dataset.benchmarks.push(Arc::new(LoadedBenchmark {
+                path: bench.slash_path(),
+                metadata,
+                value_unit: bench.value_unit().clone(),
+                measurements,
+            }));
+        }
+        if errors.is_empty() {
+            Ok(dataset)
+        } else {
+            Err(LoadReport { dataset, errors })
+        }
+    }
+
+    /// Locate and load benchmark data from the environment
+    ///
+    /// If the [`DATA_ENV_VAR`] environment variable points to a portable
+    /// dataset file, it is loaded with [`load()`](Self::load()). Otherwise,
+    /// benchmark data is located with [`Search::discover()`] and loaded with
+    /// [`Search::collect_all()`].
+    ///
+    /// Errors that prevent any data from being loaded, like a missing dataset
+    /// file, are reported as a [`LoadReport`] with a single error and an empty
+    /// dataset.
+    pub fn discover() -> Result<Self, LoadReport> {
+        let location = std::env::var_os(DATA_ENV_VAR).map(PathBuf::from);
+        let fatal = |path: PathBuf, error| LoadReport {
+            dataset: Self::default(),
+            errors: vec![DecodeError { path, error }],
+        };
+        match location {
+            Some(path) if path.is_file() => Self::load(&path).map_err(|error| fatal(path, error)),
+            location => match Search::discover() {
+                Ok(search) => search.collect_all(),
+                Err(error) => Err(fatal(location.unwrap_or_else(|| ".".into()), error)),
+            },
+        }
+    }
+
+    /// Save this dataset into a single portable file
+    ///
+    /// The file contains the metadata and all measurements of every benchmark,
+    /// encoded as gzip-compressed CBOR. By convention, it should use the
+    /// `.ccbor` extension. Any pre-existing file at this location will be
+    /// overwritten.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        let portable = PortableDataset {
+            format_version: FORMAT_VERSION,
+            benchmarks: self.benchmarks.clone(),
+        };
+        serde_cbor::to_writer(&mut encoder, &portable).map_err(io::Error::other)?;
+        encoder.finish()?.flush()
+    }
+
+    /// Load a dataset from a file produced by [`save()`](Self::save())
+    ///
+    /// Malformed files, and files produced by an incompatible version of this
+    /// library, are reported as errors of kind [`io::ErrorKind::InvalidData`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let decoder = GzDecoder::new(BufReader::new(File::open(path)?));
+        let portable: PortableDataset = serde_cbor::from_reader(decoder)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if portable.format_version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported dataset format version {} (expected {FORMAT_VERSION})",
+                    portable.format_version
+                ),
+            ));
+        }
+        Ok(Self {
+            benchmarks: portable.benchmarks,
+        })
+    }
+
+    /// Compare the latest estimates of this dataset with those of a newer one
+    ///
+    /// Estimates are considered to have changed if they differ by more than
+    /// the relative `tolerance` (e.g. 0.05 for 5%), as in
+    /// [`snapshot::compare()`](crate::snapshot::compare()).
+    pub fn compare(&self, latest: &Dataset, tolerance: f64) -> SnapshotComparison {
+        Snapshot::of_dataset(self).compare(&Snapshot::of_dataset(latest), tolerance)
+    }
+
+    /// Enumerate the benchmarks of this dataset
+    pub fn benchmarks(&self) -> impl Iterator<Item = &LoadedBenchmark> + '_ {
+        self.benchmarks.iter().map(|bench| &**bench)
+    }
+
+    /// Look up a benchmark by `/`-separated path from the data root
+    pub fn get(&self, path: &str) -> Option<&LoadedBenchmark> {
+        self.benchmarks().find(|bench| bench.path == path)
+    }
+
+    /// Number of benchmarks in this dataset
+    pub fn len(&self) -> usize {
+        self.benchmarks.len()
+    }
+
+    /// Truth that this dataset contains no benchmark
+    pub fn is_empty(&self) -> bool {
+        self.benchmarks.is_empty()
+    }
+
+    /// Only keep benchmarks that match a certain predicate
+    pub fn filter(&self, mut predicate: impl FnMut(&LoadedBenchmark) -> bool) -> Self {
+        Self {
+            benchmarks: self
+                .benchmarks
+                .iter()
+                .filter(|bench| predicate(bench))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Only keep benchmarks from the benchmark group called `group`
+    ///
+    /// Due to an ambiguity in Criterion's metadata schema, this also keeps
+    /// benchmarks that were recorded with [`Criterion::bench_with_input()`] and
+    /// a function name equal to `group`. See
+    /// [`BenchmarkId::AmbiguousFromParameter`] for details.
+    ///
+    /// [`Criterion::bench_with_input()`]: criterion::Criterion::bench_with_input
+    pub fn filter_group(&self, group: &str) -> Self {
+        self.filter(|bench| {
+            let id = &bench.metadata.id;
+            id.group_or_function_id == group
+                && !matches!(id.decode(), Ok(BenchmarkId::BenchFunction(_)))
+        })
+    }
+
+    /// Only keep benchmarks that have a certain tag
+    pub fn filter_tagged(&self, tags: &Tags, tag: &str) -> Self {
+        self.filter(|bench| tags.has(&bench.path, tag))
+    }
+
+    /// Only keep measurements that were saved at or after `datetime`
+    ///
+    /// Benchmarks with no measurement left are dropped.
+    pub fn since(&self, datetime: DateTime<Utc>) -> Self {
+        self.map_measurements(|measurements| {
+            let start = measurements.partition_point(|data| data.datetime < datetime);
+            measurements[start..].to_vec()
+        })
+    }
+
+    /// Only keep the latest measurement of each benchmark
+    pub fn latest_per_bench(&self) -> Self {
+        self.map_measurements(|measurements| measurements.last().cloned().into_iter().collect())
+    }
+
+    /// Combine this dataset with another one
+    ///
+    /// Benchmarks that only appear in one dataset are kept as is, with the
+    /// benchmarks of `self` coming first. For benchmarks that appear in both
+    /// datasets, the metadata of `self` is kept and the measurements are
+    /// merged. Measurements with identical timestamps are considered to be
+    /// duplicates, in which case the one from `self` is kept.
+    pub fn join(&self, other: &Dataset) -> Self {
+        let mut benchmarks = self.benchmarks.clone();
+        let positions = benchmarks
+            .iter()
+            .enumerate()
+            .map(|(idx, bench)| (bench.path.clone(), idx))
+            .collect::<HashMap<_, _>>();
+        for bench in &other.benchmarks {
+            let Some(&idx) = positions.get(&bench.path) else {
+                benchmarks.push(bench.clone());
+                continue;
+            };
+            let mut measurements = benchmarks[idx].measurements.clone();
+            measurements.extend(bench.measurements.iter().cloned());
+            // Stable sort keeps measurements from `self` first within each
+            // run of identical timestamps, so that deduplication keeps them
+            measurements.sort_by_key(|data| data.datetime);
+            measurements.dedup_by_key(|data| data.datetime);
+            benchmarks[idx] = Arc::new(LoadedBenchmark {
+                measurements,
+                ..(*benchmarks[idx]).clone()
+            });
+        }
+        Self { benchmarks }
+    }
+
+    /// Use the same kind of byte throughput across each benchmark's history
+    ///
+    /// Criterion's [`Throughput::Bytes`] and [`Throughput::BytesDecimal`]
+    /// measure the same quantity and only differ in how they are displayed,
+    /// but a benchmark that switched from one to the other would otherwise
+    /// appear to have changed its throughput, e.g. in
+    /// [`detect_discontinuities()`](crate::history::detect_discontinuities).
+    /// This rewrites the byte throughputs of each benchmark's measurements to
+    /// the kind used by its latest measurement, keeping their amount of bytes.
+    pub fn normalize_throughput(&self) -> Self {
+        self.map_measurements(|measurements| {
+            let latest_kind = measurements
+                .last()
+                .and_then(|data| data.throughput.as_ref())
+                .map(|throughput| ThroughputKind::of(throughput).0);
+            measurements
+                .iter()
+                .map(|data| {
+                    let bytes = match (latest_kind, &data.throughput) {
+                        (Some(ThroughputKind::Bytes), Some(Throughput::BytesDecimal(bytes))) => {
+                            Throughput::Bytes(*bytes)
+                        }
+                        (Some(ThroughputKind::BytesDecimal), Some(Throughput::Bytes(bytes))) => {
+                            Throughput::BytesDecimal(*bytes)
+                        }
+                        _ => return data.clone(),
+                    };
+                    Arc::new(MeasurementData {
+                        throughput: Some(bytes),
+                        ..(**data).clone()
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Attribute measurements of renamed benchmarks to their current name
+    ///
+    /// Each measurement is moved to the benchmark path that
+    /// [`Renames::resolve()`] gives for it. Measurements that are moved to a
+    /// benchmark of this dataset are merged into it as in
+    /// [`join()`](Self::join()), otherwise a new benchmark is created with the
+    /// metadata of the benchmark that the measurements were recorded under.
+    pub fn apply_renames(&self, renames: &Renames) -> Self {
+        let mut kept = Vec::with_capacity(self.benchmarks.len());
+        let mut moved = Vec::<LoadedBenchmark>::new();
+        for bench in &self.benchmarks {
+            let mut staying = Vec::with_capacity(bench.measurements.len());
+            for data in &bench.measurements {
+                let path = renames.resolve(&bench.path, data.datetime);
+                if path == bench.path {
+                    staying.push(data.clone());
+                    continue;
+                }
+                match moved.iter_mut().find(|moved| moved.path == path) {
+                    Some(target) => target.measurements.push(data.clone()),
+                    None => moved.push(LoadedBenchmark {
+                        path: path.to_owned(),
+                        measurements: vec![data.clone()],
+                        ..(**bench).clone()
+                    }),
+                }
+            }
+            if staying.len() == bench.measurements.len() {
+                kept.push(bench.clone());
+            } else if !staying.is_empty() {
+                kept.push(Arc::new(LoadedBenchmark {
+                    measurements: staying,
+                    ..(**bench).clone()
+                }));
+            }
+        }
+        // Moved measurements may come from several benchmarks
+        for bench in &mut moved {
+            bench.measurements.sort_by_key(|data| data.datetime);
+        }
+        Self { benchmarks: kept }.join(&Self {
+            benchmarks: moved.into_iter().map(Arc::new).collect(),
+        })
+    }
+
+    /// Only keep one measurement per benchmark and session
+    ///
+    /// When a flaky benchmark is rerun within a session, its newest
+    /// measurement is not necessarily the most representative one. This groups
+    /// the measurements of each benchmark by the session during which they
+    /// were taken, as determined by [`session::session_of()`], and keeps the
+    /// one that the benchmark's [policy](RetryPolicies::of) selects. Later
+    /// comparisons and reports then use that measurement. Measurements that do
+    /// not belong to any session, such as those of runs that started long after
+    /// the latest recorded session, are left alone.
+    ///
+    /// `sessions` must be sorted from oldest to newest, as returned by
+    /// [`session::sessions()`].
+    pub fn resolve_retries(&self, sessions: &[Session], policies: &RetryPolicies) -> Self {
+        let benchmarks = self
+            .benchmarks
+            .iter()
+            .map(|bench| {
+                let policy = policies.of(&bench.path);
+                let mut measurements = Vec::with_capacity(bench.measurements.len());
+                let mut retries = Vec::<Arc<MeasurementData>>::new();
+                let mut current_session = None;
+                for data in &bench.measurements {
+                    let session = session::session_of(sessions, data.datetime);
+                    if session.is_none() || session != current_session {
+                        measurements.extend(policy.select(&retries));
+                        retries.clear();
+                    }
+                    current_session = session;
+                    if session.is_some() {
+                        retries.push(data.clone());
+                    } else {
+                        measurements.push(data.clone());
+                    }
+                }
+                measurements.extend(policy.select(&retries));
+                Arc::new(LoadedBenchmark {
+                    measurements,
+                    ..(**bench).clone()
+                })
+            })
+            .collect();
+        Self { benchmarks }
+    }
+
+    /// Transform the measurement list of each benchmark, dropping benchmarks
+    /// whose measurement list ends up empty
+    fn map_measurements(
+        &self,
+        mut transform: impl FnMut(&[Arc<MeasurementData>]) -> Vec<Arc<MeasurementData>>,
+    ) -> Self {
+        let benchmarks = self
+            .benchmarks
+            .iter()
+            .filter_map(|bench| {
+                let measurements = transform(&bench.measurements);
+                if measurements.is_empty() {
+                    return None;
+                }
+                Some(Arc::new(LoadedBenchmark {
+                    path: bench.path.clone(),
+                    metadata: bench.metadata.clone(),
+                    value_unit: bench.value_unit.clone(),
+                    measurements,
+                }))
+            })
+            .collect();
+        Self { benchmarks }
+    }
+}
+
+/// Measurement that should be kept when a benchmark was measured several times
+/// within a session, see [`Dataset::resolve_retries()`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetryPolicy {
+    /// Keep the measurement with the lowest typical estimate, assuming that
+    /// noise can only make a benchmark slower
+    TakeBest,
+
+    /// Keep the measurement with the median typical estimate, or the lower of
+    /// both middle measurements for an even number of measurements
+    TakeMedianRun,
+
+    /// Keep the newest measurement, as cargo-criterion itself does
+    #[default]
+    TakeLast,
+}
+//
+impl RetryPolicy {
+    /// Select one of the measurements of a session, if any
+    ///
+    /// Measurements must be sorted from oldest to newest.
+    fn select(self, retries: &[Arc<MeasurementData>]) -> Option<Arc<MeasurementData>> {
+        let typical = |data: &Arc<MeasurementData>| data.estimates.typical().point_estimate;
+        let selected = match self {
+            Self::TakeBest => retries
+                .iter()
+                .min_by(|data1, data2| typical(data1).total_cmp(&typical(data2))),
+            Self::TakeMedianRun => {
+                let mut sorted = retries.iter().collect::<Vec<_>>();
+                sorted.sort_by(|data1, data2| typical(data1).total_cmp(&typical(data2)));
+                sorted.get(sorted.len().saturating_sub(1) / 2).copied()
+            }
+            Self::TakeLast => retries.last(),
+        };
+        selected.cloned()
+    }
+}
+
+/// [`RetryPolicy`] of each benchmark of a dataset
+///
+/// Benchmarks use the policy specified for their `/`-separated path in
+/// `benchmarks` if any, otherwise the `default` policy.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RetryPolicies {
+    /// Policy of benchmarks that have no specific policy
+    #[serde(default)]
+    pub default: RetryPolicy,
+
+    /// Policies of individual benchmarks, indexed by benchmark path
+    #[serde(default)]
+    pub benchmarks: BTreeMap<String, RetryPolicy>,
+}
+//
+impl RetryPolicies {
+    /// Policy of the benchmark with a certain `/`-separated path
+    pub fn of(&self, benchmark: &str) -> RetryPolicy {
+        self.benchmarks
+            .get(benchmark)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Serialized form of a [`Dataset`]
+#[derive(Deserialize, Serialize)]
+struct PortableDataset {
+    /// Version of the file format, see [`FORMAT_VERSION`]
+    format_version: u32,
+
+    /// Benchmarks, in data directory order
+    benchmarks: Vec<Arc<LoadedBenchmark>>,
+}
+
+/// Decoded data from one benchmark
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LoadedBenchmark {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    pub path: String,
+
+    /// Contents of the `benchmark.cbor` file
+    pub metadata: BenchmarkMetadata,
+
+    /// Unit of the values recorded by the benchmark
+    pub value_unit: ValueUnit,
+
+    /// Decoded measurements, from oldest to newest
+    ///
+    /// Measurements are shared between a [`Dataset`] and the views derived
+    /// from it, so they are not copied when a view is created.
+    pub measurements: Vec<Arc<MeasurementData>>,
+}
+//
+impl LoadedBenchmark {
+    /// Latest measurement, if any
+    pub fn latest(&self) -> Option<&MeasurementData> {
+        self.measurements.last().map(|data| &**data)
+    }
+
+    /// Measurement history of this benchmark
+    pub fn history(&self) -> History {
+        self.measurements
+            .iter()
+            .map(|data| HistoryPoint::new(data))
+            .collect()
+    }
+}
+
+/// Errors that occured while loading a [`Dataset`]
+///
+/// Loading does not stop at the first error, so this also contains all the
+/// data that could be loaded successfully.
+#[derive(Debug)]
+pub struct LoadReport {
+    /// Data that could be loaded despite the errors
+    pub dataset: Dataset,
+
+    /// Errors that occured while walking the data directory or decoding files
+    pub errors: Vec<DecodeError>,
+}
+//
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to load {} file(s)", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n- {error}")?;
+        }
+        Ok(())
+    }
+}
+//
+impl std::error::Error for LoadReport {}
+//
+impl LoadReport {
+    /// First error of this report, as the [`Error`] that it originally was
+    pub(crate) fn into_first_error(self) -> Error {
+        let DecodeError { path, error } = self
+            .errors
+            .into_iter()
+            .next()
+            .expect("Load reports should contain at least one error");
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = error.into_inner().expect("Checked above");
+            *inner.downcast::<Error>().expect("Checked above")
+        } else {
+            Error::reading(path, error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfidenceInterval, Estimate, Estimates, RawBenchmarkId};
+
+    fn estimate(point_estimate: f64) -> Estimate {
+        Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: 0.95,
+                lower_bound: point_estimate * 0.9,
+                upper_bound: point_estimate * 1.1,
+            },
+            point_estimate,
+            standard_error: point_estimate * 0.05,
+        }
+    }
+
+    fn measurement(datetime: &str, typical: f64) -> Arc<MeasurementData> {
+        Arc::new(MeasurementData {
+            datetime: datetime.parse().unwrap(),
+            iterations: vec![1.0, 2.0, 3.0],
+            values: vec![typical, 2.0 * typical, 3.0 * typical],
+            avg_values: vec![typical; 3],
+            estimates: Estimates {
+                mean: estimate(typical),
+                median: estimate(typical),
+                median_abs_dev: estimate(0.0),
+                slope: Some(estimate(typical)),
+                std_dev: estimate(0.0),
+            },
+            throughput: None,
+            changes: None,
+            change_direction: None,
+            history_id: None,
+            history_description: None,
+        })
+    }
+
+    fn benchmark(path: &str) -> Arc<LoadedBenchmark> {
+        Arc::new(LoadedBenchmark {
+            path: path.to_owned(),
+            metadata: BenchmarkMetadata {
+                id: RawBenchmarkId {
+                    group_or_function_id: path.to_owned(),
+                    function_id_in_group: None,
+                    value_str: None,
+                    throughput: None,
+                },
+                latest_record: PathBuf::from("measurement_260102000000.cbor"),
+            },
+            value_unit: ValueUnit::Nanoseconds,
+            measurements: vec![
+                measurement("2026-01-01T00:00:00Z", 10.0),
+                measurement("2026-01-02T00:00:00Z", 12.5),
+            ],
+        })
+    }
+
+    #[test]
+    fn filter_group_tolerates_invalid_ids() {
+        let mut invalid = (*benchmark("group/invalid")).clone();
+        invalid.metadata.id = RawBenchmarkId {
+            group_or_function_id: "group".to_owned(),
+            function_id_in_group: None,
+            value_str: None,
+            throughput: Some(Throughput::Bytes(64)),
+        };
+        let dataset = Dataset {
+            benchmarks: vec![benchmark("group"), Arc::new(invalid)],
+        };
+        let group = dataset.filter_group("group");
+        let paths = group.benchmarks().map(|bench| &bench.path[..]);
+        assert_eq!(paths.collect::<Vec<_>>(), ["group/invalid"]);
+    }
+
+    #[test]
+    fn normalize_throughput_follows_latest_byte_kind() {
+        let with_throughput = |data: &Arc<MeasurementData>, throughput| {
+            Arc::new(MeasurementData {
+                throughput: Some(throughput),
+                ..(**data).clone()
+            })
+        };
+        let mut bench = (*benchmark("alpha")).clone();
+        bench.measurements = vec![
+            with_throughput(&bench.measurements[0], Throughput::Bytes(64)),
+            with_throughput(&bench.measurements[1], Throughput::BytesDecimal(128)),
+        ];
+        let mut elements = (*benchmark("beta")).clone();
+        elements.measurements = vec![
+            with_throughput(&elements.measurements[0], Throughput::Bytes(64)),
+            with_throughput(&elements.measurements[1], Throughput::Elements(8)),
+        ];
+        let dataset = Dataset {
+            benchmarks: vec![Arc::new(bench), Arc::new(elements)],
+        };
+
+        let normalized = dataset.normalize_throughput();
+        let throughputs = |path| {
+            normalized
+                .get(path)
+                .unwrap()
+                .measurements
+                .iter()
+                .map(|data| data.throughput.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            throughputs("alpha"),
+            [Throughput::BytesDecimal(64), Throughput::BytesDecimal(128)]
+        );
+        assert_eq!(
+            throughputs("beta"),
+            [Throughput::Bytes(64), Throughput::Elements(8)]
+        );
+    }
+
+    #[test]
+    fn resolve_retries_only_merges_runs_of_a_session() {
+        let mut bench = (*benchmark("alpha")).clone();
+        bench.measurements = vec![
+            measurement("2026-01-01T00:10:00Z", 12.0),
+            measurement("2026-01-01T00:20:00Z", 10.0),
+            measurement("2026-01-01T00:30:00Z", 11.0),
+            measurement("2026-01-03T00:00:00Z", 14.0),
+            measurement("2026-01-04T00:00:00Z", 13.0),
+        ];
+        let dataset = Dataset {
+            benchmarks: vec![Arc::new(bench)],
+        };
+        let sessions = [Session::new(
+            Path::new("target"),
+            "2026-01-01T00:00:00Z".parse().unwrap(),
+        )];
+        let policies = RetryPolicies {
+            default: RetryPolicy::TakeBest,
+            ..RetryPolicies::default()
+        };
+
+        let resolved = dataset.resolve_retries(&sessions, &policies);
+        let typicals = resolved
+            .get("alpha")
+            .unwrap()
+            .measurements
+            .iter()
+            .map(|data| data.estimates.typical().point_estimate)
+            .collect::<Vec<_>>();
+        assert_eq!(typicals, [10.0, 14.0, 13.0]);
+    }
+
+    #[test]
+    fn saving_twice_gives_identical_files() {
+        let dataset = Dataset {
+            benchmarks: vec![benchmark("alpha"), benchmark("beta")],
+        };
+        let dir = std::env::temp_dir();
+        let prefix = format!("criterion-cbor-dataset-{}", std::process::id());
+        let path1 = dir.join(format!("{prefix}-1.ccbor"));
+        let path2 = dir.join(format!("{prefix}-2.ccbor"));
+        dataset.save(&path1).unwrap();
+        dataset.save(&path2).unwrap();
+        let bytes1 = std::fs::read(&path1).unwrap();
+        let bytes2 = std::fs::read(&path2).unwrap();
+        let loaded = Dataset::load(&path1).unwrap();
+        std::fs::remove_file(&path1).unwrap();
+        std::fs::remove_file(&path2).unwrap();
+
+        assert_eq!(bytes1, bytes2);
+        let paths = loaded.benchmarks().map(|bench| &bench.path[..]);
+        assert_eq!(paths.collect::<Vec<_>>(), ["alpha", "beta"]);
+        assert_eq!(
+            loaded.get("beta").unwrap().latest(),
+            dataset.get("beta").unwrap().latest()
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! Handling of malformed CBOR files
+//!
+//! Criterion data directories can contain malformed files, for example when a
+//! benchmark run was interrupted while writing them. Methods which decode a
+//! single file, like [`Benchmark::metadata()`](crate::Benchmark::metadata),
+//! report such files as an [`Error::Decode`], which converts into an
+//! [`io::Error`] of kind [`io::ErrorKind::InvalidData`].
+//! For operations that decode many files, you can instead choose a
+//! [`DecodeErrorPolicy`] via
+//! [`Search::on_decode_error()`](crate::Search::on_decode_error).
+//!
+//! By default, decoding is lenient and accepts any file that can be
+//! deserialized into this library's data types. Users who want to detect
+//! upstream format changes or corrupted data early can instead opt into
+//! stricter validation using [`DecodeOptions`]. A lighter-weight alternative is
+//! to look at the [`FormatHint`] of each benchmark, which tells which
+//! generation of cargo-criterion likely produced its data.
+//!
+//! Data that does not come from a local data directory, like files downloaded
+//! from a shared cache, can be decoded from memory with
+//! [`MeasurementData::from_slice()`] and [`BenchmarkMetadata::from_slice()`],
+//! which never panic on malformed input. The `fuzz` directory of this
+//! repository holds cargo-fuzz targets that exercise them.
+
+use crate::{cbor, layout, validation, BenchmarkMetadata, Error, MeasurementData};
+use serde::de::DeserializeOwned;
+use serde_cbor::Value;
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// What should happen when a malformed CBOR file is encountered
+///
+/// This policy applies to operations that decode many files:
+///
+/// - The benchmark iterators of [`Search`](crate::Search), which decode
+///   the metadata of each benchmark when a policy other than `Fail` is used.
+/// - [`Benchmark::decoded_measurements()`](crate::Benchmark::decoded_measurements)
+///   and the higher-level operations built on top of it, like
+///   [`History::load()`](crate::history::History::load).
+#[derive(Clone, Debug, Default)]
+pub enum DecodeErrorPolicy {
+    /// Report decoding errors to the caller, who must handle them
+    #[default]
+    Fail,
+
+    /// Silently skip malformed files
+    Skip,
+
+    /// Skip malformed files, but record the associated errors so that they can
+    /// be inspected after the fact
+    SkipAndCollect(DecodeErrors),
+}
+//
+impl DecodeErrorPolicy {
+    /// Truth that malformed files should be skipped
+    pub fn skips_errors(&self) -> bool {
+        !matches!(self, Self::Fail)
+    }
+
+    /// Apply this policy to the result of decoding a file
+    ///
+    /// Returns `None` if the file should be skipped.
+    pub(crate) fn handle<T>(&self, result: Result<T, Error>) -> Option<Result<T, Error>> {
+        match (self, result) {
+            (Self::Fail, result) | (_, result @ Ok(_)) => Some(result),
+            (Self::Skip, Err(Error::Decode { .. })) => None,
+            (Self::SkipAndCollect(errors), Err(Error::Decode { path, source })) => {
+                errors.push(DecodeError {
+                    path,
+                    error: source,
+                });
+                None
+            }
+            (Self::Skip | Self::SkipAndCollect(_), Err(error)) => Some(Err(error)),
+        }
+    }
+}
+
+/// Shared collection of decoding errors
+///
+/// This is used with [`DecodeErrorPolicy::SkipAndCollect`]. Keep a clone of it
+/// around in order to retrieve the errors after iterating over benchmark data.
+#[derive(Clone, Debug, Default)]
+pub struct DecodeErrors(Arc<Mutex<Vec<DecodeError>>>);
+//
+impl DecodeErrors {
+    /// Set up an empty error collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of errors collected so far
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Truth that no errors were collected so far
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Extract the errors collected so far
+    pub fn take(&self) -> Vec<DecodeError> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Record a new error
+    fn push(&self, error: DecodeError) {
+        self.lock().push(error);
+    }
+
+    /// Access the inner error list
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<DecodeError>> {
+        // Errors are only pushed after being fully built, so a panic while the
+        // lock is held cannot leave the list in an inconsistent state.
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Error that occured while decoding a CBOR file
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Path to the malformed file
+    pub path: PathBuf,
+
+    /// Underlying error
+    pub error: io::Error,
+}
+//
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to decode {}: {}",
+            self.path.display(),
+            self.error
+        )
+    }
+}
+//
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Options that control how CBOR files are decoded
+///
+/// These are set up via
+/// [`Search::decode_options()`](crate::Search::decode_options).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DecodeOptions {
+    /// Truth that strict validation is enabled
+    strict: bool,
+}
+//
+impl DecodeOptions {
+    /// Start from the default, lenient decoding options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable strict validation of decoded data
+    ///
+    /// In strict mode, on top of being deserializable, benchmark metadata and
+    /// measurement files must meet the following requirements:
+    ///
+    /// - They may not contain any field that this library does not know about,
+    ///   which would hint at a change of cargo-criterion's data format.
+    /// - Benchmark metadata must have a benchmark identifier that Criterion can
+    ///   produce, and its latest record must point to a measurement file.
+    /// - Measurement data may not have any of the problems detected by
+    ///   [`validation::measurement_issues()`], like non-finite or negative
+    ///   samples, unsorted iteration counts, non-finite estimates, or
+    ///   confidence levels outside of the `0.0..1.0` range.
+    ///
+    /// Files that do not meet these requirements are reported as errors of
+    /// kind [`io::ErrorKind::InvalidData`], just like malformed files, so they
+    /// are also subjected to the [`DecodeErrorPolicy`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Truth that strict validation is enabled
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+}
+
+/// Best guess of the cargo-criterion version that produced benchmark data
+///
+/// See [`Benchmark::format_hint()`](crate::Benchmark::format_hint).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FormatHint {
+    /// The producing cargo-criterion version was recorded in a marker file by
+    /// [`writer::record_format_version()`](crate::writer::record_format_version)
+    Recorded(String),
+
+    /// Measurements do not have the `history_id` and `history_description`
+    /// fields, which means that they predate cargo-criterion 1.1
+    Legacy,
+
+    /// Measurements have the fields that this library expects
+    Current,
+
+    /// Measurements have a field that this library does not know about, which
+    /// means that they were likely produced by a newer cargo-criterion
+    Newer {
+        /// Path of the first unknown field within the measurement file
+        unknown_field: String,
+    },
+}
+//
+impl FormatHint {
+    /// Truth that data with these two hints can be compared without mixing up
+    /// data format semantics
+    ///
+    /// Recorded versions are compatible if they have the same major and minor
+    /// version. Heuristic hints are compatible if they are equal. A recorded
+    /// version and a heuristic hint are assumed to be compatible, as there is
+    /// not enough information to tell otherwise.
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Recorded(v1), Self::Recorded(v2)) => {
+                let major_minor =
+                    |version: &str| version.split('.').take(2).collect::<Vec<_>>().join(".");
+                major_minor(v1) == major_minor(v2)
+            }
+            (Self::Recorded(_), _) | (_, Self::Recorded(_)) => true,
+            (Self::Newer { .. }, Self::Newer { .. }) => true,
+            (hint1, hint2) => hint1 == hint2,
+        }
+    }
+}
+//
+impl Display for FormatHint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Recorded(version) => write!(f, "cargo-criterion {version}"),
+            Self::Legacy => f.write_str("cargo-criterion < 1.1"),
+            Self::Current => f.write_str("cargo-criterion >= 1.1"),
+            Self::Newer { unknown_field } => {
+                write!(f, "newer cargo-criterion (unknown field {unknown_field})")
+            }
+        }
+    }
+}
+
+/// Render the contents of a CBOR file in diagnostic notation
+///
+/// This human-readable notation, defined by RFC 8949, shows details of the
+/// encoding that decoding into this library's data types hides, like the order
+/// of map keys, integer vs floating-point encodings, or semantic tags. It is
+/// thus useful for investigating questions about cargo-criterion's data format.
+///
+/// Malformed files are reported as [`io::ErrorKind::InvalidData`] errors.
+pub fn diagnostic_notation(path: impl AsRef<Path>) -> io::Result<String> {
+    cbor::diagnostic_notation(&std::fs::read(path)?)
+}
+
+/// Guess the format generation of a measurement file from its fields
+pub(crate) fn measurement_format(path: &Path) -> io::Result<FormatHint> {
+    let value: Value = read_cbor(path)?;
+    if let Some(unknown_field) = MeasurementData::SCHEMA.first_unknown_field(&value, "") {
+        return Ok(FormatHint::Newer { unknown_field });
+    }
+    let Value::Map(fields) = value else {
+        return Err(invalid_data("measurement file does not contain a map"));
+    };
+    let has_history = ["history_id", "history_description"]
+        .iter()
+        .all(|name| fields.contains_key(&Value::Text((*name).to_owned())));
+    Ok(if has_history {
+        FormatHint::Current
+    } else {
+        FormatHint::Legacy
+    })
+}
+
+/// Read and decode a CBOR file
+///
+/// Decoding errors are reported as [`io::ErrorKind::InvalidData`] errors.
+pub(crate) fn read_cbor<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    from_slice(&std::fs::read(path)?)
+}
+
+/// Decode CBOR data from memory
+///
+/// Decoding errors are reported as [`io::ErrorKind::InvalidData`] errors.
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> io::Result<T> {
+    serde_cbor::from_slice(bytes).map_err(invalid_data)
+}
+
+/// Read and decode a CBOR file, with validation if strict mode is enabled
+///
+/// Decoding and validation errors are reported as
+/// [`io::ErrorKind::InvalidData`] errors.
+pub(crate) fn read_validated<T: Validate>(path: &Path, options: &DecodeOptions) -> io::Result<T> {
+    if !options.strict {
+        return read_cbor(path);
+    }
+    let value: Value = read_cbor(path)?;
+    T::SCHEMA.check(&value, "")?;
+    let decoded: T = serde_cbor::value::from_value(value).map_err(invalid_data)?;
+    decoded.validate()?;
+    Ok(decoded)
+}
+
+/// Data type which can be validated in strict mode
+pub(crate) trait Validate: DeserializeOwned {
+    /// Fields which this data type is serialized into
+    const SCHEMA: Schema;
+
+    /// Check the semantic validity of decoded data
+    fn validate(&self) -> io::Result<()>;
+}
+//
+impl Validate for BenchmarkMetadata {
+    const SCHEMA: Schema = Schema::Map(&[
+        (
+            "id",
+            Schema::Map(&[
+                ("group_id", Schema::Any),
+                ("function_id", Schema::Any),
+                ("value_str", Schema::Any),
+                ("throughput", Schema::Any),
+            ]),
+        ),
+        ("latest_record", Schema::Any),
+    ]);
+
+    fn validate(&self) -> io::Result<()> {
+        self.id.decode()?;
+        let is_measurement_file = self
+            .latest_record
+            .to_str()
+            .and_then(|latest_record| {
+                crate::slash_to_path(latest_record)
+                    .file_name()?
+                    .to_str()
+                    .and_then(layout::parse_measurement_file_name)
+            })
+            .is_some();
+        if !is_measurement_file {
+            return Err(invalid_data(format!(
+                "latest record {:?} does not point to a measurement file",
+                self.latest_record
+            )));
+        }
+        Ok(())
+    }
+}
+//
+impl Validate for MeasurementData {
+    const SCHEMA: Schema = Schema::Map(&[
+        ("datetime", Schema::Any),
+        ("iterations", Schema::Any),
+        ("values", Schema::Any),
+        ("avg_values", Schema::Any),
+        (
+            "estimates",
+            Schema::Map(&[
+                ("mean", ESTIMATE_SCHEMA),
+                ("median", ESTIMATE_SCHEMA),
+                ("median_abs_dev", ESTIMATE_SCHEMA),
+                ("slope", ESTIMATE_SCHEMA),
+                ("std_dev", ESTIMATE_SCHEMA),
+            ]),
+        ),
+        ("throughput", Schema::Any),
+        (
+            "changes",
+            Schema::Map(&[("mean", ESTIMATE_SCHEMA), ("median", ESTIMATE_SCHEMA)]),
+        ),
+        ("change_direction", Schema::Any),
+        ("history_id", Schema::Any),
+        ("history_description", Schema::Any),
+    ]);
+
+    fn validate(&self) -> io::Result<()> {
+        match validation::measurement_issues(self).first() {
+            Some(issue) => Err(invalid_data(issue.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Expected fields of an [`Estimate`](crate::Estimate)
+const ESTIMATE_SCHEMA: Schema = Schema::Map(&[
+    (
+        "confidence_interval",
+        Schema::Map(&[
+            ("confidence_level", Schema::Any),
+            ("lower_bound", Schema::Any),
+            ("upper_bound", Schema::Any),
+        ]),
+    ),
+    ("point_estimate", Schema::Any),
+    ("standard_error", Schema::Any),
+]);
+
+/// Expected structure of a CBOR data item, used to detect unknown fields
+pub(crate) enum Schema {
+    /// Any value is accepted
+    Any,
+
+    /// Map with a known set of fields, or a non-map value that serde will
+    /// check (e.g. `null` for an optional field)
+    Map(&'static [(&'static str, Schema)]),
+}
+//
+impl Schema {
+    /// Check that a value does not contain unknown fields
+    ///
+    /// `path` is the location of the value in the file, used in error messages.
+    fn check(&self, value: &Value, path: &str) -> io::Result<()> {
+        match self.first_unknown_field(value, path) {
+            Some(field) => Err(invalid_data(format!("unknown field {field}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Find the location of the first unknown field of a value, if any
+    fn first_unknown_field(&self, value: &Value, path: &str) -> Option<String> {
+        let (Self::Map(fields), Value::Map(map)) = (self, value) else {
+            return None;
+        };
+        map.iter().find_map(|(key, value)| {
+            let field = fields
+                .iter()
+                .find(|(name, _)| matches!(key, Value::Text(key) if key == name));
+            let Some((name, schema)) = field else {
+                let key = match key {
+                    Value::Text(key) => key.clone(),
+                    other => format!("{other:?}"),
+                };
+                return Some(format!("{path}/{key}"));
+            };
+            schema.first_unknown_field(value, &format!("{path}/{name}"))
+        })
+    }
+}
+
+/// Wrap an error into an [`io::ErrorKind::InvalidData`] error
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,268 @@
+//! Errors reported by this library
+//!
+//! Criterion data directories can end up in surprising states, e.g. after an
+//! interrupted benchmark run or a manual edit. The operations that walk and
+//! decode them report such problems as an [`Error`], so that tools can skip the
+//! affected benchmarks and carry on with the rest of the data.
+//!
+//! Higher-level operations that read benchmark data, like
+//! [`History::load()`](crate::history::History::load) or
+//! [`Gate::check()`](crate::ci::Gate::check), report an [`Error`] too. This
+//! includes operations that also load or save another file, like
+//! [`snapshot::compare()`](crate::snapshot::compare), which report problems
+//! with that file as [`Error::Io`] or [`Error::Decode`].
+//!
+//! Operations that only load or save other files, like performance budgets or
+//! portable datasets, and operations that run cargo-criterion, report
+//! [`io::Error`]s instead, into which an [`Error`] converts without losing its
+//! message. Decoding errors and unexpected data become errors of kind
+//! [`io::ErrorKind::InvalidData`].
+//!
+//! Searches that are started at a location that cannot be searched report a
+//! [`SearchError`], which tells a missing directory apart from other problems,
+//! since the absence of benchmark data is often not an error. Operations that
+//! start a search on their own wrap it into [`Error::Search`].
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Error encountered while accessing Criterion benchmark data
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file could not be read or written
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        /// Path to the file
+        path: PathBuf,
+
+        /// Underlying error
+        source: io::Error,
+    },
+
+    /// The data directory could not be walked
+    #[error(transparent)]
+    Walk(#[from] walkdir::Error),
+
+    /// A CBOR file is malformed
+    ///
+    /// This is the only kind of error that a
+    /// [`DecodeErrorPolicy`](crate::decode::DecodeErrorPolicy) can skip.
+    #[error("failed to decode {}: {source}", path.display())]
+    Decode {
+        /// Path to the malformed file
+        path: PathBuf,
+
+        /// Underlying error, of kind [`io::ErrorKind::InvalidData`]
+        source: io::Error,
+    },
+
+    /// A file or directory name is not valid Unicode
+    ///
+    /// Criterion never generates such names, which thus come from manual
+    /// edits, data corruption or unusual filesystem encodings. Searches with
+    /// [lossy names](crate::Search::lossy_names) do not report them.
+    #[error("non-Unicode name in Criterion data directory: {}", path.display())]
+    NonUnicodeName {
+        /// Path to the file or directory
+        path: PathBuf,
+    },
+
+    /// A measurement file name does not follow cargo-criterion's naming scheme
+    #[error("unexpected measurement file name: {}", path.display())]
+    UnexpectedFileName {
+        /// Path to the measurement file, or name of the measurement file if it
+        /// was referred to by a [`BenchmarkMetadata`](crate::BenchmarkMetadata)
+        path: PathBuf,
+    },
+
+    /// The data directory does not have the layout that cargo-criterion is
+    /// expected to produce
+    ///
+    /// This is only reported with
+    /// [`InvariantChecks::Report`](crate::layout::InvariantChecks::Report).
+    #[error("{message}")]
+    UnexpectedLayout {
+        /// Path to the offending file or directory
+        path: PathBuf,
+
+        /// Description of the problem
+        message: String,
+    },
+
+    /// A benchmark identifier combines fields in a way that Criterion cannot
+    /// produce
+    ///
+    /// See [`RawBenchmarkId::decode()`](crate::RawBenchmarkId::decode).
+    #[error("impossible benchmark identifier {id}: {message}")]
+    ImpossibleBenchmarkId {
+        /// Full identifier of the benchmark
+        id: String,
+
+        /// Description of the problem
+        message: &'static str,
+    },
+
+    /// A search could not be started
+    #[error(transparent)]
+    Search(#[from] SearchError),
+}
+//
+impl Error {
+    /// Classify an error that occured while reading and decoding a file
+    pub(crate) fn reading(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        let path = path.into();
+        if source.kind() == io::ErrorKind::InvalidData {
+            Self::Decode { path, source }
+        } else {
+            Self::Io { path, source }
+        }
+    }
+
+    /// Path to the file or directory that caused the error, if known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Io { path, .. }
+            | Self::Decode { path, .. }
+            | Self::NonUnicodeName { path }
+            | Self::UnexpectedFileName { path }
+            | Self::UnexpectedLayout { path, .. } => Some(path),
+            Self::Walk(error) => error.path(),
+            Self::Search(error) => Some(error.path()),
+            Self::ImpossibleBenchmarkId { .. } => None,
+        }
+    }
+
+    /// Truth that this error comes from a malformed CBOR file
+    pub fn is_decode_error(&self) -> bool {
+        matches!(self, Self::Decode { .. })
+    }
+
+    /// Kind of the [`io::Error`] that this error converts into
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Io { source, .. } => source.kind(),
+            Self::Walk(error) => error
+                .io_error()
+                .map_or(io::ErrorKind::Other, io::Error::kind),
+            Self::Search(error) => error.kind(),
+            Self::Decode { .. }
+            | Self::NonUnicodeName { .. }
+            | Self::UnexpectedFileName { .. }
+            | Self::UnexpectedLayout { .. }
+            | Self::ImpossibleBenchmarkId { .. } => io::ErrorKind::InvalidData,
+        }
+    }
+}
+//
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+/// Error encountered while starting a [`Search`](crate::Search)
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SearchError {
+    /// The specified Cargo hierarchy root does not exist
+    #[error("Cargo root does not exist: {}", path.display())]
+    CargoRootNotFound {
+        /// Specified Cargo root
+        path: PathBuf,
+    },
+
+    /// The specified `target` directory does not exist
+    ///
+    /// This usually means that the project was never built, and thus that no
+    /// benchmark has been run yet.
+    #[error("target directory does not exist: {}", path.display())]
+    TargetDirNotFound {
+        /// Specified `target` directory
+        path: PathBuf,
+    },
+
+    /// The specified location is not a directory
+    #[error("not a directory: {}", path.display())]
+    NotADirectory {
+        /// Specified location
+        path: PathBuf,
+    },
+
+    /// The specified search prefix is not a relative path that only contains
+    /// normal components
+    #[error("invalid search prefix: {}", path.display())]
+    InvalidPrefix {
+        /// Specified prefix
+        path: PathBuf,
+    },
+
+    /// The specified location could not be queried
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        /// Specified location
+        path: PathBuf,
+
+        /// Underlying error
+        source: io::Error,
+    },
+}
+//
+impl SearchError {
+    /// Check that a directory exists, reporting `not_found` if it does not
+    pub(crate) fn check_dir(
+        path: &Path,
+        not_found: impl FnOnce(PathBuf) -> Self,
+    ) -> Result<(), Self> {
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(Self::NotADirectory { path: path.into() }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(not_found(path.into())),
+            Err(source) => Err(Self::Io {
+                path: path.into(),
+                source,
+            }),
+        }
+    }
+
+    /// Location that the search was started at, or invalid search prefix
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::CargoRootNotFound { path }
+            | Self::TargetDirNotFound { path }
+            | Self::NotADirectory { path }
+            | Self::InvalidPrefix { path }
+            | Self::Io { path, .. } => path,
+        }
+    }
+
+    /// Truth that the search location does not exist
+    ///
+    /// Tools that run before any benchmark may want to treat this case as an
+    /// empty set of benchmarks rather than as an error.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::CargoRootNotFound { .. } | Self::TargetDirNotFound { .. }
+        )
+    }
+
+    /// Kind of the [`io::Error`] that this error converts into
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::CargoRootNotFound { .. } | Self::TargetDirNotFound { .. } => {
+                io::ErrorKind::NotFound
+            }
+            Self::NotADirectory { .. } | Self::InvalidPrefix { .. } => io::ErrorKind::InvalidInput,
+            Self::Io { source, .. } => source.kind(),
+        }
+    }
+}
+//
+impl From<SearchError> for io::Error {
+    fn from(error: SearchError) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
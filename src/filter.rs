@@ -0,0 +1,167 @@
+//! Structured filters for [`Search::find_in_paths()`](crate::Search::find_in_paths)
+//!
+//! Criterion stores the data of a benchmark with identifier
+//! `group/function/value` at the same relative path below the data root,
+//! after making each component [filesystem-safe](layout::make_filename_safe).
+//! Benchmarks that were not defined within a benchmark group, or without an
+//! input value, have fewer levels of directories.
+//!
+//! Writing a [`find_in_paths()`](crate::Search::find_in_paths) predicate that gets
+//! all of this right is tedious, so a [`PathFilter`] can be built instead:
+//!
+//! ```no_run
+//! # use criterion_cbor::{filter::{any, PathFilter}, Search};
+//! let filter = PathFilter::group("fft")
+//!     .function(any())
+//!     .parameter(|p: &str| p.parse::<u32>().is_ok_and(|n| n >= 1024));
+//! for bench in Search::in_cargo_root(".").find_in_paths(filter.into_predicate()) {
+//!     // ...
+//! #   let _ = bench;
+//! }
+//! ```
+//!
+//! Filters are applied level by level, without knowing what each directory
+//! represents, so the `function` filter applies to the second directory level
+//! and the `parameter` filter to the third one. Benchmarks whose data lies at
+//! a shallower level are not affected by the filters of deeper levels.
+
+use crate::{glob, layout, DataDirectory};
+
+/// Criterion that a single data directory name must satisfy
+///
+/// This is implemented for:
+///
+/// - Strings, which select the directory that Criterion would create for an
+///   identifier component with this exact name.
+/// - Closures that take a directory name and return `true` if it should be
+///   selected. Note that directory names may differ from the original
+///   identifier components, as explained in the [module docs](self).
+/// - [`Any`], which selects all directories.
+/// - [`Glob`], which selects directories whose name matches a glob pattern.
+pub trait ComponentFilter {
+    /// Truth that a data directory name is selected
+    fn matches(&self, dir_name: &str) -> bool;
+}
+//
+impl ComponentFilter for str {
+    fn matches(&self, dir_name: &str) -> bool {
+        dir_name == layout::make_filename_safe(self)
+    }
+}
+//
+impl ComponentFilter for &str {
+    fn matches(&self, dir_name: &str) -> bool {
+        ComponentFilter::matches(*self, dir_name)
+    }
+}
+//
+impl ComponentFilter for String {
+    fn matches(&self, dir_name: &str) -> bool {
+        ComponentFilter::matches(self.as_str(), dir_name)
+    }
+}
+//
+impl<F: Fn(&str) -> bool> ComponentFilter for F {
+    fn matches(&self, dir_name: &str) -> bool {
+        self(dir_name)
+    }
+}
+
+/// [`ComponentFilter`] that selects all directories
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Any;
+//
+impl ComponentFilter for Any {
+    fn matches(&self, _dir_name: &str) -> bool {
+        true
+    }
+}
+
+/// Select all directories at some level
+pub fn any() -> Any {
+    Any
+}
+
+/// [`ComponentFilter`] that selects directories whose name matches a glob
+/// pattern
+///
+/// Patterns support the `?` wildcard, which matches any single character, and
+/// the `*` wildcard, which matches any sequence of characters. They are
+/// matched against directory names, not the original identifier components.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Glob(String);
+//
+impl ComponentFilter for Glob {
+    fn matches(&self, dir_name: &str) -> bool {
+        glob::matches(&self.0, dir_name)
+    }
+}
+
+/// Select directories whose name matches a glob pattern
+pub fn glob(pattern: impl Into<String>) -> Glob {
+    Glob(pattern.into())
+}
+
+/// Filter on the group, function and parameter directories of benchmarks
+///
+/// By default, all directories are selected.
+pub struct PathFilter<'filter> {
+    /// Filters for the first, second and third levels of directories
+    levels: [Box<dyn ComponentFilter + 'filter>; 3],
+}
+//
+impl<'filter> PathFilter<'filter> {
+    /// Select benchmarks whose top-level directory satisfies a filter
+    ///
+    /// This directory is named after the benchmark group, or after the
+    /// benchmark function for benchmarks outside of a group.
+    pub fn group(filter: impl ComponentFilter + 'filter) -> Self {
+        let mut result = Self::default();
+        result.levels[0] = Box::new(filter);
+        result
+    }
+
+    /// Additionally filter second-level directories
+    ///
+    /// These are named after the benchmark function within its group, or after
+    /// the input value for benchmarks outside of a group.
+    pub fn function(mut self, filter: impl ComponentFilter + 'filter) -> Self {
+        self.levels[1] = Box::new(filter);
+        self
+    }
+
+    /// Additionally filter third-level directories
+    ///
+    /// These are named after the input value of benchmarks within a group.
+    pub fn parameter(mut self, filter: impl ComponentFilter + 'filter) -> Self {
+        self.levels[2] = Box::new(filter);
+        self
+    }
+
+    /// Truth that a data directory and all of its parents are selected
+    ///
+    /// Criterion never creates more than three levels of directories, so
+    /// deeper directories are never selected.
+    pub fn matches(&self, dir: &DataDirectory<'_>) -> bool {
+        let mut levels = self.levels.iter();
+        dir.components().all(|dir_name| {
+            levels
+                .next()
+                .is_some_and(|filter| filter.matches(&dir_name))
+        })
+    }
+
+    /// Turn this filter into a
+    /// [`Search::find_in_paths()`](crate::Search::find_in_paths) predicate
+    pub fn into_predicate(self) -> impl FnMut(DataDirectory<'_>) -> bool + 'filter {
+        move |dir| self.matches(&dir)
+    }
+}
+//
+impl Default for PathFilter<'_> {
+    fn default() -> Self {
+        Self {
+            levels: [Box::new(Any), Box::new(Any), Box::new(Any)],
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Minimal glob pattern matching for benchmark paths
+
+/// Check if a `/`-separated benchmark path matches a glob pattern
+///
+/// Patterns support the `?` wildcard, which matches any single character, and
+/// the `*` wildcard, which matches any sequence of characters, including path
+/// separators. All other characters must match exactly.
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let path = path.chars().collect::<Vec<_>>();
+
+    // Classic wildcard matching with single-star backtracking
+    let (mut p, mut s) = (0, 0);
+    let mut last_star = None;
+    while s < path.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                last_star = Some((p, s));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == path[s] => {
+                p += 1;
+                s += 1;
+            }
+            _ => match last_star {
+                Some((star_p, star_s)) => {
+                    p = star_p + 1;
+                    s = star_s + 1;
+                    last_star = Some((star_p, star_s + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,1174 @@
+//! Time series view of a benchmark's measurements
+//!
+//! A [`Benchmark`] gives you access to individual measurement files. This
+//! module loads them into a [`History`], which summarizes each measurement as
+//! a [`HistoryPoint`] and orders them chronologically, as is needed for trend
+//! analysis. It can also fill in missing history identifiers from a git
+//! repository's commit history with [`retag_from_git()`].
+//!
+//! A benchmark may change what it measures without changing its identifier,
+//! e.g. when its input size is changed. [`detect_discontinuities()`] finds such
+//! changes, so that they are not mistaken for performance changes.
+//!
+//! Histories of different benchmarks are usually not measured at the same
+//! times, which makes them hard to overlay on a single chart.
+//! [`resample_to_grid()`] maps them onto a common time grid.
+//!
+//! Regression detection compares the latest measurement of a benchmark with
+//! an earlier one, which is not necessarily the previous run: on machines
+//! whose load follows a weekly pattern, it is fairer to compare tonight's run
+//! with that of the same night last week. A [`BaselineStrategy`] picks this
+//! earlier measurement.
+//!
+//! Dashboards can also show when benchmarks are actually run, and when they
+//! regressed, as a calendar heatmap built with [`activity_calendar()`].
+
+use crate::{
+    stats, writer, Benchmark, ChangeDirection, Error, Estimates, MeasurementData, Search, Statistic,
+};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, TimeZone, Utc};
+use criterion::Throughput;
+use std::{collections::BTreeMap, io, path::Path, process::Command};
+
+/// Chronologically ordered measurement history of one benchmark
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    /// Summaries of measurements, from oldest to newest
+    points: Vec<HistoryPoint>,
+}
+//
+impl History {
+    /// Load the measurement history of a benchmark
+    ///
+    /// This reads every measurement file of the benchmark, so it can take a
+    /// while for benchmarks with a long history. Malformed measurement files
+    /// are handled according to the
+    /// [decoding error policy](crate::Search::on_decode_error) of the search.
+    pub fn load(benchmark: &Benchmark) -> Result<Self, Error> {
+        benchmark
+            .decoded_measurements()
+            .map(|result| result.map(|(_, data)| HistoryPoint::new(&data)))
+            .collect()
+    }
+
+    /// Summaries of measurements, from oldest to newest
+    pub fn points(&self) -> &[HistoryPoint] {
+        &self.points
+    }
+
+    /// Number of measurements in this history
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Truth that this history contains no measurement
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Latest measurement, if any
+    pub fn latest(&self) -> Option<&HistoryPoint> {
+        self.points.last()
+    }
+}
+//
+impl FromIterator<HistoryPoint> for History {
+    fn from_iter<I: IntoIterator<Item = HistoryPoint>>(iter: I) -> Self {
+        let mut points = iter.into_iter().collect::<Vec<_>>();
+        points.sort_by_key(|point| point.datetime);
+        Self { points }
+    }
+}
+
+/// Summary of one measurement within a [`History`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryPoint {
+    /// Date and time at which the measurement was saved
+    pub datetime: DateTime<Utc>,
+
+    /// Statistical estimates from this measurement
+    pub estimates: Estimates,
+
+    /// Throughput of this measurement, if any
+    pub throughput: Option<Throughput>,
+
+    /// Significance of the change with respect to the previous measurement,
+    /// if Criterion could compute it
+    pub change_direction: Option<ChangeDirection>,
+
+    /// Optional user-provided identifier (e.g. version control commit ID)
+    pub history_id: Option<String>,
+
+    /// Truth that this measurement looks suspicious with respect to the
+    /// measurements that preceded it
+    ///
+    /// This is always `false` until [`flag_anomalies()`] is called.
+    pub anomaly: bool,
+}
+//
+impl HistoryPoint {
+    /// Summarize a measurement
+    pub fn new(data: &MeasurementData) -> Self {
+        Self {
+            datetime: data.datetime,
+            estimates: data.estimates,
+            throughput: data.throughput.clone(),
+            change_direction: data.change_direction,
+            history_id: data.history_id.clone(),
+            anomaly: false,
+        }
+    }
+}
+
+/// Rolling statistics of an estimate at one point of a [`History`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RollingPoint {
+    /// Date and time of the measurement at the end of the window
+    pub datetime: DateTime<Utc>,
+
+    /// Number of measurements within the window
+    ///
+    /// This is smaller than the requested window size for the first
+    /// measurements of the history.
+    pub window_len: usize,
+
+    /// Moving average of the estimate
+    pub mean: f64,
+
+    /// Moving median of the estimate
+    pub median: f64,
+
+    /// Moving standard deviation of the estimate
+    ///
+    /// This is zero when the window only contains one measurement.
+    pub std_dev: f64,
+}
+
+/// Compute rolling statistics of the typical estimate over a history
+///
+/// Each measurement is associated with statistics computed over a trailing
+/// window containing itself and up to `window - 1` previous measurements. This
+/// is typically used to smooth out noisy histories in trend charts.
+///
+/// A `window` of zero contains no measurement, so no point is produced.
+pub fn rolling(history: &History, window: usize) -> Vec<RollingPoint> {
+    rolling_by(history, window, Statistic::Typical)
+}
+
+/// Compute rolling statistics of a certain estimate over a history
+///
+/// See [`rolling()`] for details.
+pub fn rolling_by(history: &History, window: usize, statistic: Statistic) -> Vec<RollingPoint> {
+    if window == 0 {
+        return Vec::new();
+    }
+    let means = history
+        .points()
+        .iter()
+        .map(|point| point.estimates.get(statistic).point_estimate)
+        .collect::<Vec<_>>();
+    let mut scratch = Vec::with_capacity(window);
+    history
+        .points()
+        .iter()
+        .enumerate()
+        .map(|(idx, point)| {
+            let values = &means[(idx + 1).saturating_sub(window)..=idx];
+            let window_len = values.len();
+            let mean = values.iter().sum::<f64>() / window_len as f64;
+            let std_dev = if window_len > 1 {
+                let sum_sq = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+                (sum_sq / (window_len - 1) as f64).sqrt()
+            } else {
+                0.0
+            };
+            scratch.clear();
+            scratch.extend_from_slice(values);
+            let median = stats::median(&mut scratch);
+            RollingPoint {
+                datetime: point.datetime,
+                window_len,
+                mean,
+                median,
+                std_dev,
+            }
+        })
+        .collect()
+}
+
+/// Point of a history that was resampled with [`resample_to_grid()`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridPoint {
+    /// Start of the grid cell that this point represents
+    pub datetime: DateTime<Utc>,
+
+    /// Mean estimate for this grid cell
+    ///
+    /// This is the average of the mean estimates of the measurements taken
+    /// within the cell, or an interpolated value if there is none.
+    pub mean: f64,
+
+    /// Truth that no measurement was taken within this grid cell, so that
+    /// [`mean`](Self::mean) was linearly interpolated from the closest
+    /// measurements before and after it
+    ///
+    /// Charts should typically display such points differently (e.g. with a
+    /// dashed line), or not at all.
+    pub gap: bool,
+}
+
+/// Maximal number of points that [`resample_to_grid()`] may produce
+pub const MAX_GRID_POINTS: usize = 1_000_000;
+
+/// Resample a history onto a regular time grid
+///
+/// Time is divided into cells of duration `interval`, aligned on the Unix
+/// epoch so that the grids of different histories line up. One point is
+/// produced per cell, from the cell of the oldest measurement to the cell of
+/// the latest one, and empty cells are marked as [gaps](GridPoint::gap).
+///
+/// A non-positive `interval`, or one so short with respect to the time span of
+/// the history that the grid would have more than [`MAX_GRID_POINTS`] points,
+/// is reported as an error of kind [`io::ErrorKind::InvalidInput`].
+pub fn resample_to_grid(history: &History, interval: TimeDelta) -> io::Result<Vec<GridPoint>> {
+    let invalid_input = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let step =
+        i128::from(interval.num_seconds()) * NANOS_PER_SEC + i128::from(interval.subsec_nanos());
+    if step <= 0 {
+        return Err(invalid_input(format!(
+            "grid interval should be positive, got {interval}"
+        )));
+    }
+    let cell_of = |datetime: DateTime<Utc>| nanos_since_epoch(datetime).div_euclid(step);
+    let cell_start = |cell: i128| {
+        let nanos = cell * step;
+        i64::try_from(nanos.div_euclid(NANOS_PER_SEC))
+            .ok()
+            .and_then(|secs| {
+                // Remainder of a division by 10^9, which always fits
+                DateTime::from_timestamp(secs, nanos.rem_euclid(NANOS_PER_SEC) as u32)
+            })
+            .expect("Grid cells should start after the earliest representable date")
+    };
+    let mean_of = |point: &HistoryPoint| point.estimates.mean.point_estimate;
+    let points = history.points();
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Ok(Vec::new());
+    };
+    let (first_cell, last_cell) = (cell_of(first.datetime), cell_of(last.datetime));
+    let num_cells = last_cell - first_cell + 1;
+    if num_cells > MAX_GRID_POINTS as i128 {
+        return Err(invalid_input(format!(
+            "grid interval {interval} would produce {num_cells} points, more than the maximum of {MAX_GRID_POINTS}"
+        )));
+    }
+    let mut grid = Vec::with_capacity(num_cells as usize);
+    let mut next = 0;
+    for cell in first_cell..=last_cell {
+        let start = next;
+        while next < points.len() && cell_of(points[next].datetime) == cell {
+            next += 1;
+        }
+        let datetime = cell_start(cell);
+        let in_cell = &points[start..next];
+        if !in_cell.is_empty() {
+            let mean = in_cell.iter().map(mean_of).sum::<f64>() / in_cell.len() as f64;
+            grid.push(GridPoint {
+                datetime,
+                mean,
+                gap: false,
+            });
+            continue;
+        }
+        // Empty cells always lie between two measurements
+        let (before, after) = (&points[next - 1], &points[next]);
+        let before_nanos = nanos_since_epoch(before.datetime);
+        let span = (nanos_since_epoch(after.datetime) - before_nanos) as f64;
+        let elapsed = (cell * step - before_nanos) as f64;
+        let mean = mean_of(before) + (mean_of(after) - mean_of(before)) * elapsed / span;
+        grid.push(GridPoint {
+            datetime,
+            mean,
+            gap: true,
+        });
+    }
+    Ok(grid)
+}
+
+/// Number of nanoseconds in a second
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// Number of nanoseconds elapsed since the Unix epoch
+///
+/// Unlike [`DateTime::timestamp_nanos_opt()`], this covers all representable
+/// dates.
+fn nanos_since_epoch(datetime: DateTime<Utc>) -> i128 {
+    i128::from(datetime.timestamp()) * NANOS_PER_SEC + i128::from(datetime.timestamp_subsec_nanos())
+}
+
+/// Minimal number of previous measurements needed to flag anomalies
+const MIN_ANOMALY_BASELINE: usize = 3;
+
+/// Statistics used to decide whether a measurement is an outlier
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum RobustnessMode {
+    /// Use the mean and standard deviation of previous measurements
+    ///
+    /// This is the textbook approach, which is appropriate when noise is
+    /// roughly normally distributed, but a single past outlier can inflate the
+    /// standard deviation enough to hide subsequent ones.
+    Classic,
+
+    /// Use the median and median absolute deviation (MAD) of previous
+    /// measurements
+    ///
+    /// These statistics are barely affected by past outliers, which results in
+    /// far fewer false alarms on benchmarks with heavy-tailed noise.
+    #[default]
+    Robust,
+}
+
+/// Flag measurements whose typical estimate is far away from the recent
+/// rolling median
+///
+/// For each measurement, the median and median absolute deviation (MAD) of the
+/// [typical](Statistic::Typical) estimate are computed over the `window`
+/// previous measurements, and the measurement is flagged as an anomaly if its
+/// typical estimate deviates from this median by more than `k` times the MAD. Like in Criterion, the MAD is scaled
+/// so that it estimates the standard deviation for normally distributed data.
+///
+/// Measurements which are preceded by fewer than 3 measurements are never
+/// flagged, as there is not enough data to tell what is normal. For the same
+/// reason, no measurement is flagged if `window` is smaller than 3.
+///
+/// This can be used to visually mark runs that were likely affected by external
+/// factors such as thermal throttling or noisy neighbors. It is a shorthand for
+/// [`flag_anomalies_with()`] in [`RobustnessMode::Robust`] mode.
+pub fn flag_anomalies(history: &mut History, window: usize, k: f64) {
+    flag_anomalies_with(
+        history,
+        window,
+        k,
+        RobustnessMode::Robust,
+        Statistic::Typical,
+    )
+}
+
+/// Flag measurements whose estimate is far away from recent measurements
+///
+/// This works like [`flag_anomalies()`], but lets you choose which estimate is
+/// checked and which statistics are used to tell what is normal. In
+/// [`RobustnessMode::Classic`] mode, a measurement is flagged if its estimate
+/// deviates from the average of the `window` previous estimates by more than
+/// `k` times their standard deviation.
+pub fn flag_anomalies_with(
+    history: &mut History,
+    window: usize,
+    k: f64,
+    mode: RobustnessMode,
+    statistic: Statistic,
+) {
+    let means = history
+        .points
+        .iter()
+        .map(|point| point.estimates.get(statistic).point_estimate)
+        .collect::<Vec<_>>();
+    let mut scratch = Vec::with_capacity(window);
+    for (idx, point) in history.points.iter_mut().enumerate() {
+        let baseline = &means[idx.saturating_sub(window)..idx];
+        if baseline.len() < MIN_ANOMALY_BASELINE {
+            point.anomaly = false;
+            continue;
+        }
+        let (center, spread) = match mode {
+            RobustnessMode::Classic => {
+                let len = baseline.len() as f64;
+                let avg = baseline.iter().sum::<f64>() / len;
+                let sum_sq = baseline.iter().map(|x| (x - avg).powi(2)).sum::<f64>();
+                (avg, (sum_sq / (len - 1.0)).sqrt())
+            }
+            RobustnessMode::Robust => {
+                scratch.clear();
+                scratch.extend_from_slice(baseline);
+                let center = stats::median(&mut scratch);
+                for x in scratch.iter_mut() {
+                    *x = (*x - center).abs();
+                }
+                (center, stats::median(&mut scratch) * 1.4826)
+            }
+        };
+        point.anomaly = (means[idx] - center).abs() > k * spread;
+    }
+}
+
+/// Point of a [`History`] where the benchmark likely started measuring
+/// something different
+#[derive(Clone, Debug, PartialEq)]
+pub struct Discontinuity {
+    /// Index of the first measurement after the discontinuity
+    pub index: usize,
+
+    /// Date and time of the first measurement after the discontinuity
+    pub datetime: DateTime<Utc>,
+
+    /// Reason why this is considered to be a discontinuity
+    pub cause: DiscontinuityCause,
+}
+
+/// Reason why a [`Discontinuity`] was detected
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiscontinuityCause {
+    /// The throughput metadata of the benchmark changed
+    ThroughputChanged {
+        /// Throughput before the discontinuity
+        before: Option<Throughput>,
+
+        /// Throughput after the discontinuity
+        after: Option<Throughput>,
+    },
+
+    /// The mean estimate changed by a factor larger than the detection
+    /// threshold
+    ScaleChanged {
+        /// Ratio of the mean estimate after the discontinuity to the mean
+        /// estimate before it
+        ratio: f64,
+    },
+}
+
+/// Find points where a benchmark's history is likely broken
+///
+/// A discontinuity is reported between two consecutive measurements if the
+/// throughput metadata of the benchmark changed, which normally means that
+/// the input size changed, or if the mean estimate was multiplied or divided
+/// by more than `max_ratio` (e.g. 10.0), which is much more likely to result
+/// from a change in what the benchmark measures than from a code change.
+///
+/// Trend charts should not connect measurements across discontinuities, and
+/// trend analyses should treat each segment between discontinuities as a
+/// separate series.
+///
+/// A `max_ratio` that is not greater than 1 is reported as an error of kind
+/// [`io::ErrorKind::InvalidInput`]. An infinite `max_ratio` only detects
+/// throughput changes.
+pub fn detect_discontinuities(history: &History, max_ratio: f64) -> io::Result<Vec<Discontinuity>> {
+    if max_ratio.is_nan() || max_ratio <= 1.0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("discontinuity ratio should be greater than 1, got {max_ratio}"),
+        ));
+    }
+    let discontinuities = history
+        .points()
+        .windows(2)
+        .enumerate()
+        .filter_map(|(idx, pair)| {
+            let [before, after] = pair else {
+                unreachable!("windows(2) should yield pairs");
+            };
+            let cause = if before.throughput != after.throughput {
+                DiscontinuityCause::ThroughputChanged {
+                    before: before.throughput.clone(),
+                    after: after.throughput.clone(),
+                }
+            } else {
+                let ratio =
+                    after.estimates.mean.point_estimate / before.estimates.mean.point_estimate;
+                if !(1.0 / max_ratio..=max_ratio).contains(&ratio) {
+                    DiscontinuityCause::ScaleChanged { ratio }
+                } else {
+                    return None;
+                }
+            };
+            Some(Discontinuity {
+                index: idx + 1,
+                datetime: after.datetime,
+                cause,
+            })
+        })
+        .collect();
+    Ok(discontinuities)
+}
+
+/// Maximal distance from the ideal baseline time of
+/// [`BaselineStrategy::SameDayLastWeek`]
+const SAME_DAY_TOLERANCE: TimeDelta = TimeDelta::hours(12);
+
+/// How the measurement that the latest one is compared against is chosen
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BaselineStrategy {
+    /// Measurement that immediately precedes the latest one
+    ///
+    /// This is what Criterion compares against.
+    #[default]
+    PreviousRun,
+
+    /// Measurement that was taken closest to one week before the latest one
+    ///
+    /// This compensates for machine load patterns that repeat every week, e.g.
+    /// nightly runs that compete with weekday office hours. Measurements that
+    /// were taken more than 12 hours away from exactly one week before the
+    /// latest one are not considered.
+    SameDayLastWeek,
+
+    /// Latest measurement whose `history_id` is a release version
+    ///
+    /// Release versions are dot-separated numbers with at least two
+    /// components, optionally prefixed with `v` (e.g. `v1.2.3` or `0.5`), as
+    /// can be set with [`writer::annotate()`].
+    LastTaggedRelease,
+
+    /// Fastest of the specified number of measurements that precede the latest
+    /// one, according to their [typical](Estimates::typical) estimate
+    ///
+    /// This is a conservative baseline, which catches gradual regressions that
+    /// comparisons with the previous run would miss.
+    BestOfLastN(usize),
+}
+//
+impl BaselineStrategy {
+    /// Select the baseline of the latest measurement of a history
+    ///
+    /// Returns `None` if no earlier measurement matches the strategy.
+    pub fn select<'history>(&self, history: &'history History) -> Option<&'history HistoryPoint> {
+        self.select_index(history.points())
+            .map(|idx| &history.points()[idx])
+    }
+
+    /// Index of the baseline of the last point of a chronologically ordered
+    /// list, if any
+    pub(crate) fn select_index(&self, points: &[HistoryPoint]) -> Option<usize> {
+        let (latest, earlier) = points.split_last()?;
+        let candidates = earlier.iter().enumerate();
+        match *self {
+            Self::PreviousRun => earlier.len().checked_sub(1),
+            Self::SameDayLastWeek => {
+                let target = latest.datetime - TimeDelta::weeks(1);
+                candidates
+                    .map(|(idx, point)| (idx, (point.datetime - target).abs()))
+                    .filter(|(_, distance)| *distance <= SAME_DAY_TOLERANCE)
+                    .min_by_key(|(_, distance)| *distance)
+                    .map(|(idx, _)| idx)
+            }
+            Self::LastTaggedRelease => candidates
+                .rev()
+                .find(|(_, point)| point.history_id.as_deref().is_some_and(is_release))
+                .map(|(idx, _)| idx),
+            Self::BestOfLastN(n) => candidates
+                .skip(earlier.len().saturating_sub(n))
+                .min_by(|(_, point1), (_, point2)| {
+                    let typical = |point: &HistoryPoint| point.estimates.typical().point_estimate;
+                    typical(point1).total_cmp(&typical(point2))
+                })
+                .map(|(idx, _)| idx),
+        }
+    }
+}
+
+/// Truth that a history identifier designates a release version
+fn is_release(history_id: &str) -> bool {
+    let version = history_id.strip_prefix('v').unwrap_or(history_id);
+    let mut components = version.split('.');
+    let is_number = |component: &str| {
+        !component.is_empty() && component.bytes().all(|byte| byte.is_ascii_digit())
+    };
+    components.clone().count() >= 2 && components.all(is_number)
+}
+
+/// Benchmarking activity on one day, as computed by [`activity_calendar()`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DayActivity {
+    /// Day, in the local time zone
+    pub date: NaiveDate,
+
+    /// Number of measurements taken on that day, across all benchmarks
+    pub measurements: usize,
+
+    /// Number of these measurements that Criterion flagged as
+    /// [regressions](ChangeDirection::Regressed)
+    pub regressions: usize,
+}
+
+/// Count the measurements and regressions of each day
+///
+/// Days are delimited in the local time zone, like the dates of measurement
+/// file names. Only days with at least one measurement are listed, from oldest
+/// to newest, so renderers must fill in the other days of the calendar.
+///
+/// Every measurement file is decoded, and malformed measurement files are
+/// handled according to the
+/// [decoding error policy](crate::Search::on_decode_error) of the search.
+pub fn activity_calendar(search: Search) -> Result<Vec<DayActivity>, Error> {
+    let mut measurements = Vec::new();
+    for bench in search.find_all() {
+        let bench = bench?;
+        for result in bench.decoded_measurements() {
+            let (_, data) = result?;
+            measurements.push((data.datetime, data.change_direction));
+        }
+    }
+    Ok(count_activity(measurements, &Local))
+}
+
+/// Count the measurements and regressions of each day in a certain time zone,
+/// given the date and time and the change direction of every measurement
+fn count_activity<Tz: TimeZone>(
+    measurements: impl IntoIterator<Item = (DateTime<Utc>, Option<ChangeDirection>)>,
+    timezone: &Tz,
+) -> Vec<DayActivity> {
+    let mut days = BTreeMap::<NaiveDate, DayActivity>::new();
+    for (datetime, change_direction) in measurements {
+        let date = datetime.with_timezone(timezone).date_naive();
+        let day = days.entry(date).or_insert(DayActivity {
+            date,
+            measurements: 0,
+            regressions: 0,
+        });
+        day.measurements += 1;
+        if change_direction == Some(ChangeDirection::Regressed) {
+            day.regressions += 1;
+        }
+    }
+    days.into_values().collect()
+}
+
+/// Fill in missing history identifiers using the commit history of a git
+/// repository
+///
+/// Every measurement that has no `history_id`, or an empty one, is tagged with
+/// the hash of the latest commit of the repository's current branch that was
+/// committed before the measurement was taken, which is the commit that was
+/// most likely checked out when the benchmark was run. Measurements that
+/// predate all commits, and measurements that already have a `history_id`, are
+/// left alone. Files are modified using [`writer::annotate()`].
+///
+/// This requires the `git` command-line tool to be available. Failures to run
+/// it are reported as an [`Error::Io`] about the repository. Returns the number
+/// of measurements that were tagged.
+pub fn retag_from_git(repo: impl AsRef<Path>, search: Search) -> Result<usize, Error> {
+    let repo = repo.as_ref();
+    let commits = git_commit_times(repo).map_err(|source| Error::Io {
+        path: repo.to_owned(),
+        source,
+    })?;
+    let mut num_tagged = 0;
+    for bench in search.find_all() {
+        let bench = bench?;
+        for result in bench.decoded_measurements() {
+            let (measurement, data) = result?;
+            if data.history_id.as_deref().is_some_and(|id| !id.is_empty()) {
+                continue;
+            }
+            let idx = commits.partition_point(|(_, time)| *time <= data.datetime);
+            let Some((hash, _)) = idx.checked_sub(1).map(|idx| &commits[idx]) else {
+                continue;
+            };
+            writer::annotate(measurement.path(), Some(hash), None)
+                .map_err(|source| Error::reading(measurement.path(), source))?;
+            num_tagged += 1;
+        }
+    }
+    Ok(num_tagged)
+}
+
+/// List the commits of a git repository's current branch, along with their
+/// commit time, from oldest to newest
+///
+/// Only first parents are followed, so that the commits of merged branches,
+/// which were never checked out on the current branch, are not listed.
+fn git_commit_times(repo: &Path) -> io::Result<Vec<(String, DateTime<Utc>)>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--first-parent", "--format=%H %ct"])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let invalid_output = || io::Error::new(io::ErrorKind::InvalidData, "unexpected git log output");
+    let mut commits = String::from_utf8(output.stdout)
+        .map_err(|_| invalid_output())?
+        .lines()
+        .map(|line| {
+            let (hash, timestamp) = line.split_once(' ').ok_or_else(invalid_output)?;
+            let timestamp = timestamp.parse::<i64>().map_err(|_| invalid_output())?;
+            let time = DateTime::from_timestamp(timestamp, 0).ok_or_else(invalid_output)?;
+            Ok((hash.to_owned(), time))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    // Commit times are not necessarily monotonic (e.g. after a rebase)
+    commits.sort_by_key(|(_, time)| *time);
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{bench_id, measurement, TempTarget},
+        ConfidenceInterval, Estimate,
+    };
+
+    fn estimate(point_estimate: f64) -> Estimate {
+        Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: 0.95,
+                lower_bound: point_estimate,
+                upper_bound: point_estimate,
+            },
+            point_estimate,
+            standard_error: 0.0,
+        }
+    }
+
+    fn point(datetime: &str, mean: f64) -> HistoryPoint {
+        HistoryPoint {
+            datetime: datetime.parse().unwrap(),
+            estimates: Estimates {
+                mean: estimate(mean),
+                median: estimate(mean),
+                median_abs_dev: estimate(0.0),
+                slope: None,
+                std_dev: estimate(0.0),
+            },
+            throughput: None,
+            change_direction: None,
+            history_id: None,
+            anomaly: false,
+        }
+    }
+
+    fn history(points: &[(&str, f64)]) -> History {
+        points
+            .iter()
+            .map(|&(datetime, mean)| point(datetime, mean))
+            .collect()
+    }
+
+    #[test]
+    fn rolling_statistics() {
+        let history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 14.0),
+            ("2026-01-03T00:00:00Z", 12.0),
+            ("2026-01-04T00:00:00Z", 20.0),
+        ]);
+        let points = rolling(&history, 3);
+        let window_lens = points.iter().map(|point| point.window_len);
+        assert_eq!(window_lens.collect::<Vec<_>>(), [1, 2, 3, 3]);
+        let means = points.iter().map(|point| point.mean);
+        assert_eq!(means.collect::<Vec<_>>(), [10.0, 12.0, 12.0, 46.0 / 3.0]);
+        let medians = points.iter().map(|point| point.median);
+        assert_eq!(medians.collect::<Vec<_>>(), [10.0, 12.0, 12.0, 14.0]);
+        let std_devs = points.iter().map(|point| point.std_dev).collect::<Vec<_>>();
+        assert_eq!(std_devs[..3], [0.0, 8.0f64.sqrt(), 2.0]);
+        assert!((std_devs[3] - (52.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(points[3].datetime, history.points()[3].datetime);
+
+        assert!(rolling(&history, 0).is_empty());
+    }
+
+    #[test]
+    fn rolling_prefers_the_slope() {
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 14.0),
+        ]);
+        for (point, slope) in history.points.iter_mut().zip([8.0, 12.0]) {
+            point.estimates.slope = Some(estimate(slope));
+        }
+        let means = |points: Vec<RollingPoint>| points.iter().map(|p| p.mean).collect::<Vec<_>>();
+        assert_eq!(means(rolling(&history, 2)), [8.0, 10.0]);
+        assert_eq!(
+            means(rolling_by(&history, 2, Statistic::Mean)),
+            [10.0, 12.0]
+        );
+    }
+
+    fn anomalies(history: &History) -> Vec<bool> {
+        history.points().iter().map(|point| point.anomaly).collect()
+    }
+
+    #[test]
+    fn flag_anomalies_flags_obvious_outliers() {
+        let mut history = history_with_outlier();
+        flag_anomalies(&mut history, 4, 3.0);
+        assert_eq!(anomalies(&history), [false, false, false, false, true]);
+    }
+
+    #[test]
+    fn flag_anomalies_needs_a_baseline() {
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 10.0),
+            ("2026-01-03T00:00:00Z", 100.0),
+        ]);
+        flag_anomalies(&mut history, 5, 3.0);
+        assert_eq!(anomalies(&history), [false; 3]);
+
+        let mut history = history_with_outlier();
+        for window in [0, 1, 2] {
+            flag_anomalies(&mut history, 4, 3.0);
+            flag_anomalies(&mut history, window, 3.0);
+            assert_eq!(anomalies(&history), [false; 5]);
+        }
+    }
+
+    #[test]
+    fn flag_anomalies_with_constant_baseline() {
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 10.0),
+            ("2026-01-03T00:00:00Z", 10.0),
+            ("2026-01-04T00:00:00Z", 10.0),
+            ("2026-01-05T00:00:00Z", 10.1),
+        ]);
+        flag_anomalies(&mut history, 3, 3.0);
+        assert_eq!(anomalies(&history), [false, false, false, false, true]);
+    }
+
+    #[test]
+    fn flag_anomalies_checks_the_typical_estimate() {
+        let mut history = history_with_outlier();
+        for point in &mut history.points {
+            point.estimates.slope = Some(estimate(10.0));
+        }
+        flag_anomalies(&mut history, 4, 3.0);
+        assert_eq!(anomalies(&history), [false; 5]);
+        let (robust, mean) = (RobustnessMode::Robust, Statistic::Mean);
+        flag_anomalies_with(&mut history, 4, 3.0, robust, mean);
+        assert_eq!(anomalies(&history), [false, false, false, false, true]);
+    }
+
+    #[test]
+    fn flag_anomalies_with_robustness_modes() {
+        // The past outlier inflates the standard deviation of the baseline
+        // enough to hide the latest one, but barely affects the MAD
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 11.0),
+            ("2026-01-03T00:00:00Z", 9.0),
+            ("2026-01-04T00:00:00Z", 50.0),
+            ("2026-01-05T00:00:00Z", 10.5),
+            ("2026-01-06T00:00:00Z", 30.0),
+        ]);
+        let typical = Statistic::Typical;
+        flag_anomalies_with(&mut history, 4, 3.0, RobustnessMode::Classic, typical);
+        let classic = [false, false, false, true, false, false];
+        assert_eq!(anomalies(&history), classic);
+        flag_anomalies_with(&mut history, 4, 3.0, RobustnessMode::Robust, typical);
+        let robust = [false, false, false, true, false, true];
+        assert_eq!(anomalies(&history), robust);
+    }
+
+    fn history_with_outlier() -> History {
+        history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 11.0),
+            ("2026-01-03T00:00:00Z", 9.0),
+            ("2026-01-04T00:00:00Z", 10.5),
+            ("2026-01-05T00:00:00Z", 30.0),
+        ])
+    }
+
+    #[test]
+    fn detect_discontinuities_in_throughput_and_scale() {
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 12.0),
+            ("2026-01-03T00:00:00Z", 12.0),
+            ("2026-01-04T00:00:00Z", 150.0),
+            ("2026-01-05T00:00:00Z", 140.0),
+            ("2026-01-06T00:00:00Z", 10.0),
+        ]);
+        for point in &mut history.points[2..] {
+            point.throughput = Some(Throughput::Bytes(64));
+        }
+        let discontinuities = detect_discontinuities(&history, 10.0).unwrap();
+        let causes = discontinuities
+            .iter()
+            .map(|discontinuity| (discontinuity.index, discontinuity.cause.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            causes,
+            [
+                (
+                    2,
+                    DiscontinuityCause::ThroughputChanged {
+                        before: None,
+                        after: Some(Throughput::Bytes(64)),
+                    }
+                ),
+                (3, DiscontinuityCause::ScaleChanged { ratio: 12.5 }),
+                (5, DiscontinuityCause::ScaleChanged { ratio: 1.0 / 14.0 }),
+            ]
+        );
+        assert_eq!(discontinuities[1].datetime, history.points()[3].datetime);
+
+        let only_throughput = detect_discontinuities(&history, f64::INFINITY).unwrap();
+        assert_eq!(only_throughput.len(), 1);
+        assert_eq!(only_throughput[0].index, 2);
+    }
+
+    #[test]
+    fn detect_discontinuities_rejects_invalid_ratios() {
+        let history = history_with_outlier();
+        for max_ratio in [1.0, 0.5, -2.0, f64::NAN] {
+            let error = detect_discontinuities(&history, max_ratio).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn baseline_same_day_last_week() {
+        let select = |datetimes: &[&str]| {
+            let mut points = datetimes
+                .iter()
+                .map(|&datetime| (datetime, 10.0))
+                .collect::<Vec<_>>();
+            points.push(("2026-01-15T12:00:00Z", 10.0));
+            BaselineStrategy::SameDayLastWeek.select_index(history(&points).points())
+        };
+        // Measurements up to 12 hours away from one week ago are eligible
+        let candidates = [
+            "2026-01-07T23:00:00Z",
+            "2026-01-08T00:00:00Z",
+            "2026-01-09T01:00:00Z",
+        ];
+        assert_eq!(select(&candidates), Some(1));
+        assert_eq!(select(&[candidates[0], candidates[2]]), None);
+        // The closest one wins
+        let candidates = [
+            "2026-01-08T10:00:00Z",
+            "2026-01-08T13:00:00Z",
+            "2026-01-08T15:00:00Z",
+        ];
+        assert_eq!(select(&candidates), Some(1));
+        assert_eq!(select(&[]), None);
+    }
+
+    #[test]
+    fn baseline_best_of_last_n() {
+        let history = history(&[
+            ("2026-01-01T00:00:00Z", 5.0),
+            ("2026-01-02T00:00:00Z", 20.0),
+            ("2026-01-03T00:00:00Z", 10.0),
+            ("2026-01-04T00:00:00Z", 30.0),
+            ("2026-01-05T00:00:00Z", 15.0),
+        ]);
+        let select = |n| BaselineStrategy::BestOfLastN(n).select_index(history.points());
+        assert_eq!(select(0), None);
+        assert_eq!(select(1), Some(3));
+        assert_eq!(select(2), Some(2));
+        assert_eq!(select(4), Some(0));
+        assert_eq!(select(100), Some(0));
+        let previous = BaselineStrategy::PreviousRun.select_index(history.points());
+        assert_eq!(previous, Some(3));
+
+        let single = &history.points()[..1];
+        for strategy in [
+            BaselineStrategy::PreviousRun,
+            BaselineStrategy::SameDayLastWeek,
+            BaselineStrategy::LastTaggedRelease,
+            BaselineStrategy::BestOfLastN(3),
+        ] {
+            assert_eq!(strategy.select_index(single), None);
+            assert_eq!(strategy.select_index(&[]), None);
+        }
+    }
+
+    #[test]
+    fn baseline_last_tagged_release() {
+        let mut history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-02T00:00:00Z", 10.0),
+            ("2026-01-03T00:00:00Z", 10.0),
+            ("2026-01-04T00:00:00Z", 10.0),
+        ]);
+        for (point, id) in history.points.iter_mut().zip(["v1.2", "v1.3", "main"]) {
+            point.history_id = Some(id.to_owned());
+        }
+        let select = BaselineStrategy::LastTaggedRelease.select_index(history.points());
+        assert_eq!(select, Some(1));
+    }
+
+    #[test]
+    fn release_versions() {
+        for release in ["v1.2", "1.2", "0.5", "v1.2.3", "10.20.30.40"] {
+            assert!(is_release(release), "{release} should be a release");
+        }
+        for other in [
+            "1", "v1", "1..2", "", "v", "1.2.", ".1.2", "v1.2-rc1", "main", "vv1.2",
+        ] {
+            assert!(!is_release(other), "{other} should not be a release");
+        }
+    }
+
+    #[test]
+    fn count_activity_per_local_day() {
+        let measurement = |datetime: &str, direction| (datetime.parse().unwrap(), direction);
+        let measurements = [
+            measurement("2026-01-01T23:00:00Z", None),
+            measurement("2026-01-02T10:00:00Z", Some(ChangeDirection::Regressed)),
+            measurement("2026-01-01T10:00:00Z", Some(ChangeDirection::Improved)),
+            measurement("2026-01-05T00:00:00Z", Some(ChangeDirection::Regressed)),
+            measurement(
+                "2026-01-05T01:00:00Z",
+                Some(ChangeDirection::NotSignificant),
+            ),
+        ];
+        let day = |date: &str, measurements, regressions| DayActivity {
+            date: date.parse().unwrap(),
+            measurements,
+            regressions,
+        };
+        // Late measurements belong to the next day two hours east of UTC
+        let east = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            count_activity(measurements, &east),
+            [
+                day("2026-01-01", 1, 0),
+                day("2026-01-02", 2, 1),
+                day("2026-01-05", 2, 1),
+            ]
+        );
+        assert_eq!(
+            count_activity(measurements, &Utc),
+            [
+                day("2026-01-01", 2, 0),
+                day("2026-01-02", 1, 1),
+                day("2026-01-05", 2, 1),
+            ]
+        );
+        assert!(count_activity([], &Utc).is_empty());
+    }
+
+    #[test]
+    fn retag_from_git_fills_missing_ids() {
+        let target = TempTarget::new("retag-from-git");
+        let repo = target.path.join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .env("GIT_AUTHOR_DATE", "2020-01-01T00:00:00Z")
+                .env("GIT_COMMITTER_DATE", "2020-01-01T00:00:00Z")
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8(output.stdout).unwrap().trim().to_owned()
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "Initial commit"]);
+        let hash = git(&["rev-parse", "HEAD"]);
+
+        let with_id = |datetime, history_id: Option<&str>| MeasurementData {
+            history_id: history_id.map(str::to_owned),
+            ..measurement(datetime, &[1.0, 2.0])
+        };
+        target.write_benchmark(
+            &bench_id("parse"),
+            &[
+                with_id("2019-01-01T00:00:00Z", None),
+                with_id("2026-01-01T00:00:00Z", Some("")),
+                with_id("2026-01-02T00:00:00Z", None),
+                with_id("2026-01-03T00:00:00Z", Some("v1.0")),
+            ],
+        );
+        assert_eq!(retag_from_git(&repo, target.search()).unwrap(), 2);
+        let history = History::load(&target.search().find_all().next().unwrap().unwrap());
+        let ids = history
+            .unwrap()
+            .points()
+            .iter()
+            .map(|point| point.history_id.clone())
+            .collect::<Vec<_>>();
+        let tagged = Some(hash);
+        assert_eq!(ids, [None, tagged.clone(), tagged, Some("v1.0".to_owned())]);
+
+        let error = retag_from_git(target.path.join("missing"), target.search()).unwrap_err();
+        assert_eq!(error.path(), Some(target.path.join("missing").as_path()));
+    }
+
+    #[test]
+    fn resample_averages_within_cells() {
+        let history = history(&[
+            ("2026-01-01T01:00:00Z", 10.0),
+            ("2026-01-01T23:00:00Z", 20.0),
+            ("2026-01-02T12:00:00Z", 30.0),
+        ]);
+        let grid = resample_to_grid(&history, TimeDelta::days(1)).unwrap();
+        assert_eq!(
+            grid,
+            [
+                GridPoint {
+                    datetime: "2026-01-01T00:00:00Z".parse().unwrap(),
+                    mean: 15.0,
+                    gap: false,
+                },
+                GridPoint {
+                    datetime: "2026-01-02T00:00:00Z".parse().unwrap(),
+                    mean: 30.0,
+                    gap: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resample_interpolates_gaps() {
+        let history = history(&[
+            ("2026-01-01T00:00:00Z", 10.0),
+            ("2026-01-05T00:00:00Z", 50.0),
+        ]);
+        let grid = resample_to_grid(&history, TimeDelta::days(1)).unwrap();
+        let means = grid.iter().map(|point| point.mean).collect::<Vec<_>>();
+        let gaps = grid.iter().map(|point| point.gap).collect::<Vec<_>>();
+        assert_eq!(means, [10.0, 20.0, 30.0, 40.0, 50.0]);
+        assert_eq!(gaps, [false, true, true, true, false]);
+        assert_eq!(
+            grid[2].datetime,
+            "2026-01-03T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn resample_supports_sub_millisecond_intervals() {
+        let history = history(&[
+            ("2026-01-01T00:00:00.000000Z", 1.0),
+            ("2026-01-01T00:00:00.000300Z", 4.0),
+        ]);
+        let grid = resample_to_grid(&history, TimeDelta::microseconds(100)).unwrap();
+        let means = grid.iter().map(|point| point.mean).collect::<Vec<_>>();
+        assert_eq!(means, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(
+            grid[1].datetime,
+            "2026-01-01T00:00:00.000100Z"
+                .parse::<DateTime<Utc>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn resample_rejects_invalid_intervals() {
+        let history = history(&[("2026-01-01T00:00:00Z", 1.0), ("2026-01-02T00:00:00Z", 2.0)]);
+        for interval in [TimeDelta::zero(), TimeDelta::seconds(-1)] {
+            let error = resample_to_grid(&history, interval).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        let error = resample_to_grid(&history, TimeDelta::nanoseconds(1)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn resample_empty_history() {
+        let grid = resample_to_grid(&History::default(), TimeDelta::days(1)).unwrap();
+        assert!(grid.is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+//! Layout of cargo-criterion's data directory
+//!
+//! cargo-criterion stores benchmark data in the following hierarchy:
+//!
+//! ```text
+//! target/
+//! └── criterion/
+//!     └── data/
+//!         └── main/                         <- data root
+//!             └── <group or function>/      <- one directory per benchmark,
+//!                 └── [<function>/]            whose path is derived from
+//!                     └── [<value>/]           its identifier
+//!                         ├── benchmark.cbor
+//!                         ├── measurement_<yymmddHHMMSS>.cbor
+//!                         └── ...
+//! ```
+//!
+//! This library can additionally record the configuration of each
+//! cargo-criterion run in a sidecar directory that cargo-criterion itself
+//! ignores:
+//!
+//! ```text
+//! target/
+//! └── criterion/
+//!     └── sessions/
+//!         └── <YYYYmmddTHHMMSSZ>/           <- one directory per session
+//!             └── run_config.cbor
+//! ```
+//!
+//! Files that explain a measurement, like flamegraphs, profiler outputs or
+//! logs, can be attached to it as artifacts. These are stored next to the
+//! measurement file, in a subdirectory of the benchmark directory that
+//! cargo-criterion also ignores:
+//!
+//! ```text
+//! <benchmark directory>/
+//! ├── benchmark.cbor
+//! ├── measurement_<yymmddHHMMSS>.cbor
+//! └── artifacts/
+//!     └── measurement_<yymmddHHMMSS>/   <- one directory per measurement
+//!         ├── flamegraph.svg
+//!         └── ...
+//! ```
+//!
+//! This module exposes the naming conventions of this hierarchy, so that tools
+//! which read or write Criterion data do not need to hard-code them.
+//!
+//! Benchmark directories can be designated relative to the data root, as a
+//! [`DataPath`], or relative to their top-level group directory, as a
+//! [`GroupPath`]. These are distinct types so that paths from different levels
+//! of the hierarchy are not accidentally compared or joined.
+
+use crate::{Error, RawBenchmarkId};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Path of a data directory relative to the data root
+///
+/// Its first component is the top-level group or function directory.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DataPath<'path>(&'path Path);
+//
+impl<'path> DataPath<'path> {
+    /// Interpret a path as being relative to the data root
+    pub fn new(path: &'path (impl AsRef<Path> + ?Sized)) -> Self {
+        Self(path.as_ref())
+    }
+
+    /// Underlying filesystem path
+    pub fn as_path(self) -> &'path Path {
+        self.0
+    }
+
+    /// Depth of the data directory below the data root
+    ///
+    /// Top-level data directories have depth 1, their children have depth 2,
+    /// and so on.
+    pub fn depth(self) -> usize {
+        self.0.components().count()
+    }
+
+    /// Name of the top-level group or function directory, if any
+    ///
+    /// Non-Unicode names are converted lossily, see
+    /// [`Search::lossy_names()`](crate::Search::lossy_names).
+    pub fn group(self) -> Option<Cow<'path, str>> {
+        self.0.iter().next().map(OsStr::to_string_lossy)
+    }
+
+    /// Path of the data directory relative to its top-level group directory
+    ///
+    /// Returns `None` if this path is empty.
+    pub fn within_group(self) -> Option<GroupPath<'path>> {
+        let group = self.0.iter().next()?;
+        Some(GroupPath(
+            self.0
+                .strip_prefix(group)
+                .expect("Paths should start with their first component"),
+        ))
+    }
+
+    /// Truth that this data directory lies within another one, or is the same
+    pub fn starts_with(self, ancestor: DataPath<'_>) -> bool {
+        self.0.starts_with(ancestor.0)
+    }
+
+    /// `/`-separated string form of this path (see
+    /// [`path_to_slash()`](crate::path_to_slash))
+    pub fn to_slash(self) -> String {
+        crate::path_to_slash(self.0)
+    }
+}
+//
+impl AsRef<Path> for DataPath<'_> {
+    fn as_ref(&self) -> &Path {
+        self.0
+    }
+}
+
+/// Path of a data directory relative to its top-level group directory
+///
+/// This is empty for the top-level directory itself.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct GroupPath<'path>(&'path Path);
+//
+impl<'path> GroupPath<'path> {
+    /// Interpret a path as being relative to a top-level group directory
+    pub fn new(path: &'path (impl AsRef<Path> + ?Sized)) -> Self {
+        Self(path.as_ref())
+    }
+
+    /// Underlying filesystem path
+    pub fn as_path(self) -> &'path Path {
+        self.0
+    }
+
+    /// Depth of the data directory below its top-level group directory
+    pub fn depth(self) -> usize {
+        self.0.components().count()
+    }
+
+    /// Path of the data directory relative to the data root, given the name of
+    /// its top-level group directory
+    ///
+    /// Use [`DataPath::new()`] on the result to get a [`DataPath`].
+    pub fn in_group(self, group: &str) -> PathBuf {
+        Path::new(group).join(self.0)
+    }
+}
+//
+impl AsRef<Path> for GroupPath<'_> {
+    fn as_ref(&self) -> &Path {
+        self.0
+    }
+}
+
+/// Path components leading from the `target` directory to the data root
+///
+/// The last component is the "timeline" of cargo-criterion's data model, which
+/// is currently unused and always set to "main".
+pub const DATA_ROOT_COMPONENTS: [&str; 3] = ["criterion", "data", "main"];
+
+/// Name of the file that holds the metadata of a benchmark
+pub const BENCHMARK_FILE_NAME: &str = "benchmark.cbor";
+
+/// Prefix of measurement file names
+pub const MEASUREMENT_FILE_PREFIX: &str = "measurement_";
+
+/// Extension of measurement file names, including the leading dot
+pub const MEASUREMENT_FILE_SUFFIX: &str = ".cbor";
+
+/// `chrono` format of the local date and time within measurement file names
+pub const MEASUREMENT_DATETIME_FORMAT: &str = "%y%m%d%H%M%S";
+
+/// Name of the optional file that records which cargo-criterion version
+/// produced the data, within the parent directory of the data root
+pub const FORMAT_MARKER_FILE_NAME: &str = "cargo-criterion-version";
+
+/// Path components leading from the `target` directory to the HTML reports
+///
+/// Below this directory, the reports of each benchmark are stored at the same
+/// relative path as its data below the data root.
+pub const REPORTS_DIR_COMPONENTS: [&str; 2] = ["criterion", "reports"];
+
+/// Path components leading from the `target` directory to session sidecars
+pub const SESSIONS_DIR_COMPONENTS: [&str; 2] = ["criterion", "sessions"];
+
+/// Name of the file that holds the run configuration of a session
+pub const RUN_CONFIG_FILE_NAME: &str = "run_config.cbor";
+
+/// `chrono` format of the UTC start time within session directory names
+pub const SESSION_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Path components leading from the `target` directory to the cache of
+/// measurement summaries (see [`SummaryCache`](crate::cache::SummaryCache))
+pub const SUMMARY_CACHE_COMPONENTS: [&str; 2] = ["criterion", "criterion-cbor-cache.cbor"];
+
+/// Name of the directory that holds the artifacts of the measurements of a
+/// benchmark, within the benchmark directory
+pub const ARTIFACTS_DIR_NAME: &str = "artifacts";
+
+/// Maximal length of a benchmark data directory name, in bytes
+pub const MAX_DIRECTORY_NAME_LEN: usize = 64;
+
+/// Location of the data root within a `target` directory
+pub fn data_root(target_dir: impl AsRef<Path>) -> PathBuf {
+    let mut data_root = target_dir.as_ref().to_owned();
+    data_root.extend(DATA_ROOT_COMPONENTS);
+    data_root
+}
+
+/// Location of the session sidecar directory within a `target` directory
+pub fn sessions_dir(target_dir: impl AsRef<Path>) -> PathBuf {
+    let mut sessions_dir = target_dir.as_ref().to_owned();
+    sessions_dir.extend(SESSIONS_DIR_COMPONENTS);
+    sessions_dir
+}
+
+/// Location of the cache of measurement summaries within a `target` directory
+pub fn summary_cache(target_dir: impl AsRef<Path>) -> PathBuf {
+    let mut summary_cache = target_dir.as_ref().to_owned();
+    summary_cache.extend(SUMMARY_CACHE_COMPONENTS);
+    summary_cache
+}
+
+/// Location of the format marker file of a data root
+///
+/// See [`FORMAT_MARKER_FILE_NAME`].
+pub fn format_marker(data_root: &Path) -> PathBuf {
+    data_root
+        .parent()
+        .unwrap_or(data_root)
+        .join(FORMAT_MARKER_FILE_NAME)
+}
+
+/// Location of the `target` directory that contains a data root
+///
+/// This is the inverse of [`data_root()`]. Returns `None` if the specified path
+/// does not end with [`DATA_ROOT_COMPONENTS`].
+pub fn target_dir_of(data_root: &Path) -> Option<&Path> {
+    if !data_root.ends_with(DATA_ROOT_COMPONENTS.iter().collect::<PathBuf>()) {
+        return None;
+    }
+    data_root.ancestors().nth(DATA_ROOT_COMPONENTS.len())
+}
+
+/// Location of the artifact directory of a measurement
+///
+/// Artifacts are stored below the [`ARTIFACTS_DIR_NAME`] directory of the
+/// benchmark, in a directory named after the measurement file without its
+/// extension.
+pub fn artifacts_dir(measurement_path: &Path) -> PathBuf {
+    let benchmark_dir = measurement_path.parent().unwrap_or(Path::new(""));
+    benchmark_dir
+        .join(ARTIFACTS_DIR_NAME)
+        .join(measurement_path.file_stem().unwrap_or_default())
+}
+
+/// Truth that a directory holds measurement artifacts
+///
+/// A benchmark whose identifier maps to an [`ARTIFACTS_DIR_NAME`] directory
+/// would have its own metadata file there, which tells both cases apart.
+pub fn is_artifacts_dir(dir_path: &Path) -> bool {
+    let Some(parent) = dir_path.parent() else {
+        return false;
+    };
+    dir_path.file_name() == Some(ARTIFACTS_DIR_NAME.as_ref())
+        && parent.join(BENCHMARK_FILE_NAME).is_file()
+        && !dir_path.join(BENCHMARK_FILE_NAME).exists()
+}
+
+/// Relative path from the data root to the data directory of a benchmark
+///
+/// Each component of the benchmark identifier is turned into a directory name
+/// using [`make_filename_safe()`].
+pub fn benchmark_dir(id: &RawBenchmarkId) -> PathBuf {
+    let mut path = PathBuf::from(make_filename_safe(&id.group_or_function_id));
+    if let Some(function_id) = &id.function_id_in_group {
+        path.push(make_filename_safe(function_id));
+    }
+    if let Some(value_str) = &id.value_str {
+        path.push(make_filename_safe(value_str));
+    }
+    path
+}
+
+/// Turn a benchmark identifier component into a directory name
+///
+/// This follows the same rules as Criterion: characters that are not allowed
+/// in file names on some platforms are replaced with underscores, and the name
+/// is truncated to [`MAX_DIRECTORY_NAME_LEN`] bytes. On Windows, trailing
+/// whitespace is additionally trimmed and the name is lowercased.
+pub fn make_filename_safe(component: &str) -> String {
+    let mut name = component.replace(
+        &['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'][..],
+        "_",
+    );
+    if name.len() > MAX_DIRECTORY_NAME_LEN {
+        let boundary = (0..=MAX_DIRECTORY_NAME_LEN)
+            .rev()
+            .find(|&idx| name.is_char_boundary(idx))
+            .expect("Index 0 is always a char boundary");
+        name.truncate(boundary);
+    }
+    if cfg!(target_os = "windows") {
+        name = name.trim_end().to_lowercase();
+    }
+    name
+}
+
+/// Name of the measurement file for a measurement taken at a certain time
+///
+/// cargo-criterion names measurement files after the local time at which they
+/// were taken, with a precision of one second.
+pub fn measurement_file_name<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String {
+    format!(
+        "{MEASUREMENT_FILE_PREFIX}{}{MEASUREMENT_FILE_SUFFIX}",
+        datetime
+            .with_timezone(&Local)
+            .format(MEASUREMENT_DATETIME_FORMAT)
+    )
+}
+
+/// Truth that a file name follows the measurement file naming convention
+pub fn is_measurement_file(file_name: &str) -> bool {
+    parse_measurement_file_name(file_name).is_some()
+}
+
+/// Extract the local date and time from a measurement file name
+///
+/// Returns `None` if the file name does not follow the measurement file naming
+/// convention.
+pub fn parse_measurement_file_name(file_name: &str) -> Option<NaiveDateTime> {
+    let datetime = file_name
+        .strip_prefix(MEASUREMENT_FILE_PREFIX)?
+        .strip_suffix(MEASUREMENT_FILE_SUFFIX)?;
+    NaiveDateTime::parse_from_str(datetime, MEASUREMENT_DATETIME_FORMAT).ok()
+}
+
+/// Name of the sidecar directory of a session that started at a certain time
+///
+/// Unlike measurement files, session directories are named after UTC time, so
+/// that their names are not ambiguous around daylight saving time transitions.
+pub fn session_dir_name<Tz: TimeZone>(start: &DateTime<Tz>) -> String {
+    start
+        .with_timezone(&Utc)
+        .format(SESSION_DATETIME_FORMAT)
+        .to_string()
+}
+
+/// Extract the UTC start time from a session directory name
+///
+/// Returns `None` if the directory name does not follow the session directory
+/// naming convention.
+pub fn parse_session_dir_name(dir_name: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(dir_name, SESSION_DATETIME_FORMAT)
+        .ok()
+        .map(|datetime| datetime.and_utc())
+}
+
+/// What should happen when a data directory does not have the layout that
+/// cargo-criterion is expected to produce
+///
+/// This is configured via
+/// [`Search::invariant_checks()`](crate::Search::invariant_checks), and covers
+/// [stray entries](StrayEntryPolicy) within the data root, unless they are
+/// skipped, and benchmark directories that lack a `benchmark.cbor` file or
+/// measurements. Such layouts are not
+/// produced by cargo-criterion, but may result from manual edits or from
+/// interrupted copies of the data directory.
+#[derive(Clone, Default)]
+pub enum InvariantChecks {
+    /// Silently ignore unexpected entries and incomplete benchmarks
+    Off,
+
+    /// Ignore unexpected entries and incomplete benchmarks, but call a
+    /// function with the [`Error::UnexpectedLayout`] that describes each of
+    /// them, e.g. to log a warning
+    Warn(Arc<dyn Fn(&Error) + Send + Sync>),
+
+    /// Report each unexpected entry or incomplete benchmark as an
+    /// [`Error::UnexpectedLayout`] from the benchmark iterator, then carry on
+    ///
+    /// This is the default, which ensures that surprising layouts are noticed
+    /// by callers that propagate errors, while letting other callers skip the
+    /// offending data.
+    ///
+    /// **Breaking change:** the default used to be [`Panic`](Self::Panic).
+    /// Callers that ignored errors from the benchmark iterators, e.g. with
+    /// `filter_map(Result::ok)`, now skip incomplete benchmarks silently. Use
+    /// `Panic` explicitly to keep the former behavior.
+    #[default]
+    Report,
+
+    /// Panic on the first unexpected entry or incomplete benchmark
+    Panic,
+}
+//
+impl InvariantChecks {
+    /// Ignore unexpected entries and incomplete benchmarks, calling `callback`
+    /// with the error that describes each of them
+    pub fn warn(callback: impl Fn(&Error) + Send + Sync + 'static) -> Self {
+        Self::Warn(Arc::new(callback))
+    }
+
+    /// Check that an invariant holds for some path, and handle violations
+    ///
+    /// Returns `true` if the invariant holds, `false` if it does not and the
+    /// offending data should be ignored, and an error if the violation should
+    /// be reported.
+    pub(crate) fn check(
+        &self,
+        holds: bool,
+        path: &Path,
+        message: impl FnOnce() -> String,
+    ) -> Result<bool, Error> {
+        if !holds {
+            let error = || Error::UnexpectedLayout {
+                path: path.to_owned(),
+                message: message(),
+            };
+            match self {
+                Self::Off => {}
+                Self::Warn(callback) => callback(&error()),
+                Self::Report => return Err(error()),
+                Self::Panic => panic!("{}", error()),
+            }
+        }
+        Ok(holds)
+    }
+}
+//
+impl Debug for InvariantChecks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => f.write_str("Off"),
+            Self::Warn(_) => f.write_str("Warn(..)"),
+            Self::Report => f.write_str("Report"),
+            Self::Panic => f.write_str("Panic"),
+        }
+    }
+}
+
+/// What should happen to stray entries of a data directory
+///
+/// Stray entries are files that are neither [`BENCHMARK_FILE_NAME`] nor
+/// measurement files, like the `.DS_Store` files of macOS or the lock files of
+/// backup tools, along with symlinks and special files. This is configured via
+/// [`Search::on_stray_entry()`](crate::Search::on_stray_entry).
+#[derive(Clone, Default)]
+pub enum StrayEntryPolicy {
+    /// Handle each stray entry according to the [`InvariantChecks`] of the
+    /// search
+    ///
+    /// Stray entries are never enumerated, but the benchmarks around them
+    /// still are.
+    #[default]
+    Check,
+
+    /// Silently skip stray entries, and enumerate the benchmarks around them
+    Skip,
+
+    /// Skip stray entries like `Skip`, but call a function with the path of
+    /// each of them, e.g. to log a warning
+    Warn(Arc<dyn Fn(&Path) + Send + Sync>),
+}
+//
+impl StrayEntryPolicy {
+    /// Skip stray entries, calling `callback` with the path of each of them
+    pub fn warn(callback: impl Fn(&Path) + Send + Sync + 'static) -> Self {
+        Self::Warn(Arc::new(callback))
+    }
+
+    /// Truth that stray entries should be skipped
+    pub fn skips_entries(&self) -> bool {
+        !matches!(self, Self::Check)
+    }
+
+    /// Handle a stray entry that is being skipped
+    pub(crate) fn skip(&self, path: &Path) {
+        if let Self::Warn(callback) = self {
+            callback(path);
+        }
+    }
+}
+//
+impl Debug for StrayEntryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Check => f.write_str("Check"),
+            Self::Skip => f.write_str("Skip"),
+            Self::Warn(_) => f.write_str("Warn(..)"),
+        }
+    }
+}
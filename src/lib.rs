@@ -5,20 +5,113 @@
 //! [`find_all()`](Search::find_all) or
 //! [`find_in_paths()`](Search::find_in_paths) method of the resulting object to
 //! start enumerating data.
+//!
+//! If you only need the latest estimates of every benchmark, [`load_latest()`]
+//! does all of this in one call. Commonly used items can be brought into scope
+//! with `use criterion_cbor::prelude::*`.
+//!
+//! All data handles, from [`Search`] and [`Benchmark`] to the decoded
+//! [`MeasurementData`] and [`Dataset`](dataset::Dataset), are `Send` and
+//! `Sync`, so they can be processed by thread pools. Use
+//! [`Benchmark::into_owned_parts()`] to distribute the measurements of a
+//! benchmark across threads without borrowing the benchmark.
+//!
+//! Problems with the data directory, like malformed files or unexpected
+//! names, are reported as an [`Error`] rather than a panic, so that tools can
+//! skip the affected benchmarks and carry on with the rest of the data.
+
+pub mod assert;
+pub mod budgets;
+pub mod cache;
+pub mod catalog;
+mod cbor;
+pub mod ci;
+pub mod dataset;
+pub mod decode;
+mod error;
+pub mod filter;
+mod glob;
+pub mod history;
+pub mod layout;
+pub mod merge;
+pub mod messages;
+#[cfg(feature = "mirror")]
+pub mod mirror;
+pub mod prelude;
+#[cfg(feature = "profile")]
+pub mod profile;
+pub mod renames;
+pub mod report;
+pub mod runner;
+pub mod session;
+pub mod snapshot;
+pub mod stats;
+pub mod tags;
+#[cfg(test)]
+mod testing;
+pub mod units;
+pub mod validation;
+pub mod writer;
 
-use chrono::{DateTime, Local, MappedLocalTime, NaiveDateTime, TimeZone, Utc};
+pub use crate::error::{Error, SearchError};
+
+use crate::{
+    decode::{DecodeErrorPolicy, DecodeOptions, FormatHint, Validate},
+    layout::{DataPath, InvariantChecks, StrayEntryPolicy},
+    units::ValueUnit,
+};
+use chrono::{DateTime, Local, MappedLocalTime, TimeZone, Utc};
 use criterion::Throughput;
 #[cfg(doc)]
 use criterion::{BenchmarkGroup, Criterion};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
     cmp::Ordering,
     ffi::OsStr,
-    io,
-    iter::Peekable,
-    path::{Path, PathBuf},
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufReader},
+    iter::{FusedIterator, Peekable},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
-use walkdir::{DirEntry, WalkDir};
+use walkdir::{DirEntry, FilterEntry, WalkDir};
+
+/// Load the latest estimates of every benchmark in a Cargo project/workspace
+///
+/// This is a shortcut for the common case of enumerating all benchmarks with
+/// [`Search::in_cargo_root()`], then decoding their metadata and their latest
+/// measurement. Benchmarks are returned in data directory order. Use
+/// [`Search`] directly if you need more control over the search or access to
+/// older measurements.
+///
+/// An [`Error::Search`] is reported if the specified directory or its `target`
+/// directory does not exist.
+pub fn load_latest(
+    cargo_root: impl AsRef<Path>,
+) -> Result<Vec<(RawBenchmarkId, Estimates)>, Error> {
+    Search::try_in_cargo_root(cargo_root)?
+        .find_all()
+        .map(|bench| {
+            let bench = bench?;
+            let id = bench.metadata()?.id;
+            let estimates = bench.latest_measurement().data()?.estimates;
+            Ok((id, estimates))
+        })
+        .collect()
+}
+
+/// Environment variable that overrides the location of benchmark data
+///
+/// This is honored by [`Search::discover()`] and [`Dataset::discover()`], and
+/// can point to either a `target` directory, a Criterion data root, or a
+/// portable dataset file produced by [`Dataset::save()`].
+///
+/// [`Dataset::discover()`]: dataset::Dataset::discover()
+/// [`Dataset::save()`]: dataset::Dataset::save()
+pub const DATA_ENV_VAR: &str = "CRITERION_CBOR_DATA";
 
 /// Criterion benchmark data search
 ///
@@ -26,8 +119,11 @@ use walkdir::{DirEntry, WalkDir};
 /// specify where the `target` directory of the project is located.
 #[derive(Debug)]
 pub struct Search {
+    target_dir: Box<Path>,
     data_root: Box<Path>,
-    walker: walkdir::IntoIter,
+    prefix: PathBuf,
+    max_depth: Option<usize>,
+    options: SearchOptions,
 }
 //
 impl Search {
@@ -39,12 +135,59 @@ impl Search {
     ///
     /// # Panics
     ///
-    /// If the specified directory does not exist.
+    /// If the specified directory does not exist. Use
+    /// [`try_in_cargo_root()`](Self::try_in_cargo_root()) to handle this case.
     pub fn in_cargo_root(cargo_root: impl AsRef<Path>) -> Self {
-        // Find the Criterion data root
+        Self::try_in_cargo_root(cargo_root).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible version of [`in_cargo_root()`](Self::in_cargo_root())
+    ///
+    /// An error is returned if the Cargo root or its `target` directory does
+    /// not exist. Since the latter means that no benchmark has been run yet,
+    /// some tools will want to check [`SearchError::is_not_found()`] and
+    /// handle it as an empty set of benchmarks.
+    pub fn try_in_cargo_root(cargo_root: impl AsRef<Path>) -> Result<Self, SearchError> {
         let cargo_root = cargo_root.as_ref();
-        assert!(cargo_root.exists(), "Specified Cargo root does not exist");
-        Self::in_target_dir(cargo_root.join("target"))
+        SearchError::check_dir(cargo_root, |path| SearchError::CargoRootNotFound { path })?;
+        Self::try_in_target_dir(cargo_root.join("target"))
+    }
+
+    /// Locate benchmark data from the environment
+    ///
+    /// If the [`DATA_ENV_VAR`] environment variable is set, it is used as the
+    /// location of either the Criterion data root (if the path ends with the
+    /// [standard data root components](layout::DATA_ROOT_COMPONENTS)) or the
+    /// `target` directory. Otherwise, the current directory is assumed to be
+    /// the Cargo hierarchy root, as in [`in_cargo_root()`](Self::in_cargo_root()).
+    ///
+    /// Errors of kind [`io::ErrorKind::NotFound`] are reported if the
+    /// specified location does not exist, and errors of kind
+    /// [`io::ErrorKind::InvalidInput`] are reported if it is a portable
+    /// dataset file, which must be loaded with
+    /// [`Dataset::discover()`](dataset::Dataset::discover()) instead.
+    pub fn discover() -> io::Result<Self> {
+        let Some(location) = std::env::var_os(DATA_ENV_VAR).map(PathBuf::from) else {
+            return Ok(Self::try_in_cargo_root(std::env::current_dir()?)?);
+        };
+        let metadata = std::fs::metadata(&location).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("{DATA_ENV_VAR} points to {}: {e}", location.display()),
+            )
+        })?;
+        if !metadata.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{DATA_ENV_VAR} points to a dataset file, which cannot be searched: {}",
+                    location.display()
+                ),
+            ));
+        }
+        Ok(Self::try_in_target_dir(
+            layout::target_dir_of(&location).unwrap_or(&location),
+        )?)
     }
 
     /// Start by specifying the target directory location
@@ -54,58 +197,270 @@ impl Search {
     ///
     /// # Panics
     ///
-    /// If the specified directory does not exist.
+    /// If the specified directory does not exist. Use
+    /// [`try_in_target_dir()`](Self::try_in_target_dir()) to handle this case.
     pub fn in_target_dir(target_path: impl AsRef<Path>) -> Self {
+        Self::try_in_target_dir(target_path).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible version of [`in_target_dir()`](Self::in_target_dir())
+    pub fn try_in_target_dir(target_path: impl AsRef<Path>) -> Result<Self, SearchError> {
         // Find the Criterion data root
         let target_path = target_path.as_ref();
-        assert!(
-            target_path.exists(),
-            "Specified target directory does not exist"
-        );
-        let mut data_root = target_path.to_owned();
-        data_root.push("criterion");
-        data_root.push("data");
-        // This is the "timeline" field of cargo-criterion's Model, which is
-        // curently unused by cargo-criterion and always set to "main".
-        data_root.push("main");
-        let data_root = data_root.into_boxed_path();
-
-        // Set up the common directory-walking configuration
-        let walker = WalkDir::new(&data_root)
-            .min_depth(1)
-            .follow_root_links(false)
-            .sort_by(|entry1, entry2| {
-                // - Emit all files before emitting directories
-                // - Emit files in descending name order (this will yield all
-                //   measurement_xxx.cbor files first, sorted by decreasing
-                //   measurement date/time to put latest measurement first, then
-                //   the benchmark.cbor metadata file at the end)
-                // - Emit directories in ascending name order
-                let is_file_not_dir = |entry: &DirEntry| -> bool {
-                    let ty = entry.file_type();
-                    assert!(
-                        ty.is_dir() || ty.is_file(),
-                        "Criterion's data directory should only contain files and directories"
-                    );
-                    ty.is_file()
-                };
-                match (is_file_not_dir(entry1), is_file_not_dir(entry2)) {
-                    // Files before directories
-                    (true, false) => Ordering::Less,
-                    (false, true) => Ordering::Greater,
-                    // Files in descending name order
-                    (true, true) => entry2.file_name().cmp(entry1.file_name()),
-                    // Directories in ascending name order
-                    (false, false) => entry1.file_name().cmp(entry2.file_name()),
-                }
-            })
-            .into_iter();
-        Self { data_root, walker }
+        SearchError::check_dir(target_path, |path| SearchError::TargetDirNotFound { path })?;
+        let data_root = layout::data_root(target_path).into_boxed_path();
+        Ok(Self {
+            target_dir: target_path.into(),
+            data_root,
+            prefix: PathBuf::new(),
+            max_depth: None,
+            options: SearchOptions::default(),
+        })
+    }
+
+    /// Start by specifying a machine-local mirror of benchmark data
+    ///
+    /// See the [`mirror`] module for more information. An error is returned
+    /// if the mirror directory was deleted since the [`Mirror`](mirror::Mirror)
+    /// was set up.
+    #[cfg(feature = "mirror")]
+    pub fn in_mirror(mirror: &mirror::Mirror) -> Result<Self, SearchError> {
+        Self::try_in_target_dir(mirror.root())
+    }
+
+    /// Location of the `target` directory that this search is looking into
+    pub fn target_dir(&self) -> &Path {
+        &self.target_dir
+    }
+
+    /// Location of the Criterion data root within the `target` directory
+    ///
+    /// Relative benchmark paths like [`Benchmark::path_from_data_root()`] are
+    /// relative to this directory. Note that this directory does not exist if
+    /// no benchmark has been run yet.
+    pub fn data_root(&self) -> &Path {
+        &self.data_root
+    }
+
+    /// List the sessions recorded in the `target` directory
+    ///
+    /// See the [`session`] module for more information.
+    pub fn sessions(&self) -> io::Result<Vec<session::Session>> {
+        session::sessions(&self.target_dir)
+    }
+
+    /// Only look for benchmarks under a certain path from the data root
+    ///
+    /// For example, `under_prefix("my_group")` will only look for the
+    /// benchmarks of the `my_group` benchmark group. Unlike filtering with
+    /// [`find_in_paths()`](Self::find_in_paths()), this does not walk the
+    /// rest of the data directory at all, which is much faster in large
+    /// workspaces. Multi-component prefixes like `"my_group/my_function"` are
+    /// accepted, and paths of the resulting benchmarks remain relative to the
+    /// data root.
+    ///
+    /// # Panics
+    ///
+    /// If the prefix is not a relative path that only contains normal
+    /// components. Use [`try_under_prefix()`](Self::try_under_prefix()) to
+    /// handle this case, e.g. for prefixes that come from user input.
+    pub fn under_prefix(self, prefix: impl AsRef<Path>) -> Self {
+        self.try_under_prefix(prefix)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible version of [`under_prefix()`](Self::under_prefix())
+    ///
+    /// A [`SearchError::InvalidPrefix`] error is returned if the prefix is
+    /// absolute, or contains `.` or `..` components.
+    pub fn try_under_prefix(mut self, prefix: impl AsRef<Path>) -> Result<Self, SearchError> {
+        let prefix = prefix.as_ref();
+        if !prefix
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(SearchError::InvalidPrefix {
+                path: prefix.to_owned(),
+            });
+        }
+        self.prefix = prefix.to_owned();
+        Ok(self)
+    }
+
+    /// Ignore benchmark directories deeper than `max_depth`
+    ///
+    /// Depth is counted from the data root as in [`DataDirectory::depth()`].
+    /// Criterion's own benchmark identifiers never produce directories deeper
+    /// than 3 (group, function and value), so deeper directories can only come
+    /// from other tools or from data corruption. Use this option to avoid
+    /// walking such unexpectedly deep trees.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Specify the unit of the values recorded by the benchmarks
+    ///
+    /// By default, values are assumed to be durations in nanoseconds, as
+    /// recorded by Criterion's default `WallTime` measurement. If your
+    /// benchmarks use a custom [`criterion::measurement::Measurement`], you
+    /// should use this method to specify what unit it records values in.
+    pub fn value_unit(mut self, unit: ValueUnit) -> Self {
+        self.options.value_unit = unit;
+        self
+    }
+
+    /// Ignore files that were modified less than `min_age` ago
+    ///
+    /// When `cargo criterion` is running, it may leave partially written files
+    /// in the data directory, which will fail to decode. If you are monitoring
+    /// the data directory of a live benchmark run, you can use this option to
+    /// ignore such in-flight files.
+    ///
+    /// Recently modified measurement files are skipped, and benchmarks whose
+    /// `benchmark.cbor` metadata file was recently modified are skipped
+    /// entirely. Note that as a result, [`BenchmarkMetadata::latest_record`]
+    /// may point to a measurement that is not enumerated.
+    pub fn ignore_newer_than(mut self, min_age: Duration) -> Self {
+        self.options.min_file_age = Some(min_age);
+        self
+    }
+
+    /// Specify what should happen when a malformed CBOR file is encountered
+    ///
+    /// By default, decoding errors are reported to the caller of the method
+    /// that decodes the file. See [`DecodeErrorPolicy`] for alternatives.
+    pub fn on_decode_error(mut self, policy: DecodeErrorPolicy) -> Self {
+        self.options.decode_error_policy = policy;
+        self
+    }
+
+    /// Specify how CBOR files should be decoded
+    ///
+    /// This notably lets you enable strict validation of benchmark data. See
+    /// [`DecodeOptions`] for details.
+    pub fn decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options.decode_options = options;
+        self
+    }
+
+    /// Specify what should happen when the data directory does not have the
+    /// expected layout
+    ///
+    /// By default, the benchmark iterators report each surprising entry as an
+    /// [`Error::UnexpectedLayout`], then carry on. They used to panic instead,
+    /// which [`InvariantChecks::Panic`] still does. Tools may also want to
+    /// silently ignore the affected entries, see [`InvariantChecks`] for
+    /// details.
+    pub fn invariant_checks(mut self, checks: InvariantChecks) -> Self {
+        self.options.invariant_checks = checks;
+        self
+    }
+
+    /// Specify what should happen to stray files and symlinks
+    ///
+    /// Data directories often pick up files that cargo-criterion did not
+    /// write, like the `.DS_Store` files of macOS. By default, each of them is
+    /// handled according to the [invariant checks](Self::invariant_checks) of
+    /// the search, e.g. reported as an [`Error::UnexpectedLayout`]. This
+    /// lenient mode instead skips the stray entries, see [`StrayEntryPolicy`]
+    /// for details.
+    pub fn on_stray_entry(mut self, policy: StrayEntryPolicy) -> Self {
+        self.options.stray_entry_policy = policy;
+        self
+    }
+
+    /// Specify whether files and directories with non-Unicode names should be
+    /// searched
+    ///
+    /// Criterion never generates such names, so by default they are reported
+    /// as an [`Error::NonUnicodeName`] and the contents of such directories are
+    /// skipped. On filesystems with unusual name encodings, enabling this lossy
+    /// mode lets these benchmarks be enumerated and read. Their names are then
+    /// converted lossily by string accessors like
+    /// [`DataDirectory::dir_name()`] and [`Benchmark::slash_path()`], while
+    /// accessors like [`DataDirectory::dir_name_os()`] and
+    /// [`Benchmark::path_from_data_root_os()`] provide the exact names.
+    pub fn lossy_names(mut self, lossy: bool) -> Self {
+        self.options.lossy_names = lossy;
+        self
     }
 
     /// Find all benchmark data in the specified Cargo project/workspace
-    pub fn find_all(self) -> impl Iterator<Item = walkdir::Result<Benchmark>> {
-        BenchmarkIter::new(self.data_root, self.walker)
+    ///
+    /// This is also what iterating over a `Search` does.
+    pub fn find_all(self) -> FindAll {
+        let walker = self
+            .walker()
+            .filter_entry(is_not_artifacts_dir as fn(&DirEntry) -> bool);
+        let no_data = !self.walk_root().exists();
+        FindAll(BenchmarkIter::new(
+            self.data_root,
+            walker,
+            no_data,
+            self.options,
+        ))
+    }
+
+    /// Load all benchmark data into memory
+    ///
+    /// This decodes the metadata and every measurement of every benchmark,
+    /// which is convenient for analysis scripts but can take a while for large
+    /// data directories. Loading carries on after errors, which are aggregated
+    /// into a [`LoadReport`](dataset::LoadReport) that also contains all the
+    /// data that could be loaded. Malformed files are handled according to the
+    /// [decoding error policy](Self::on_decode_error) of the search.
+    pub fn collect_all(self) -> Result<dataset::Dataset, dataset::LoadReport> {
+        dataset::Dataset::collect(self)
+    }
+
+    /// Decode the metadata of all benchmarks
+    ///
+    /// This returns the path of each benchmark's data directory, relative to
+    /// the data root, along with its decoded metadata, in data directory order.
+    /// Metadata files are small, so this is much faster than
+    /// [`collect_all()`](Self::collect_all()) when measurements are not needed,
+    /// e.g. in order to list benchmarks. If `parallel` is true, the files are
+    /// decoded by as many threads as the system can run in parallel.
+    ///
+    /// The first error that is encountered is returned, as in [`find_all()`]
+    /// and [`Benchmark::metadata()`].
+    ///
+    /// [`find_all()`]: Self::find_all()
+    pub fn decode_all_metadata(
+        self,
+        parallel: bool,
+    ) -> Result<Vec<(PathBuf, BenchmarkMetadata)>, Error> {
+        let benchmarks = self.find_all().collect::<Result<Vec<_>, _>>()?;
+        let decode = |benchmarks: &[Benchmark]| {
+            benchmarks
+                .iter()
+                .map(|bench| {
+                    let path = bench.path_from_data_root().as_path().to_owned();
+                    Ok((path, bench.metadata()?))
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let num_threads = if parallel {
+            std::thread::available_parallelism().map_or(1, usize::from)
+        } else {
+            1
+        };
+        if num_threads == 1 || benchmarks.len() < 2 {
+            return decode(&benchmarks);
+        }
+        let chunk_size = benchmarks.len().div_ceil(num_threads);
+        std::thread::scope(|scope| {
+            let workers = benchmarks
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || decode(chunk)))
+                .collect::<Vec<_>>();
+            let mut metadata = Vec::with_capacity(benchmarks.len());
+            for worker in workers {
+                metadata.extend(worker.join().expect("Metadata decoding should not panic")?);
+            }
+            Ok(metadata)
+        })
     }
 
     /// Find benchmark data whose filesystem path matches a certain predicate
@@ -122,28 +477,130 @@ impl Search {
     /// filesystem path. So if you want to select benchmark data from location
     /// `a/b/c` only, your filter should successively match for three
     /// directories: first `a` at depth 1, then `b` at depth 2, and finally `c`
-    /// at depth 3.
+    /// at depth 3. If a prefix was specified with
+    /// [`under_prefix()`](Self::under_prefix()), the predicate is not called
+    /// for the directories of the prefix.
     pub fn find_in_paths<'path_filter>(
         self,
         mut path_filter: impl FnMut(DataDirectory) -> bool + 'path_filter,
-    ) -> impl Iterator<Item = walkdir::Result<Benchmark>> + 'path_filter {
+    ) -> impl FusedIterator<Item = Result<Benchmark, Error>> + 'path_filter {
         let data_root = self.data_root.clone();
-        let walker = self.walker.filter_entry(move |entry| {
-            if entry.file_type().is_dir() {
-                path_filter(DataDirectory::new(&data_root, entry))
+        let no_data = !self.walk_root().exists();
+        let lossy_names = self.options.lossy_names;
+        let walker = self.walker().filter_entry(move |entry| {
+            // Directories with non-Unicode names are reported by the benchmark
+            // iterator, without reaching the filter, unless names are lossy
+            if entry.file_type().is_dir()
+                && !entry.path_is_symlink()
+                && (lossy_names || is_unicode(entry))
+            {
+                is_not_artifacts_dir(entry) && path_filter(DataDirectory::new(&data_root, entry))
             } else {
                 true
             }
         });
-        BenchmarkIter::new(self.data_root, walker)
+        BenchmarkIter::new(self.data_root, walker, no_data, self.options)
+    }
+
+    /// Root of the directory walk
+    fn walk_root(&self) -> PathBuf {
+        self.data_root.join(&self.prefix)
+    }
+
+    /// Set up the directory walk
+    fn walker(&self) -> walkdir::IntoIter {
+        let mut walker = WalkDir::new(self.walk_root()).min_depth(1);
+        if let Some(max_depth) = self.max_depth {
+            // Walk depth is relative to the prefix, and benchmark data files
+            // lie one level below their benchmark directory
+            let prefix_depth = self.prefix.components().count();
+            walker = walker.max_depth((max_depth + 1).saturating_sub(prefix_depth));
+        }
+        walker
+            .follow_root_links(false)
+            .sort_by(|entry1, entry2| {
+                // - Emit all files before emitting directories
+                // - Emit files in descending name order (this will yield all
+                //   measurement_xxx.cbor files first, sorted by decreasing
+                //   measurement date/time to put latest measurement first, then
+                //   the benchmark.cbor metadata file at the end)
+                // - Emit directories in ascending name order
+                //
+                // Other entries, like symlinks, are sorted as files here and
+                // reported by the benchmark iterator.
+                let is_file_not_dir = |entry: &DirEntry| -> bool { !entry.file_type().is_dir() };
+                match (is_file_not_dir(entry1), is_file_not_dir(entry2)) {
+                    // Files before directories
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    // Files in descending name order
+                    (true, true) => entry2.file_name().cmp(entry1.file_name()),
+                    // Directories in ascending name order
+                    (false, false) => entry1.file_name().cmp(entry2.file_name()),
+                }
+            })
+            .into_iter()
     }
 }
+//
+impl IntoIterator for Search {
+    type Item = Result<Benchmark, Error>;
+    type IntoIter = FindAll;
+
+    fn into_iter(self) -> FindAll {
+        self.find_all()
+    }
+}
+
+/// Configuration of a [`Search`]
+#[derive(Clone, Debug, Default)]
+struct SearchOptions {
+    /// Unit of the values recorded by the benchmarks
+    value_unit: ValueUnit,
+
+    /// Files modified more recently than this are ignored
+    min_file_age: Option<Duration>,
+
+    /// What should happen when a malformed CBOR file is encountered
+    decode_error_policy: DecodeErrorPolicy,
+
+    /// How CBOR files should be decoded
+    decode_options: DecodeOptions,
+
+    /// What should happen when the data directory has an unexpected layout
+    invariant_checks: InvariantChecks,
+
+    /// What should happen to stray entries of the data directory
+    stray_entry_policy: StrayEntryPolicy,
+
+    /// Truth that entries with non-Unicode names should be searched
+    lossy_names: bool,
+}
+
+/// Iterator over all benchmarks of a [`Search`]
+///
+/// This is produced by [`Search::find_all()`].
+pub struct FindAll(BenchmarkIter<FindAllWalker>);
+
+/// Directory walker of [`FindAll`], which skips measurement artifacts
+type FindAllWalker = FilterEntry<walkdir::IntoIter, fn(&DirEntry) -> bool>;
+//
+impl Iterator for FindAll {
+    type Item = Result<Benchmark, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+//
+impl FusedIterator for FindAll {}
 
 /// Criterion benchmark data directory
 #[derive(Debug)]
 pub struct DataDirectory<'dirwalk> {
     data_root: &'dirwalk Path,
     entry: &'dirwalk DirEntry,
+    name: Cow<'dirwalk, str>,
 }
 //
 impl<'dirwalk> DataDirectory<'dirwalk> {
@@ -151,15 +608,24 @@ impl<'dirwalk> DataDirectory<'dirwalk> {
     /// user-irrelevant details
     fn new(data_root: &'dirwalk Path, entry: &'dirwalk DirEntry) -> Self {
         debug_assert!(!entry.path_is_symlink() && entry.file_type().is_dir() && entry.depth() > 0);
-        Self { data_root, entry }
+        Self {
+            data_root,
+            entry,
+            name: entry.file_name().to_string_lossy(),
+        }
     }
 
     /// Name of this data directory (without the path leading to it)
+    ///
+    /// Non-Unicode names, which are only found by searches with
+    /// [lossy names](Search::lossy_names), are converted lossily.
     pub fn dir_name(&self) -> &str {
-        self.entry
-            .file_name()
-            .to_str()
-            .expect("Criterion should not generate non-Unicode names")
+        &self.name
+    }
+
+    /// Name of this data directory, as stored in the filesystem
+    pub fn dir_name_os(&self) -> &'dirwalk OsStr {
+        self.entry.file_name()
     }
 
     /// Depth at which this data directory appears
@@ -167,16 +633,69 @@ impl<'dirwalk> DataDirectory<'dirwalk> {
     /// Top-level data directories have depth 1, their children have depth 2,
     /// their grandchildren have depth 3, and so on.
     pub fn depth(&self) -> usize {
-        self.entry.depth()
+        self.path_from_data_root().depth()
     }
 
     /// Relative path to this data directory from the Criterion data root
-    pub fn path_from_data_root(&self) -> &Path {
+    pub fn path_from_data_root(&self) -> DataPath<'dirwalk> {
+        DataPath::new(self.path_from_data_root_os())
+    }
+
+    /// Relative path to this data directory from the Criterion data root, as
+    /// stored in the filesystem
+    pub fn path_from_data_root_os(&self) -> &'dirwalk Path {
         self.entry
             .path()
             .strip_prefix(self.data_root)
             .expect("Walkdir should prefix entry paths with the search root path")
     }
+
+    /// Relative path to this data directory from the Criterion data root, as a
+    /// `/`-separated string (see [`path_to_slash()`])
+    pub fn slash_path(&self) -> String {
+        self.path_from_data_root().to_slash()
+    }
+
+    /// Names of the data directories leading to this one from the data root,
+    /// including this one
+    ///
+    /// The first name is that of the top-level group or function directory,
+    /// and the last one is [`dir_name()`](Self::dir_name). This lets
+    /// [`Search::find_in_paths()`] predicates take decisions based on the
+    /// directories that were already selected, e.g. only accept a value
+    /// directory if it lies within a certain group:
+    ///
+    /// ```no_run
+    /// # use criterion_cbor::Search;
+    /// let large_ffts = Search::in_cargo_root(".").find_in_paths(|dir| {
+    ///     let components = dir.components().collect::<Vec<_>>();
+    ///     match &components[..] {
+    ///         [group] => group == "fft",
+    ///         [_, _function] => true,
+    ///         [_, _, value] => value.parse::<u32>().is_ok_and(|n| n >= 1024),
+    ///         _ => false,
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// Like [`dir_name()`](Self::dir_name), non-Unicode names are converted
+    /// lossily.
+    pub fn components(&self) -> impl DoubleEndedIterator<Item = Cow<'dirwalk, str>> {
+        self.path_from_data_root_os()
+            .iter()
+            .map(OsStr::to_string_lossy)
+    }
+
+    /// Paths of the parent data directories of this one, from the nearest to
+    /// the top-level one
+    pub fn ancestors(&self) -> impl Iterator<Item = DataPath<'dirwalk>> {
+        self.path_from_data_root()
+            .as_path()
+            .ancestors()
+            .skip(1)
+            .take(self.depth().saturating_sub(1))
+            .map(DataPath::new)
+    }
 }
 
 /// Benchmark iterator
@@ -193,12 +712,19 @@ struct BenchmarkIter<Walker: Iterator> {
     /// Files seen so far in the current directory
     files_in_current_dir: Vec<DirEntry>,
 
+    /// Directory with a non-Unicode name that was reported as an error, and
+    /// whose contents should be skipped
+    non_unicode_dir: Option<PathBuf>,
+
     /// There is no benchmark data and this iterator should always yield None
     ///
     /// This is used to work around the fact that `walkdir` returns errors when
     /// the data directory to be walked does not exists, whereas we want to
     /// treat this as a normal situation where there is no benchmark data.
     no_data: bool,
+
+    /// Search configuration
+    options: Arc<SearchOptions>,
 }
 //
 impl<Walker: Iterator> BenchmarkIter<Walker> {
@@ -206,22 +732,88 @@ impl<Walker: Iterator> BenchmarkIter<Walker> {
     ///
     /// This is an implementation detail of [`Search`], and it is assumed that
     /// all preparations from [`Search::in_cargo_root()`] have been done.
-    fn new(data_root: Box<Path>, walker: Walker) -> Self {
-        let no_data = !data_root.exists();
+    fn new(data_root: Box<Path>, walker: Walker, no_data: bool, options: SearchOptions) -> Self {
         BenchmarkIter {
             data_root,
             walker: walker.peekable(),
             files_in_current_dir: Vec::new(),
+            non_unicode_dir: None,
             no_data,
+            options: Arc::new(options),
         }
     }
 
     /// Reached end of file list for current depth, produce a Benchmark from it
-    fn emit_benchmark(&mut self) -> Option<walkdir::Result<Benchmark>> {
+    ///
+    /// Returns `None` if there is no file or if the benchmark should be skipped
+    /// because of the search options.
+    fn emit_benchmark(&mut self) -> Option<Result<Benchmark, Error>> {
         // Last file will be benchmark.cbor due to the sorting we applied
         let metadata = self.files_in_current_dir.pop()?;
-        let measurements = std::mem::take(&mut self.files_in_current_dir).into_boxed_slice();
-        Some(Ok(Benchmark::new(&self.data_root, metadata, measurements)))
+        let mut measurements = std::mem::take(&mut self.files_in_current_dir);
+
+        // Skip directories that do not look like benchmark data directories
+        let checks = &self.options.invariant_checks;
+        let dir_path = metadata.path().parent().unwrap_or(&self.data_root);
+        let is_complete = checks
+            .check(
+                metadata.file_name() == layout::BENCHMARK_FILE_NAME,
+                dir_path,
+                || {
+                    format!(
+                        "Benchmark directory {} has no {} file",
+                        dir_path.display(),
+                        layout::BENCHMARK_FILE_NAME
+                    )
+                },
+            )
+            .and_then(|is_complete| {
+                Ok(is_complete
+                    && checks.check(!measurements.is_empty(), dir_path, || {
+                        format!(
+                            "Benchmark directory {} has no measurements",
+                            dir_path.display()
+                        )
+                    })?)
+            });
+        match is_complete {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(error) => return Some(Err(error)),
+        }
+
+        // Skip files that may still be in the process of being written
+        if let Some(min_age) = self.options.min_file_age {
+            let is_too_recent = |entry: &DirEntry| {
+                // Files whose age cannot be determined are conservatively
+                // treated as too recent
+                let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+                modified
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_none_or(|age| age < min_age)
+            };
+            if is_too_recent(&metadata) {
+                return None;
+            }
+            measurements.retain(|entry| !is_too_recent(entry));
+            if measurements.is_empty() {
+                return None;
+            }
+        }
+
+        let benchmark = Benchmark::new(
+            &self.data_root,
+            metadata,
+            measurements.into_boxed_slice(),
+            self.options.clone(),
+        );
+
+        // If requested, skip benchmarks with malformed metadata
+        let policy = &self.options.decode_error_policy;
+        if policy.skips_errors() && policy.handle(benchmark.metadata()).is_none() {
+            return None;
+        }
+        Some(Ok(benchmark))
     }
 }
 //
@@ -229,7 +821,7 @@ impl<Walker> Iterator for BenchmarkIter<Walker>
 where
     Walker: Iterator<Item = walkdir::Result<DirEntry>>,
 {
-    type Item = walkdir::Result<Benchmark>;
+    type Item = Result<Benchmark, Error>;
     fn next(&mut self) -> Option<Self::Item> {
         // Yield None if there is no benchmark data
         if self.no_data {
@@ -242,24 +834,68 @@ where
             let entry = match self.walker.peek() {
                 Some(Ok(entry)) => entry,
                 Some(Err(_)) => {
-                    return self
-                        .walker
-                        .next()
-                        .map(|err| err.map(|_| unreachable!("Peeked Err() above")))
+                    return self.walker.next().map(|err| {
+                        err.map(|_| unreachable!("Peeked Err() above"))
+                            .map_err(Error::from)
+                    })
                 }
                 None => return self.emit_benchmark(),
             };
 
-            // Makes sure entries meet expectations
+            // Report entries with non-Unicode names, which Criterion never
+            // generates, and skip the contents of such directories
+            if let Some(non_unicode_dir) = &self.non_unicode_dir {
+                if entry.path().starts_with(non_unicode_dir) {
+                    self.walker.next();
+                    continue 'files;
+                }
+                self.non_unicode_dir = None;
+            }
+
+            // Skip stray entries if requested
+            let policy = &self.options.stray_entry_policy;
+            if policy.skips_entries() && is_stray(entry) {
+                policy.skip(entry.path());
+                self.walker.next();
+                continue 'files;
+            }
+            if !self.options.lossy_names && !is_unicode(entry) {
+                let path = entry.path().to_owned();
+                if entry.file_type().is_dir() && !entry.path_is_symlink() {
+                    self.non_unicode_dir = Some(path.clone());
+                }
+                self.walker.next();
+                return Some(Err(Error::NonUnicodeName { path }));
+            }
+
+            // Make sure entries meet expectations, skipping them otherwise.
+            // Stray entries are handled one by one, so that they do not get
+            // mistaken for the metadata file of the benchmark around them.
             let ty = entry.file_type();
-            assert!(
-                !entry.path_is_symlink(),
-                "No symlink expected in Criterion data directory"
-            );
-            assert!(
-                ty.is_file() || ty.is_dir(),
-                "Only files & subdirectories expected inside of Criterion data directory"
-            );
+            if is_stray(entry) {
+                let message = || {
+                    let what = if entry.path_is_symlink() {
+                        "symlink"
+                    } else if ty.is_file() {
+                        "file"
+                    } else {
+                        "special file"
+                    };
+                    format!(
+                        "Encountered unexpected {what} {} in Criterion data directory",
+                        entry.path().display()
+                    )
+                };
+                let checked = self
+                    .options
+                    .invariant_checks
+                    .check(false, entry.path(), message);
+                self.walker.next();
+                match checked {
+                    Ok(_) => continue 'files,
+                    Err(error) => return Some(Err(error)),
+                }
+            }
             debug_assert!(
                 entry.depth() >= 1,
                 "Root directory should filtered out by min_depth"
@@ -284,8 +920,12 @@ where
                     // This not a file from the same benchmark directory. Flush
                     // all files seen so far into a new benchmark, and yield
                     // that benchmark. We'll get back to the current entry next
-                    // time Iterator::next() is called.
-                    return self.emit_benchmark();
+                    // time Iterator::next() is called, or right away if the
+                    // benchmark is skipped.
+                    if let Some(benchmark) = self.emit_benchmark() {
+                        return Some(benchmark);
+                    }
+                    continue 'files;
                 }
             }
 
@@ -306,6 +946,13 @@ where
         }
     }
 }
+//
+// Once the walker is exhausted, the last benchmark is emitted and the list of
+// pending files stays empty, so the iterator keeps yielding None
+impl<Walker> FusedIterator for BenchmarkIter<Walker> where
+    Walker: FusedIterator<Item = walkdir::Result<DirEntry>>
+{
+}
 
 /// Benchmark for which `cargo criterion` has recorded data
 #[derive(Debug)]
@@ -313,13 +960,19 @@ pub struct Benchmark {
     path_from_data_root: Box<Path>,
     metadata: DirEntry,
     measurements: Box<[DirEntry]>,
+    options: Arc<SearchOptions>,
 }
 //
 impl Benchmark {
     /// If a directory contains benchmark data, let the user access it
-    fn new(data_root: &Path, metadata: DirEntry, measurements: Box<[DirEntry]>) -> Self {
+    fn new(
+        data_root: &Path,
+        metadata: DirEntry,
+        measurements: Box<[DirEntry]>,
+        options: Arc<SearchOptions>,
+    ) -> Self {
         assert!(
-            metadata.file_type().is_file() && metadata.file_name() == "benchmark.cbor",
+            metadata.file_type().is_file() && metadata.file_name() == layout::BENCHMARK_FILE_NAME,
             "Encountered unexpected file {metadata:?} in Criterion data directory"
         );
         assert!(
@@ -337,28 +990,180 @@ impl Benchmark {
             path_from_data_root: path_from_data_root.into(),
             metadata,
             measurements,
+            options,
         }
     }
 
     /// Relative path to this benchmark's data directory from the Criterion data root
-    pub fn path_from_data_root(&self) -> &Path {
+    pub fn path_from_data_root(&self) -> DataPath<'_> {
+        DataPath::new(&self.path_from_data_root)
+    }
+
+    /// Relative path to this benchmark's data directory from the Criterion
+    /// data root, as stored in the filesystem
+    ///
+    /// Unlike [`slash_path()`](Self::slash_path), this preserves non-Unicode
+    /// names, which are only found by searches with
+    /// [lossy names](Search::lossy_names).
+    pub fn path_from_data_root_os(&self) -> &Path {
         &self.path_from_data_root
     }
 
+    /// Relative path to this benchmark's data directory from the Criterion data
+    /// root, as a `/`-separated string (see [`path_to_slash()`])
+    pub fn slash_path(&self) -> String {
+        self.path_from_data_root().to_slash()
+    }
+
+    /// Full path to this benchmark's data directory
+    ///
+    /// This is the [data root](Search::data_root) of the search that found the
+    /// benchmark, followed by [`path_from_data_root()`](Self::path_from_data_root).
+    /// It is thus absolute if the search was started from an absolute path.
+    /// This is typically used to tell users which directory to inspect or
+    /// delete.
+    pub fn dir_path(&self) -> &Path {
+        self.metadata
+            .path()
+            .parent()
+            .expect("Detected benchmark.cbor file should lie inside a parent directory")
+    }
+
+    /// Best guess of the cargo-criterion version that produced this
+    /// benchmark's data
+    ///
+    /// If the version was recorded in a [format marker](layout::format_marker)
+    /// next to the data root, it is returned. Otherwise, the fields of the
+    /// latest measurement are inspected. Data with incompatible hints should
+    /// not be compared without warning the user, as the meaning of some fields
+    /// may have changed between cargo-criterion versions.
+    pub fn format_hint(&self) -> Result<FormatHint, Error> {
+        let data_root = self
+            .dir_path()
+            .ancestors()
+            .nth(self.path_from_data_root.components().count())
+            .expect("Benchmark directories should lie inside of the data root");
+        let marker = layout::format_marker(data_root);
+        match std::fs::read_to_string(&marker) {
+            Ok(version) => return Ok(FormatHint::Recorded(version.trim().to_owned())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(source) => {
+                return Err(Error::Io {
+                    path: marker,
+                    source,
+                })
+            }
+        }
+        let latest = self.latest_measurement();
+        decode::measurement_format(latest.path())
+            .map_err(|error| Error::reading(latest.path(), error))
+    }
+
+    /// Unit of the values recorded by this benchmark
+    ///
+    /// See [`Search::value_unit()`] for more information.
+    pub fn value_unit(&self) -> &ValueUnit {
+        &self.options.value_unit
+    }
+
     /// Read this benchmark's metadata
-    pub fn metadata(&self) -> io::Result<BenchmarkMetadata> {
-        let data = std::fs::read(self.metadata.path())?;
-        Ok(serde_cbor::from_slice(&data[..]).expect("Failed to deserialize benchmark metadata"))
+    ///
+    /// A malformed metadata file is reported as an [`Error::Decode`].
+    pub fn metadata(&self) -> Result<BenchmarkMetadata, Error> {
+        let path = self.metadata.path();
+        decode::read_validated(path, &self.options.decode_options)
+            .map_err(|error| Error::reading(path, error))
     }
 
     /// Enumerate this benchmark's measurements
-    pub fn measurements(&self) -> impl Iterator<Item = Measurement> + '_ {
-        self.measurements.iter().map(Measurement::new)
+    ///
+    /// Measurements are enumerated from the latest to the oldest one.
+    pub fn measurements(&self) -> impl Iterator<Item = Measurement<'_>> + '_ {
+        self.measurements
+            .iter()
+            .map(|entry| Measurement::new(entry, &self.options.decode_options))
+    }
+
+    /// Enumerate and read this benchmark's measurements
+    ///
+    /// Measurements are enumerated from the latest to the oldest one. Malformed
+    /// measurement files are handled according to the
+    /// [decoding error policy](Search::on_decode_error) of the search.
+    pub fn decoded_measurements(
+        &self,
+    ) -> impl Iterator<Item = Result<(Measurement<'_>, MeasurementData), Error>> + '_ {
+        self.measurements().filter_map(|measurement| {
+            self.options
+                .decode_error_policy
+                .handle(measurement.data())
+                .map(|result| result.map(|data| (measurement, data)))
+        })
+    }
+
+    /// Split this benchmark into owned parts
+    ///
+    /// Unlike [`Measurement`]s, which borrow the benchmark that they come from,
+    /// the resulting [`OwnedMeasurement`]s can be sent to other threads
+    /// independently. Call [`metadata()`](Self::metadata) beforehand if you need
+    /// the benchmark's metadata.
+    pub fn into_owned_parts(self) -> BenchmarkParts {
+        let decode_options = self.options.decode_options;
+        BenchmarkParts {
+            path_from_data_root: self.path_from_data_root.into(),
+            metadata_path: self.metadata.into_path(),
+            measurements: self
+                .measurements
+                .into_vec()
+                .into_iter()
+                .map(|entry| OwnedMeasurement {
+                    entry,
+                    decode_options,
+                })
+                .collect(),
+        }
+    }
+
+    /// Latest measurement of this benchmark
+    pub fn latest_measurement(&self) -> Measurement<'_> {
+        Measurement::new(
+            self.measurements
+                .first()
+                .expect("Benchmarks should have at least one measurement"),
+            &self.options.decode_options,
+        )
+    }
+}
+
+/// Owned parts of a [`Benchmark`], produced by [`Benchmark::into_owned_parts()`]
+#[derive(Debug)]
+pub struct BenchmarkParts {
+    /// Relative path to the benchmark's data directory from the Criterion data
+    /// root
+    pub path_from_data_root: PathBuf,
+
+    /// Location of the benchmark's `benchmark.cbor` metadata file
+    pub metadata_path: PathBuf,
+
+    /// Measurements of the benchmark, from the latest to the oldest one
+    pub measurements: Vec<OwnedMeasurement>,
+}
+
+/// Criterion measurement that does not borrow its [`Benchmark`]
+#[derive(Debug)]
+pub struct OwnedMeasurement {
+    entry: DirEntry,
+    decode_options: DecodeOptions,
+}
+//
+impl OwnedMeasurement {
+    /// Access this measurement
+    pub fn as_measurement(&self) -> Measurement<'_> {
+        Measurement::new(&self.entry, &self.decode_options)
     }
 }
 
 /// Contents of a `benchmark.cbor` file from cargo-criterion
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BenchmarkMetadata {
     /// Data which uniquely identifies a benchmark
     pub id: RawBenchmarkId,
@@ -368,6 +1173,21 @@ pub struct BenchmarkMetadata {
 }
 //
 impl BenchmarkMetadata {
+    /// Decode the contents of a `benchmark.cbor` file
+    ///
+    /// This never panics, whatever the input bytes, and reports malformed
+    /// input as an error of kind [`io::ErrorKind::InvalidData`]. It is thus
+    /// suitable for decoding files from untrusted sources, like shared caches.
+    ///
+    /// On top of being deserializable, the metadata must be meaningful: its
+    /// benchmark identifier must be one that Criterion can produce, and its
+    /// latest record must point to a measurement file.
+    pub fn from_slice(bytes: &[u8]) -> io::Result<Self> {
+        let metadata: Self = decode::from_slice(bytes)?;
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
     /// Local date and time of the latest measurement
     ///
     /// This is identical to [`Measurement::local_datetime()`] for the
@@ -376,12 +1196,25 @@ impl BenchmarkMetadata {
     ///
     /// A more precise timestamp (sub-second, UTC...) can be found inside of
     /// individual measurement files via [`MeasurementData::datetime`].
-    pub fn latest_local_datetime(&self) -> MappedLocalTime<DateTime<Local>> {
-        parse_measurement_datetime(
-            self.latest_record
-                .file_name()
-                .expect("Latest record field should point to a measurement file"),
-        )
+    ///
+    /// If the latest record does not point to a measurement file, an
+    /// [`Error::NonUnicodeName`] or [`Error::UnexpectedFileName`] is returned.
+    pub fn latest_local_datetime(&self) -> Result<MappedLocalTime<DateTime<Local>>, Error> {
+        // The latest record path uses the separators of the platform on which
+        // the benchmark was run, which may not be the host platform
+        let latest_record = self
+            .latest_record
+            .to_str()
+            .ok_or_else(|| Error::NonUnicodeName {
+                path: self.latest_record.clone(),
+            })?;
+        let latest_record = slash_to_path(latest_record);
+        let file_name = latest_record
+            .file_name()
+            .ok_or_else(|| Error::UnexpectedFileName {
+                path: latest_record.clone(),
+            })?;
+        parse_measurement_datetime(Path::new(file_name))
     }
 }
 //
@@ -394,7 +1227,7 @@ impl BenchmarkMetadata {
 /// benchmarking procedure, it is recommended to use the
 /// [`decode()`](Self::decode) method, which is the product of a careful
 /// reverse-engineering of the Criterion benchmark identification rules.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct RawBenchmarkId {
     #[serde(rename = "group_id")]
     pub group_or_function_id: String,
@@ -405,88 +1238,115 @@ pub struct RawBenchmarkId {
 }
 //
 impl RawBenchmarkId {
+    /// Full textual identifier of the benchmark, as displayed by Criterion
+    ///
+    /// This is the `/`-separated concatenation of all identifier components,
+    /// which Criterion's benchmark filters are matched against. Unlike
+    /// [`Benchmark::slash_path()`], it is not altered to be filesystem-safe.
+    pub fn full_id(&self) -> String {
+        let mut full_id = self.group_or_function_id.clone();
+        for component in [&self.function_id_in_group, &self.value_str]
+            .into_iter()
+            .flatten()
+        {
+            full_id.push('/');
+            full_id.push_str(component);
+        }
+        full_id
+    }
+
     /// Decode the raw benchmark metadata into a higher-level view where field
     /// names are clearer and only valid combinations of fields are allowed.
-    pub fn decode(&self) -> BenchmarkId<'_> {
-        match (
-            &self.function_id_in_group,
-            &self.value_str,
-            self.throughput.clone(),
-        ) {
-            // - Because both `function_id` and `value_str` are absent, we know
-            //   that this benchmark is not part of a group. If the benchmark is
-            //   part of a group, then `group_id` contains the group name, and
-            //   at least one of these extra metadata must be specified so that
-            //   group members can be differentiated from each other.
-            // - Because `value_str` is absent, we know that
-            //   `Criterion::bench_with_input()` has not been used, as it takes
-            //   a `BenchmarkId` and Criterion users are only allowed to
-            //   construct `BenchmarkId`s with a non-blank `parameter` value.
-            // - Thus, by way of elimination, we know that
-            //   `Criterion::bench_function()` was used.
-            (None, None, None) => BenchmarkId::BenchFunction(&self.group_or_function_id),
-
-            // As said above, if `function_id` and `value_str` are both absent,
-            // this benchmark cannot be part of a group. Given that the
-            // Criterion API does not let user specify throughput for
-            // non-grouped benchmark, this metadata violates Criterion's
-            // metadata schema and should be rejected.
-            (None, None, Some(_)) => {
-                unreachable!("Can't specify throughput in non-grouped Criterion benchmarks")
-            }
-
-            // - Because `throughput` is present, we know that a benchmark group
-            //   was used (see above).
-            // - Because `value_str` is present and `function_id` is absent, we
-            //   know that the benchmark was identified within the group using a
-            //   `BenchmarkId` that was constructed via
-            //   `BenchmarkId::from_parameter()`.
-            (None, Some(parameter), Some(throughput)) => BenchmarkId::InGroup {
-                group_id: &self.group_or_function_id,
-                member_id: MemberId::FromParameter(parameter),
-                throughput: Some(throughput),
-            },
+    ///
+    /// Combinations of fields that Criterion cannot produce are reported as
+    /// [`Error::ImpossibleBenchmarkId`]. Metadata files are not validated
+    /// outside of [strict mode](decode::DecodeOptions::strict), so such
+    /// identifiers can be found on disk.
+    pub fn decode(&self) -> Result<BenchmarkId<'_>, Error> {
+        Ok(
+            match (
+                &self.function_id_in_group,
+                &self.value_str,
+                self.throughput.clone(),
+            ) {
+                // - Because both `function_id` and `value_str` are absent, we know
+                //   that this benchmark is not part of a group. If the benchmark is
+                //   part of a group, then `group_id` contains the group name, and
+                //   at least one of these extra metadata must be specified so that
+                //   group members can be differentiated from each other.
+                // - Because `value_str` is absent, we know that
+                //   `Criterion::bench_with_input()` has not been used, as it takes
+                //   a `BenchmarkId` and Criterion users are only allowed to
+                //   construct `BenchmarkId`s with a non-blank `parameter` value.
+                // - Thus, by way of elimination, we know that
+                //   `Criterion::bench_function()` was used.
+                (None, None, None) => BenchmarkId::BenchFunction(&self.group_or_function_id),
 
-            // - Because `throughput` and `function_id` are absent, we do not
-            //   know whether a benchmark group was used or not.
-            // - Because `group_id` is used for group names in benchmark groups
-            //   but also for function names in `Criterion::bench_with_input()`,
-            //   the metadata is ambiguous and we cannot tell whether this is...
-            //   * ...a benchmark inside of a group, with a parameter value but
-            //     no function name (benchmark ID constructed using
-            //     `BenchmarkId::from_parameter`).
-            //   * ...a benchmark outside of a group, with a function name and a
-            //     value string (benchmark ID constructed using
-            //     `BenchmarkId::new()`)
-            (None, Some(parameter), None) => BenchmarkId::AmbiguousFromParameter {
-                group_or_function_id: &self.group_or_function_id,
-                parameter,
-            },
+                // As said above, if `function_id` and `value_str` are both absent,
+                // this benchmark cannot be part of a group. Given that the
+                // Criterion API does not let user specify throughput for
+                // non-grouped benchmark, this metadata violates Criterion's
+                // metadata schema and should be rejected.
+                (None, None, Some(_)) => {
+                    return Err(Error::ImpossibleBenchmarkId {
+                        id: self.full_id(),
+                        message: "throughput specified for a benchmark outside of a group",
+                    })
+                }
 
-            // - Because `function_id` is present, we know that this benchmark
-            //   is part of a group
-            // - Because `value_str` is absent, we know that the user did not
-            //   use an explicit `BenchmarkId` constructor (all of which require
-            //   specifying a parameter representation) and instead relied on
-            //   implicit conversion of strings to benchmark identifiers.
-            (Some(string), None, throughput) => BenchmarkId::InGroup {
-                group_id: &self.group_or_function_id,
-                member_id: MemberId::String(string),
-                throughput,
-            },
+                // - Because `throughput` is present, we know that a benchmark group
+                //   was used (see above).
+                // - Because `value_str` is present and `function_id` is absent, we
+                //   know that the benchmark was identified within the group using a
+                //   `BenchmarkId` that was constructed via
+                //   `BenchmarkId::from_parameter()`.
+                (None, Some(parameter), Some(throughput)) => BenchmarkId::InGroup {
+                    group_id: &self.group_or_function_id,
+                    member_id: MemberId::FromParameter(parameter),
+                    throughput: Some(throughput),
+                },
 
-            // The only case both `function_id` and `value_str` are present is
-            // if the benchmark is part of a group and the most general
-            // `BenchmarkId::new()` ID construction method was used.
-            (Some(function_name), Some(parameter), throughput) => BenchmarkId::InGroup {
-                group_id: &self.group_or_function_id,
-                member_id: MemberId::Full {
-                    function_name,
+                // - Because `throughput` and `function_id` are absent, we do not
+                //   know whether a benchmark group was used or not.
+                // - Because `group_id` is used for group names in benchmark groups
+                //   but also for function names in `Criterion::bench_with_input()`,
+                //   the metadata is ambiguous and we cannot tell whether this is...
+                //   * ...a benchmark inside of a group, with a parameter value but
+                //     no function name (benchmark ID constructed using
+                //     `BenchmarkId::from_parameter`).
+                //   * ...a benchmark outside of a group, with a function name and a
+                //     value string (benchmark ID constructed using
+                //     `BenchmarkId::new()`)
+                (None, Some(parameter), None) => BenchmarkId::AmbiguousFromParameter {
+                    group_or_function_id: &self.group_or_function_id,
                     parameter,
                 },
-                throughput,
+
+                // - Because `function_id` is present, we know that this benchmark
+                //   is part of a group
+                // - Because `value_str` is absent, we know that the user did not
+                //   use an explicit `BenchmarkId` constructor (all of which require
+                //   specifying a parameter representation) and instead relied on
+                //   implicit conversion of strings to benchmark identifiers.
+                (Some(string), None, throughput) => BenchmarkId::InGroup {
+                    group_id: &self.group_or_function_id,
+                    member_id: MemberId::String(string),
+                    throughput,
+                },
+
+                // The only case both `function_id` and `value_str` are present is
+                // if the benchmark is part of a group and the most general
+                // `BenchmarkId::new()` ID construction method was used.
+                (Some(function_name), Some(parameter), throughput) => BenchmarkId::InGroup {
+                    group_id: &self.group_or_function_id,
+                    member_id: MemberId::Full {
+                        function_name,
+                        parameter,
+                    },
+                    throughput,
+                },
             },
-        }
+        )
     }
 }
 //
@@ -567,33 +1427,142 @@ pub enum MemberId<'raw> {
 #[derive(Debug)]
 pub struct Measurement<'parent> {
     entry: &'parent DirEntry,
+    decode_options: &'parent DecodeOptions,
 }
 //
 impl<'parent> Measurement<'parent> {
     /// Wrap a `DirEntry` after checking that it matches our expectations for
     /// `cargo-criterion`'s benchmark data directories.
-    fn new(entry: &'parent DirEntry) -> Self {
+    fn new(entry: &'parent DirEntry, decode_options: &'parent DecodeOptions) -> Self {
         assert!(
             entry.file_type().is_file(),
             "Criterion's benchmark directories should only contain data files"
         );
-        Self { entry }
+        Self {
+            entry,
+            decode_options,
+        }
+    }
+
+    /// Location of this measurement's data file
+    pub fn path(&self) -> &Path {
+        self.entry.path()
     }
 
     /// Local date and time at which this measurement was taken
-    pub fn local_datetime(&self) -> MappedLocalTime<DateTime<Local>> {
-        parse_measurement_datetime(self.entry.file_name())
+    ///
+    /// This is parsed from the measurement file name, so it is cheap to
+    /// compute, but it only has a precision of one second and may be ambiguous
+    /// around daylight saving time transitions. Prefer
+    /// [`utc_datetime()`](Self::utc_datetime) when this is a problem.
+    ///
+    /// Files whose name does not follow cargo-criterion's naming scheme are
+    /// reported as an [`Error::UnexpectedFileName`].
+    pub fn local_datetime(&self) -> Result<MappedLocalTime<DateTime<Local>>, Error> {
+        parse_measurement_datetime(self.entry.path())
+    }
+
+    /// Unambiguous UTC date and time at which this measurement was taken
+    ///
+    /// This is parsed from the measurement file name like
+    /// [`local_datetime()`](Self::local_datetime) whenever possible, and thus
+    /// only has a precision of one second. But around daylight saving time
+    /// transitions, where the local time in the file name is ambiguous or
+    /// does not exist, it falls back to reading the precise timestamp from the
+    /// measurement file like [`utc_datetime()`](Self::utc_datetime).
+    pub fn resolved_datetime(&self) -> Result<DateTime<Utc>, Error> {
+        match self.local_datetime()? {
+            MappedLocalTime::Single(datetime) => Ok(datetime.to_utc()),
+            MappedLocalTime::Ambiguous(..) | MappedLocalTime::None => self.utc_datetime(),
+        }
+    }
+
+    /// Precise UTC date and time at which this measurement was saved
+    ///
+    /// This is read from the measurement file, which is more expensive than
+    /// calling [`local_datetime()`](Self::local_datetime) but yields an
+    /// unambiguous timestamp with sub-second precision. It is identical to the
+    /// [`MeasurementData::datetime`] field of the measurement's data.
+    ///
+    /// This is currently implemented using
+    /// [`precise_datetime()`](Self::precise_datetime).
+    pub fn utc_datetime(&self) -> Result<DateTime<Utc>, Error> {
+        self.precise_datetime()
+    }
+
+    /// Precise UTC date and time at which this measurement was saved, read
+    /// from the header of the measurement file
+    ///
+    /// cargo-criterion stores the timestamp at the beginning of measurement
+    /// files, so this only needs to read a few dozen bytes of the file, instead
+    /// of reading and deserializing all of the measurement's samples like
+    /// [`data()`](Self::data) does. It is therefore cheap enough to be used
+    /// when sub-second precision is needed for many measurements.
+    ///
+    /// If the measurement file does not start with a timestamp in the expected
+    /// format, this falls back to decoding the full measurement file.
+    pub fn precise_datetime(&self) -> Result<DateTime<Utc>, Error> {
+        let path = self.entry.path();
+        let peeked = File::open(path)
+            .and_then(|file| cbor::peek_datetime(BufReader::with_capacity(128, file)))
+            .map_err(|error| Error::reading(path, error))?;
+        if let Some(datetime) = peeked {
+            return Ok(datetime);
+        }
+
+        /// Subset of [`MeasurementData`] that only contains the timestamp
+        #[derive(Deserialize)]
+        struct MeasurementDateTime {
+            datetime: DateTime<Utc>,
+        }
+        decode::read_cbor(path)
+            .map(|data: MeasurementDateTime| data.datetime)
+            .map_err(|error| Error::reading(path, error))
     }
 
     /// Read this measurement's data
-    pub fn data(&self) -> io::Result<MeasurementData> {
-        let data = std::fs::read(self.entry.path())?;
-        Ok(serde_cbor::from_slice(&data[..]).expect("Failed to deserialize benchmark metadata"))
+    ///
+    /// A malformed measurement file is reported as an [`Error::Decode`].
+    pub fn data(&self) -> Result<MeasurementData, Error> {
+        let path = self.entry.path();
+        decode::read_validated(path, self.decode_options)
+            .map_err(|error| Error::reading(path, error))
+    }
+
+    /// Location of this measurement's artifact directory
+    ///
+    /// See the [`layout`] module for more information about artifacts. This
+    /// directory only exists if artifacts were attached to the measurement,
+    /// e.g. with [`writer::attach_artifact()`].
+    pub fn artifacts_dir(&self) -> PathBuf {
+        layout::artifacts_dir(self.entry.path())
+    }
+
+    /// Enumerate the artifacts attached to this measurement, sorted by name
+    ///
+    /// Artifacts can be files or directories. If no artifact was attached to
+    /// the measurement, an empty list is returned.
+    pub fn artifacts(&self) -> Result<Vec<PathBuf>, Error> {
+        let dir = self.artifacts_dir();
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => return Err(Error::Io { path: dir, source }),
+        };
+        let mut artifacts = entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|source| Error::Io {
+                path: dir.clone(),
+                source,
+            })?;
+        artifacts.sort_unstable();
+        Ok(artifacts)
     }
 }
 
 /// Contents of a `measurement_<datetime>.cbor` file from cargo-criterion
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct MeasurementData {
     /// The date and time of when these measurements were saved.
     pub datetime: DateTime<Utc>,
@@ -621,8 +1590,24 @@ pub struct MeasurementData {
     pub history_description: Option<String>,
 }
 //
+impl MeasurementData {
+    /// Decode the contents of a measurement file
+    ///
+    /// This never panics, whatever the input bytes, and reports malformed
+    /// input as an error of kind [`io::ErrorKind::InvalidData`]. It is thus
+    /// suitable for decoding files from untrusted sources, like shared caches.
+    ///
+    /// Like the default, lenient decoding of [`Measurement::data()`], this only
+    /// checks that the input can be deserialized. Use
+    /// [`validation::measurement_issues()`] to check that the decoded data
+    /// makes sense before trusting its values.
+    pub fn from_slice(bytes: &[u8]) -> io::Result<Self> {
+        decode::from_slice(bytes)
+    }
+}
+//
 /// Statistical estimates concerning a benchmark's iteration time
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Estimates {
     pub mean: Estimate,
     pub median: Estimate,
@@ -631,15 +1616,129 @@ pub struct Estimates {
     pub std_dev: Estimate,
 }
 //
+impl Estimates {
+    /// Estimate that best summarizes typical performance
+    ///
+    /// Like Criterion's "typical" metric, this is the slope estimate if
+    /// available (i.e. if linear sampling was used), and the mean otherwise.
+    pub fn typical(&self) -> &Estimate {
+        self.slope.as_ref().unwrap_or(&self.mean)
+    }
+
+    /// Estimate corresponding to a certain statistic
+    pub fn get(&self, statistic: Statistic) -> &Estimate {
+        match statistic {
+            Statistic::Typical => self.typical(),
+            Statistic::Mean => &self.mean,
+            Statistic::Median => &self.median,
+        }
+    }
+
+    /// Truth that all estimates are finite
+    ///
+    /// Benchmarks that crashed or misbehaved during a run can produce NaN or
+    /// infinite estimates, which silently poison any computation that they
+    /// are used in. Such estimates should be excluded from analysis.
+    pub fn is_finite(&self) -> bool {
+        [self.mean, self.median, self.median_abs_dev, self.std_dev]
+            .iter()
+            .chain(&self.slope)
+            .all(Estimate::is_finite)
+    }
+}
+//
+/// Statistic used to summarize a benchmark's performance
+///
+/// Operations that boil a benchmark down to a single number, like budget
+/// checks, use [`Statistic::Typical`] by default and let you override this
+/// choice. See [`Estimates::get()`] for the associated estimates.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Statistic {
+    /// Slope if available, mean otherwise (see [`Estimates::typical()`])
+    #[default]
+    Typical,
+
+    /// Mean
+    Mean,
+
+    /// Median, which is less sensitive to outliers
+    Median,
+}
+//
+impl Display for Statistic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Typical => "typical",
+            Self::Mean => "mean",
+            Self::Median => "median",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Statistical estimates concerning a change of benchmark iteration time
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ChangeEstimates {
     pub mean: Estimate,
     pub median: Estimate,
 }
 //
+impl ChangeEstimates {
+    /// Change of the mean, in percent
+    pub fn mean_pct(&self) -> PercentChange {
+        PercentChange::new(&self.mean)
+    }
+
+    /// Change of the median, in percent
+    pub fn median_pct(&self) -> PercentChange {
+        PercentChange::new(&self.median)
+    }
+}
+//
+/// Relative change of a quantity across benchmark runs, in percent
+///
+/// Criterion stores changes as relative differences (e.g. 0.032 for a 3.2%
+/// increase). This is the same information expressed in percent, which is
+/// what should usually be shown to humans. The [`Display`] implementation
+/// follows Criterion's formatting of changes, e.g. `+3.20% [-1.10% +5.40%]`,
+/// and honors the precision specified in the format string, if any.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PercentChange {
+    /// Most likely change, in percent
+    pub point: f64,
+
+    /// Lower bound of the confidence interval, in percent
+    pub lower_bound: f64,
+
+    /// Upper bound of the confidence interval, in percent
+    pub upper_bound: f64,
+}
+//
+impl PercentChange {
+    /// Convert an estimate of a relative change into percent
+    fn new(change: &Estimate) -> Self {
+        Self {
+            point: change.point_estimate * 100.0,
+            lower_bound: change.confidence_interval.lower_bound * 100.0,
+            upper_bound: change.confidence_interval.upper_bound * 100.0,
+        }
+    }
+}
+//
+impl Display for PercentChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(2);
+        write!(
+            f,
+            "{:+.precision$}% [{:+.precision$}% {:+.precision$}%]",
+            self.point, self.lower_bound, self.upper_bound
+        )
+    }
+}
+//
 /// Statistical estimate of some quantity
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Estimate {
     /// The confidence interval for this estimate
     pub confidence_interval: ConfidenceInterval,
@@ -649,8 +1748,122 @@ pub struct Estimate {
     pub standard_error: f64,
 }
 //
+impl Estimate {
+    /// Truth that the point estimate, standard error and confidence interval
+    /// bounds of this estimate are all finite
+    pub fn is_finite(&self) -> bool {
+        let ConfidenceInterval {
+            lower_bound,
+            upper_bound,
+            ..
+        } = self.confidence_interval;
+        [
+            self.point_estimate,
+            self.standard_error,
+            lower_bound,
+            upper_bound,
+        ]
+        .iter()
+        .all(|x| x.is_finite())
+    }
+
+    /// Width of the confidence interval, relative to the point estimate
+    ///
+    /// This is a common measure of how precise an estimate is, where e.g. 0.04
+    /// means that the confidence interval spans 4% of the point estimate.
+    pub fn relative_ci_width(&self) -> f64 {
+        let ConfidenceInterval {
+            lower_bound,
+            upper_bound,
+            ..
+        } = self.confidence_interval;
+        (upper_bound - lower_bound) / self.point_estimate.abs()
+    }
+
+    /// Truth that the confidence intervals of two estimates overlap
+    ///
+    /// When they do not, the difference between both estimates is
+    /// statistically significant at (at least) the confidence level of both
+    /// intervals.
+    pub fn overlaps(&self, other: &Estimate) -> bool {
+        let (this, other) = (&self.confidence_interval, &other.confidence_interval);
+        this.lower_bound <= other.upper_bound && other.lower_bound <= this.upper_bound
+    }
+
+    /// This estimate with a confidence interval at another confidence level
+    ///
+    /// `samples` must be the measurement that this estimate was taken from,
+    /// e.g. `data.estimates.mean.at_confidence(0.99, &data)`. The confidence
+    /// interval and standard error are bootstrapped again from its raw samples,
+    /// as [`stats::releveled_estimates()`] does for all estimates of a
+    /// measurement.
+    ///
+    /// Returns `None` if `confidence_level` is not in range `0.0..1.0`, if
+    /// `samples` has fewer than two usable samples, or if this estimate is not
+    /// one of the estimates of `samples`.
+    pub fn at_confidence(
+        &self,
+        confidence_level: f64,
+        samples: &MeasurementData,
+    ) -> Option<Estimate> {
+        let statistics: [fn(&Estimates) -> Option<&Estimate>; 5] = [
+            |estimates| estimates.slope.as_ref(),
+            |estimates| Some(&estimates.mean),
+            |estimates| Some(&estimates.median),
+            |estimates| Some(&estimates.median_abs_dev),
+            |estimates| Some(&estimates.std_dev),
+        ];
+        let statistic = statistics
+            .into_iter()
+            .find(|statistic| statistic(&samples.estimates) == Some(self))?;
+        let releveled = stats::releveled_estimates(samples, confidence_level)?;
+        statistic(&releveled).copied()
+    }
+
+    /// Estimate of the ratio of this quantity to another one
+    ///
+    /// Uncertainties are propagated to first order, assuming that both
+    /// estimates are independent and that their point estimates are positive,
+    /// as is the case for execution times. The standard error is propagated
+    /// from the relative standard errors of both estimates, and the bounds of
+    /// the confidence interval are propagated from the relative distances
+    /// between the point estimates and the matching bounds of both intervals.
+    /// If both estimates have different confidence levels, the lowest one is
+    /// used.
+    pub fn ratio(&self, other: &Estimate) -> Estimate {
+        let point_estimate = self.point_estimate / other.point_estimate;
+        let relative = |estimate: &Estimate, deviation: f64| deviation / estimate.point_estimate;
+        let quadratic_sum = |x: f64, y: f64| (x.powi(2) + y.powi(2)).sqrt();
+        let standard_error = point_estimate.abs()
+            * quadratic_sum(
+                relative(self, self.standard_error),
+                relative(other, other.standard_error),
+            );
+        let (this_ci, other_ci) = (&self.confidence_interval, &other.confidence_interval);
+        // The ratio is lowest when the numerator is low and the denominator is
+        // high, and highest in the opposite situation
+        let lower_deviation = quadratic_sum(
+            relative(self, self.point_estimate - this_ci.lower_bound),
+            relative(other, other_ci.upper_bound - other.point_estimate),
+        );
+        let upper_deviation = quadratic_sum(
+            relative(self, this_ci.upper_bound - self.point_estimate),
+            relative(other, other.point_estimate - other_ci.lower_bound),
+        );
+        Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: this_ci.confidence_level.min(other_ci.confidence_level),
+                lower_bound: point_estimate * (1.0 - lower_deviation),
+                upper_bound: point_estimate * (1.0 + upper_deviation),
+            },
+            point_estimate,
+            standard_error,
+        }
+    }
+}
+//
 /// Confidence interval associated with a certain [`Estimate`]
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ConfidenceInterval {
     pub confidence_level: f64,
     pub lower_bound: f64,
@@ -658,7 +1871,7 @@ pub struct ConfidenceInterval {
 }
 //
 /// Statistical change detected across benchmark runs
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ChangeDirection {
     NoChange,
     NotSignificant,
@@ -666,17 +1879,455 @@ pub enum ChangeDirection {
     Regressed,
 }
 
-/// Parse a measurement file name to find the measurement date & time
-fn parse_measurement_datetime(file_name: impl AsRef<OsStr>) -> MappedLocalTime<DateTime<Local>> {
-    let datetime = file_name
-        .as_ref()
+/// Convert a relative path into a `/`-separated string
+///
+/// Unlike the textual representation of [`Path`], which uses the separator of
+/// the host platform, this representation is the same on every platform, and is
+/// thus suitable for storing paths that will be shared across machines.
+///
+/// Criterion never generates non-Unicode names, but they can be found by
+/// searches with [lossy names](Search::lossy_names). Such path components are
+/// converted lossily, so distinct paths may map to the same string.
+pub fn path_to_slash(path: impl AsRef<Path>) -> String {
+    path.as_ref()
+        .iter()
+        .map(OsStr::to_string_lossy)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Convert a `/`-separated relative path back into a [`PathBuf`]
+///
+/// This is the inverse of [`path_to_slash()`]. Backslashes are also accepted as
+/// separators, so that paths recorded on Windows can be read everywhere.
+pub fn slash_to_path(slash_path: &str) -> PathBuf {
+    slash_path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .collect()
+}
+
+/// Parse the name of a measurement file to find the measurement date & time
+fn parse_measurement_datetime(path: &Path) -> Result<MappedLocalTime<DateTime<Local>>, Error> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::UnexpectedFileName {
+            path: path.to_owned(),
+        })?
         .to_str()
-        .expect("Measurement file name should be Unicode")
-        .strip_prefix("measurement_")
-        .expect("Measurement file name should start with measurement_")
-        .strip_suffix(".cbor")
-        .expect("Measurement file name should end with .cbor extension");
-    let datetime = NaiveDateTime::parse_from_str(datetime, "%y%m%d%H%M%S")
-        .expect("Unexpected criterion measurement date/time format");
-    Local.from_local_datetime(&datetime)
+        .ok_or_else(|| Error::NonUnicodeName {
+            path: path.to_owned(),
+        })?;
+    let datetime = layout::parse_measurement_file_name(file_name).ok_or_else(|| {
+        Error::UnexpectedFileName {
+            path: path.to_owned(),
+        }
+    })?;
+    Ok(Local.from_local_datetime(&datetime))
+}
+
+/// Truth that the name of a directory entry is valid Unicode
+fn is_unicode(entry: &DirEntry) -> bool {
+    entry.file_name().to_str().is_some()
+}
+
+/// Truth that a directory entry is a stray entry in the sense of
+/// [`StrayEntryPolicy`]
+fn is_stray(entry: &DirEntry) -> bool {
+    let ty = entry.file_type();
+    if entry.path_is_symlink() || !(ty.is_file() || ty.is_dir()) {
+        return true;
+    }
+    ty.is_file()
+        && !entry.file_name().to_str().is_some_and(|name| {
+            name == layout::BENCHMARK_FILE_NAME || layout::is_measurement_file(name)
+        })
+}
+
+/// Truth that a directory entry is not a [measurement artifact
+/// directory](layout::is_artifacts_dir), which must not be walked
+fn is_not_artifacts_dir(entry: &DirEntry) -> bool {
+    !(entry.file_type().is_dir() && layout::is_artifacts_dir(entry.path()))
+}
+
+// Data handles are meant to be processed by thread pools, so they must remain
+// thread-safe as the library evolves
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Search>();
+    assert_send_sync::<Benchmark>();
+    assert_send_sync::<BenchmarkParts>();
+    assert_send_sync::<Measurement<'static>>();
+    assert_send_sync::<OwnedMeasurement>();
+    assert_send_sync::<BenchmarkMetadata>();
+    assert_send_sync::<catalog::Catalog>();
+    assert_send_sync::<MeasurementData>();
+    assert_send_sync::<dataset::Dataset>();
+    assert_send_sync::<dataset::LoadedBenchmark>();
+    assert_send_sync::<history::History>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode::DecodeErrors,
+        testing::{bench_id, measurement, TempTarget},
+    };
+    use std::sync::Mutex;
+
+    fn raw_id(
+        function_id: Option<&str>,
+        value_str: Option<&str>,
+        throughput: Option<Throughput>,
+    ) -> RawBenchmarkId {
+        RawBenchmarkId {
+            group_or_function_id: "group".to_owned(),
+            function_id_in_group: function_id.map(str::to_owned),
+            value_str: value_str.map(str::to_owned),
+            throughput,
+        }
+    }
+
+    #[test]
+    fn search_prefixes_must_be_normal_relative_paths() {
+        let search = || Search::in_target_dir(std::env::temp_dir());
+        let valid = search().try_under_prefix("group/function").unwrap();
+        assert_eq!(valid.prefix, Path::new("group/function"));
+        for prefix in ["../group", "group/../function", "/group", "."] {
+            let error = search().try_under_prefix(prefix).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(error.path(), Path::new(prefix));
+        }
+    }
+
+    #[test]
+    fn decode_valid_ids() {
+        let bytes = Some(Throughput::Bytes(64));
+        assert_eq!(
+            raw_id(None, None, None).decode().unwrap(),
+            BenchmarkId::BenchFunction("group")
+        );
+        assert_eq!(
+            raw_id(None, Some("42"), None).decode().unwrap(),
+            BenchmarkId::AmbiguousFromParameter {
+                group_or_function_id: "group",
+                parameter: "42",
+            }
+        );
+        assert_eq!(
+            raw_id(None, Some("42"), bytes.clone()).decode().unwrap(),
+            BenchmarkId::InGroup {
+                group_id: "group",
+                member_id: MemberId::FromParameter("42"),
+                throughput: bytes.clone(),
+            }
+        );
+        assert_eq!(
+            raw_id(Some("f"), None, None).decode().unwrap(),
+            BenchmarkId::InGroup {
+                group_id: "group",
+                member_id: MemberId::String("f"),
+                throughput: None,
+            }
+        );
+        assert_eq!(
+            raw_id(Some("f"), Some("42"), bytes.clone())
+                .decode()
+                .unwrap(),
+            BenchmarkId::InGroup {
+                group_id: "group",
+                member_id: MemberId::Full {
+                    function_name: "f",
+                    parameter: "42",
+                },
+                throughput: bytes,
+            }
+        );
+    }
+
+    #[test]
+    fn decode_rejects_throughput_outside_of_group() {
+        let error = raw_id(None, None, Some(Throughput::Elements(1)))
+            .decode()
+            .unwrap_err();
+        assert!(matches!(error, Error::ImpossibleBenchmarkId { .. }));
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Slash paths of the benchmarks found by a search, along with the errors
+    /// that it reported
+    fn walk(search: Search) -> (Vec<String>, Vec<Error>) {
+        let mut benchmarks = Vec::new();
+        let mut errors = Vec::new();
+        for bench in search {
+            match bench {
+                Ok(bench) => benchmarks.push(bench.slash_path()),
+                Err(error) => errors.push(error),
+            }
+        }
+        (benchmarks, errors)
+    }
+
+    /// Paths of errors, which must all describe an unexpected layout
+    fn layout_error_paths(errors: &[Error]) -> Vec<&Path> {
+        errors
+            .iter()
+            .map(|error| {
+                assert!(matches!(error, Error::UnexpectedLayout { .. }), "{error}");
+                error.path().unwrap()
+            })
+            .collect()
+    }
+
+    /// Benchmark `parse`, with a single measurement, and return its directory
+    fn write_parse_benchmark(target: &TempTarget) -> PathBuf {
+        target.write_benchmark(
+            &bench_id("parse"),
+            &[measurement("2026-01-01T00:00:00Z", &[1.0])],
+        )
+    }
+
+    #[test]
+    fn stray_files_do_not_hide_benchmarks() {
+        let target = TempTarget::new("stray-files");
+        let bench_dir = write_parse_benchmark(&target);
+        // .DS_Store sorts after benchmark.cbor, and lock files before
+        // measurements and directories
+        let ds_store = bench_dir.join(".DS_Store");
+        let lock = layout::data_root(&target.path).join("backup.lock");
+        std::fs::write(&ds_store, b"").unwrap();
+        std::fs::write(&lock, b"").unwrap();
+
+        let (benchmarks, errors) = walk(target.search());
+        assert_eq!(benchmarks, ["parse"]);
+        assert_eq!(layout_error_paths(&errors), [&lock, &ds_store]);
+
+        let (benchmarks, errors) = walk(target.search().invariant_checks(InvariantChecks::Off));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+
+        let (benchmarks, errors) = walk(target.search().on_stray_entry(StrayEntryPolicy::Skip));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let policy = StrayEntryPolicy::warn({
+            let skipped = skipped.clone();
+            move |path| skipped.lock().unwrap().push(path.to_owned())
+        });
+        let (benchmarks, errors) = walk(target.search().on_stray_entry(policy));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+        assert_eq!(*skipped.lock().unwrap(), [lock, ds_store]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_stray_entries() {
+        let target = TempTarget::new("symlinks");
+        let bench_dir = write_parse_benchmark(&target);
+        let measurement_path = std::fs::read_dir(&bench_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path != &bench_dir.join(layout::BENCHMARK_FILE_NAME))
+            .unwrap();
+        let file_link = bench_dir.join("latest.cbor");
+        let dir_link = layout::data_root(&target.path).join("parse-link");
+        std::os::unix::fs::symlink(&measurement_path, &file_link).unwrap();
+        std::os::unix::fs::symlink(&bench_dir, &dir_link).unwrap();
+
+        // Symlinked directories are not followed
+        let (benchmarks, errors) = walk(target.search());
+        assert_eq!(benchmarks, ["parse"]);
+        assert_eq!(layout_error_paths(&errors), [&dir_link, &file_link]);
+
+        let (benchmarks, errors) = walk(target.search().on_stray_entry(StrayEntryPolicy::Skip));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+        let bench = target.search().find_all().find_map(Result::ok).unwrap();
+        assert_eq!(bench.measurements().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_names() {
+        use std::os::unix::ffi::OsStrExt;
+        let target = TempTarget::new("non-unicode");
+        let parse_dir = write_parse_benchmark(&target);
+        let fmt_dir = target.write_benchmark(
+            &bench_id("fmt"),
+            &[measurement("2026-01-01T00:00:00Z", &[2.0])],
+        );
+        let bad_name = OsStr::from_bytes(b"fmt\xff");
+        let bad_dir = fmt_dir.with_file_name(bad_name);
+        std::fs::rename(&fmt_dir, &bad_dir).unwrap();
+        let bad_file = parse_dir.join(OsStr::from_bytes(b"notes\xff.txt"));
+        std::fs::write(&bad_file, b"").unwrap();
+
+        // Directories are reported without being walked, files are reported
+        // without hiding the benchmark around them
+        let (benchmarks, errors) = walk(target.search());
+        assert_eq!(benchmarks, ["parse"]);
+        let paths = errors
+            .iter()
+            .map(|error| {
+                assert!(matches!(error, Error::NonUnicodeName { .. }), "{error}");
+                error.path().unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [&bad_dir, &bad_file]);
+
+        // Lossy searches find the benchmark, and only report the stray file
+        let search = || target.search().lossy_names(true);
+        let (benchmarks, errors) = walk(search());
+        assert_eq!(benchmarks, ["fmt\u{FFFD}", "parse"]);
+        assert_eq!(layout_error_paths(&errors), [&bad_file]);
+        let bench = search().find_all().find_map(Result::ok).unwrap();
+        assert_eq!(bench.path_from_data_root_os(), Path::new(bad_name));
+        assert_eq!(bench.metadata().unwrap().id, bench_id("fmt"));
+        assert_eq!(bench.decoded_measurements().count(), 1);
+
+        let mut dir_names = Vec::new();
+        search()
+            .find_in_paths(|dir| {
+                dir_names.push((dir.dir_name().to_owned(), dir.dir_name_os().to_owned()));
+                true
+            })
+            .for_each(drop);
+        assert_eq!(
+            dir_names,
+            [
+                ("fmt\u{FFFD}".to_owned(), bad_name.to_owned()),
+                ("parse".to_owned(), "parse".into()),
+            ]
+        );
+    }
+
+    /// Target directory with benchmark `parse`, along with a benchmark
+    /// directory without measurements and one without metadata, whose paths
+    /// are returned
+    fn write_incomplete_benchmarks(target: &TempTarget) -> [PathBuf; 2] {
+        write_parse_benchmark(target);
+        let data = [measurement("2026-01-01T00:00:00Z", &[1.0])];
+        let empty = target.write_benchmark(&bench_id("empty"), &[]);
+        let orphan = target.write_benchmark(&bench_id("orphan"), &data);
+        std::fs::remove_file(orphan.join(layout::BENCHMARK_FILE_NAME)).unwrap();
+        [empty, orphan]
+    }
+
+    #[test]
+    fn invariant_checks_handle_incomplete_benchmarks() {
+        let target = TempTarget::new("invariant-checks");
+        let [empty, orphan] = write_incomplete_benchmarks(&target);
+
+        let (benchmarks, errors) = walk(target.search());
+        assert_eq!(benchmarks, ["parse"]);
+        assert_eq!(layout_error_paths(&errors), [&empty, &orphan]);
+
+        let (benchmarks, errors) = walk(target.search().invariant_checks(InvariantChecks::Off));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let checks = InvariantChecks::warn({
+            let warnings = warnings.clone();
+            move |error| {
+                warnings
+                    .lock()
+                    .unwrap()
+                    .push(error.path().unwrap().to_owned())
+            }
+        });
+        let (benchmarks, errors) = walk(target.search().invariant_checks(checks));
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+        assert_eq!(*warnings.lock().unwrap(), [empty, orphan]);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no measurements")]
+    fn invariant_checks_can_panic() {
+        let target = TempTarget::new("invariant-panic");
+        write_incomplete_benchmarks(&target);
+        walk(target.search().invariant_checks(InvariantChecks::Panic));
+    }
+
+    #[test]
+    fn decode_error_policies() {
+        let target = TempTarget::new("decode-errors");
+        let parse_dir = target.write_benchmark(
+            &bench_id("parse"),
+            &[
+                measurement("2026-01-01T00:00:00Z", &[1.0]),
+                measurement("2026-01-02T00:00:00Z", &[2.0]),
+            ],
+        );
+        let broken_dir = target.write_benchmark(
+            &bench_id("broken"),
+            &[measurement("2026-01-01T00:00:00Z", &[1.0])],
+        );
+        let datetime = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let bad_measurement = parse_dir.join(layout::measurement_file_name(&datetime));
+        let bad_metadata = broken_dir.join(layout::BENCHMARK_FILE_NAME);
+        // Truncated CBOR map
+        std::fs::write(&bad_measurement, [0xa2, 0x61]).unwrap();
+        std::fs::write(&bad_metadata, [0xa2, 0x61]).unwrap();
+        let decoded = |bench: &Benchmark| {
+            bench
+                .decoded_measurements()
+                .map(|result| result.map(|(_, data)| data.values[0]))
+                .collect::<Vec<_>>()
+        };
+
+        // Malformed files are reported when they are decoded
+        let benchmarks = target
+            .search()
+            .find_all()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(benchmarks.len(), 2);
+        let error = benchmarks[0].metadata().unwrap_err();
+        assert!(error.is_decode_error());
+        assert_eq!(error.path(), Some(bad_metadata.as_path()));
+        let values = decoded(&benchmarks[1]);
+        assert_eq!(values[0].as_ref().unwrap(), &2.0);
+        let error = values[1].as_ref().unwrap_err();
+        assert!(error.is_decode_error());
+        assert_eq!(error.path(), Some(bad_measurement.as_path()));
+
+        // Skipping drops benchmarks with malformed metadata and malformed
+        // measurements
+        let search = target.search().on_decode_error(DecodeErrorPolicy::Skip);
+        let (benchmarks, errors) = walk(search);
+        assert_eq!(benchmarks, ["parse"]);
+        assert!(errors.is_empty());
+        let search = target.search().on_decode_error(DecodeErrorPolicy::Skip);
+        let bench = search.find_all().next().unwrap().unwrap();
+        assert_eq!(
+            decoded(&bench)
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [2.0]
+        );
+
+        // Skipped errors can be collected
+        let collected = DecodeErrors::new();
+        let policy = DecodeErrorPolicy::SkipAndCollect(collected.clone());
+        let mut benchmarks = target.search().on_decode_error(policy).find_all();
+        let bench = benchmarks.next().unwrap().unwrap();
+        assert!(benchmarks.next().is_none());
+        assert_eq!(decoded(&bench).len(), 1);
+        let paths = collected
+            .take()
+            .into_iter()
+            .map(|error| {
+                assert_eq!(error.error.kind(), io::ErrorKind::InvalidData);
+                error.path
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [bad_metadata, bad_measurement]);
+        assert!(collected.is_empty());
+    }
 }
@@ -0,0 +1,391 @@
+//! Command-line interface to the criterion-cbor library
+//!
+//! Run without arguments to get a list of available commands.
+
+use criterion_cbor::{
+    budgets::{self, Budgets},
+    dataset::Dataset,
+    decode,
+    layout::{self, InvariantChecks},
+    tags::{self, Tags},
+    units::ValueUnit,
+    validation, Benchmark, BenchmarkMetadata, Estimate, MeasurementData, Search,
+};
+use serde::Serialize;
+use std::{
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Command-line usage summary
+const USAGE: &str = "\
+Usage:
+    criterion-cbor budget check [--budgets <file>] [--tag <tag>] [<cargo root>]
+    criterion-cbor budget update --from-latest [--margin <fraction>] [--budgets <file>] [<cargo root>]
+    criterion-cbor check [<cargo root>]
+    criterion-cbor cmp [--tolerance <fraction>] <baseline.ccbor> <latest.ccbor>
+    criterion-cbor dump [--format json|cbor-diag|text] [<cargo root>]
+
+The cargo root defaults to the current directory, and the budget file defaults
+to perf-budgets.toml inside of the cargo root. If no cargo root is specified,
+the CRITERION_CBOR_DATA environment variable can point to a target directory or
+to a Criterion data root, which will then be used as the benchmark data source.
+
+Budget checks can be restricted to the benchmarks that have a certain tag in
+the tags.toml file of the cargo root.
+
+The check command verifies that benchmark data matches the assumptions that
+this library makes about cargo-criterion, and lists the violations.
+
+The cmp tolerance defaults to 0.05, i.e. estimates may change by up to 5%
+before being reported.
+
+The dump command prints all benchmark data, by default as text. The json
+format outputs the decoded data, whereas the cbor-diag format shows the raw
+contents of the CBOR files in diagnostic notation.";
+
+/// Result type used by CLI commands
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Dispatch to the command selected by command-line arguments
+fn run() -> Result<ExitCode> {
+    let mut args = Args::from_env()?;
+    match args.positional().as_deref() {
+        Some("budget") => match args.positional().as_deref() {
+            Some("check") => budget_check(args),
+            Some("update") => budget_update(args),
+            _ => Err("unknown or missing budget command".into()),
+        },
+        Some("check") => check(args),
+        Some("cmp") => cmp(args),
+        Some("dump") => dump(args),
+        _ => Err("unknown or missing command".into()),
+    }
+}
+
+/// Check the latest benchmark data against the performance budgets
+fn budget_check(mut args: Args) -> Result<ExitCode> {
+    let budgets_path = args.option("--budgets")?;
+    let tag = args.option("--tag")?;
+    let (cargo_root, search) = args.cargo_root_and_search()?;
+    let budgets_path = budgets_path.map_or_else(
+        || cargo_root.join(budgets::DEFAULT_FILE_NAME),
+        PathBuf::from,
+    );
+    let budgets = Budgets::load(&budgets_path)
+        .map_err(|e| format!("failed to load {}: {e}", budgets_path.display()))?;
+    let mut report = budgets.check(search)?;
+    if let Some(tag) = tag {
+        let tags = Tags::in_cargo_root(&cargo_root)
+            .map_err(|e| format!("failed to load {}: {e}", tags::DEFAULT_FILE_NAME))?;
+        report = report.filter_tagged(&tags, &tag);
+    }
+    println!("{report}");
+    Ok(if report.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Regenerate the performance budgets from the latest benchmark data
+fn budget_update(mut args: Args) -> Result<ExitCode> {
+    if !args.flag("--from-latest") {
+        return Err("budgets can only be updated --from-latest".into());
+    }
+    let margin = args
+        .option("--margin")?
+        .map(|margin| margin.parse::<f64>())
+        .transpose()
+        .map_err(|e| format!("invalid margin: {e}"))?
+        .unwrap_or(0.1);
+    let budgets_path = args.option("--budgets")?;
+    let (cargo_root, search) = args.cargo_root_and_search()?;
+    let budgets_path = budgets_path.map_or_else(
+        || cargo_root.join(budgets::DEFAULT_FILE_NAME),
+        PathBuf::from,
+    );
+    let mut budgets = if budgets_path.exists() {
+        Budgets::load(&budgets_path)
+            .map_err(|e| format!("failed to load {}: {e}", budgets_path.display()))?
+    } else {
+        Budgets::default()
+    };
+    budgets.update_from_latest(search, margin)?;
+    budgets.save(&budgets_path)?;
+    println!(
+        "Updated {} budgets in {}",
+        budgets.budgets.len(),
+        budgets_path.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Check benchmark data against this library's model of cargo-criterion
+fn check(args: Args) -> Result<ExitCode> {
+    let (_cargo_root, search) = args.cargo_root_and_search()?;
+    let result = validation::check_model(search.invariant_checks(InvariantChecks::Report))?;
+    println!("{result}");
+    Ok(if result.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Compare the latest estimates from two portable dataset files
+fn cmp(mut args: Args) -> Result<ExitCode> {
+    let tolerance = args
+        .option("--tolerance")?
+        .map(|tolerance| tolerance.parse::<f64>())
+        .transpose()
+        .map_err(|e| format!("invalid tolerance: {e}"))?
+        .unwrap_or(0.05);
+    let (Some(baseline), Some(latest)) = (args.positional(), args.positional()) else {
+        return Err("cmp expects two dataset files".into());
+    };
+    args.finish()?;
+    let load = |path: &str| Dataset::load(path).map_err(|e| format!("failed to load {path}: {e}"));
+    let comparison = load(&baseline)?.compare(&load(&latest)?, tolerance);
+    if comparison.is_match() {
+        println!("No change beyond tolerance");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        print!("{comparison}");
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Print all benchmark data in the requested format
+fn dump(mut args: Args) -> Result<ExitCode> {
+    let format = args.option("--format")?;
+    let (_cargo_root, search) = args.cargo_root_and_search()?;
+    let benchmarks = search.find_all();
+    match format.as_deref() {
+        None | Some("text") => {
+            for bench in benchmarks {
+                dump_text(&bench?)?;
+            }
+        }
+        Some("json") => {
+            let benchmarks = benchmarks
+                .map(|bench| dump_json(&bench?))
+                .collect::<Result<Vec<_>>>()?;
+            println!("{}", serde_json::to_string_pretty(&benchmarks)?);
+        }
+        Some("cbor-diag") => {
+            for bench in benchmarks {
+                let bench = bench?;
+                println!("=== {} ===", bench.slash_path());
+                let measurement_paths = bench.measurements().map(|meas| meas.path().to_owned());
+                for path in std::iter::once(metadata_path(&bench)).chain(measurement_paths) {
+                    let diag = decode::diagnostic_notation(&path).map_err(decode_error(&path))?;
+                    println!("\n{}:\n{diag}", file_name(&path));
+                }
+                println!();
+            }
+        }
+        Some(other) => return Err(format!("unknown dump format {other}").into()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the data of a benchmark as human-readable text
+fn dump_text(bench: &Benchmark) -> Result<()> {
+    let metadata = bench.metadata()?;
+    println!("=== {} ===", bench.slash_path());
+    println!("id: {}", metadata.id.full_id());
+    if let Some(throughput) = &metadata.id.throughput {
+        println!("throughput: {throughput:?}");
+    }
+    println!("latest record: {}", metadata.latest_record.display());
+    let unit = bench.value_unit();
+    for meas in bench.measurements() {
+        let data = meas.data()?;
+        println!("\n{}", file_name(meas.path()));
+        println!("  datetime: {}", data.datetime);
+        println!("  samples: {}", data.values.len());
+        let estimates = &data.estimates;
+        let estimates = [
+            ("mean", Some(&estimates.mean)),
+            ("median", Some(&estimates.median)),
+            ("median_abs_dev", Some(&estimates.median_abs_dev)),
+            ("slope", estimates.slope.as_ref()),
+            ("std_dev", Some(&estimates.std_dev)),
+        ];
+        for (name, estimate) in estimates {
+            if let Some(estimate) = estimate {
+                println!("  {name}: {}", format_estimate(estimate, unit));
+            }
+        }
+        if let Some(changes) = &data.changes {
+            println!("  mean change: {}", changes.mean_pct());
+            println!("  median change: {}", changes.median_pct());
+        }
+        if let Some(direction) = &data.change_direction {
+            println!("  change direction: {direction:?}");
+        }
+        for (name, field) in [
+            ("history id", &data.history_id),
+            ("history description", &data.history_description),
+        ] {
+            if let Some(field) = field {
+                println!("  {name}: {field}");
+            }
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Gather the data of a benchmark for JSON output
+fn dump_json(bench: &Benchmark) -> Result<DumpedBenchmark> {
+    let measurements = bench
+        .measurements()
+        .map(|meas| {
+            Ok(DumpedMeasurement {
+                file: file_name(meas.path()),
+                data: meas.data()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DumpedBenchmark {
+        path: bench.slash_path(),
+        metadata: bench.metadata()?,
+        measurements,
+    })
+}
+
+/// JSON output of the dump command for one benchmark
+#[derive(Serialize)]
+struct DumpedBenchmark {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    path: String,
+
+    /// Contents of the benchmark's metadata file
+    metadata: BenchmarkMetadata,
+
+    /// Measurements of the benchmark, from the latest to the oldest one
+    measurements: Vec<DumpedMeasurement>,
+}
+
+/// JSON output of the dump command for one measurement
+#[derive(Serialize)]
+struct DumpedMeasurement {
+    /// Name of the measurement file
+    file: String,
+
+    /// Contents of the measurement file
+    data: MeasurementData,
+}
+
+/// Format a statistical estimate with its confidence interval
+fn format_estimate(estimate: &Estimate, unit: &ValueUnit) -> String {
+    let interval = &estimate.confidence_interval;
+    format!(
+        "{} [{} {}] ({}% CI)",
+        unit.format_value(estimate.point_estimate),
+        unit.format_value(interval.lower_bound),
+        unit.format_value(interval.upper_bound),
+        interval.confidence_level * 100.0
+    )
+}
+
+/// Location of the metadata file of a benchmark
+fn metadata_path(bench: &Benchmark) -> PathBuf {
+    bench.dir_path().join(layout::BENCHMARK_FILE_NAME)
+}
+
+/// Add the path of the file that failed to decode to a decoding error
+fn decode_error(path: &Path) -> impl FnOnce(io::Error) -> String + '_ {
+    move |e| format!("failed to decode {}: {e}", path.display())
+}
+
+/// File name of a benchmark data file, for display purposes
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Minimal command-line argument parser
+struct Args(Vec<String>);
+//
+impl Args {
+    /// Collect the command-line arguments of this program
+    fn from_env() -> Result<Self> {
+        std::env::args_os()
+            .skip(1)
+            .map(|arg| {
+                arg.into_string()
+                    .map_err(|arg| format!("non-Unicode argument {arg:?}").into())
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self)
+    }
+
+    /// Extract a boolean flag, telling if it was present
+    fn flag(&mut self, name: &str) -> bool {
+        let len_before = self.0.len();
+        self.0.retain(|arg| arg != name);
+        self.0.len() != len_before
+    }
+
+    /// Extract an option that takes a value, if present
+    fn option(&mut self, name: &str) -> Result<Option<String>> {
+        let Some(idx) = self.0.iter().position(|arg| arg == name) else {
+            return Ok(None);
+        };
+        self.0.remove(idx);
+        if idx < self.0.len() {
+            Ok(Some(self.0.remove(idx)))
+        } else {
+            Err(format!("missing value for option {name}").into())
+        }
+    }
+
+    /// Extract the next positional argument, if any
+    ///
+    /// Options and flags must be extracted before positional arguments that
+    /// follow them, otherwise they will be mistaken for positional arguments.
+    fn positional(&mut self) -> Option<String> {
+        (!self.0.is_empty()).then(|| self.0.remove(0))
+    }
+
+    /// Extract the optional trailing cargo root argument, check that there are
+    /// no other arguments left, and set up a search for benchmark data
+    ///
+    /// If no cargo root is specified, the current directory is used, and the
+    /// benchmark data is located with [`Search::discover()`].
+    fn cargo_root_and_search(mut self) -> Result<(PathBuf, Search)> {
+        let cargo_root = self.positional().map(PathBuf::from);
+        self.finish()?;
+        if let Some(cargo_root) = cargo_root {
+            let search = Search::try_in_cargo_root(&cargo_root)?;
+            Ok((cargo_root, search))
+        } else {
+            Ok((std::env::current_dir()?, Search::discover()?))
+        }
+    }
+
+    /// Check that there are no arguments left
+    fn finish(mut self) -> Result<()> {
+        if let Some(unexpected) = self.positional() {
+            return Err(format!("unexpected argument {unexpected}").into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Reassembly of benchmark suites that were split across several CI jobs
+//!
+//! Large benchmark suites are often sharded across CI jobs, each of which runs
+//! a subset of the benchmarks and produces a partial data directory. Before
+//! they can be compared against a baseline, these partial results must be
+//! merged back into a single [`Dataset`] with [`shards()`], which also checks
+//! that the sharding was done correctly.
+//!
+//! Shards are expected to only contain the measurements of their own CI job,
+//! e.g. because each job starts from an empty `target` directory, or because
+//! older measurements were dropped with [`Dataset::since()`].
+
+use crate::dataset::Dataset;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+/// Merge the partial datasets of a sharded benchmark run
+///
+/// Every benchmark with at least one measurement must appear in only one
+/// shard, otherwise an [`OverlapError`] is returned, since that means that the
+/// sharding is wrong and that some benchmarks were measured several times.
+///
+/// `expected` lists the `/`-separated paths of the benchmarks that the run
+/// should have covered, typically the benchmarks of the baseline that the
+/// merged dataset will be compared against. Those that are not found in any
+/// shard are reported as [missing](MergedShards::missing), which usually means
+/// that they were not assigned to any shard.
+pub fn shards(
+    shards: &[Dataset],
+    expected: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<MergedShards, OverlapError> {
+    let mut owners = BTreeMap::<&str, Vec<usize>>::new();
+    for (idx, shard) in shards.iter().enumerate() {
+        for bench in shard.benchmarks() {
+            if !bench.measurements.is_empty() {
+                owners.entry(&bench.path).or_default().push(idx);
+            }
+        }
+    }
+    let overlaps = owners
+        .iter()
+        .filter(|(_, shards)| shards.len() > 1)
+        .map(|(&benchmark, shards)| Overlap {
+            benchmark: benchmark.to_owned(),
+            shards: shards.clone(),
+        })
+        .collect::<Vec<_>>();
+    if !overlaps.is_empty() {
+        return Err(OverlapError { overlaps });
+    }
+    let dataset = shards
+        .iter()
+        .fold(Dataset::default(), |merged, shard| merged.join(shard));
+    let missing = expected
+        .into_iter()
+        .filter(|path| !owners.contains_key(path.as_ref()))
+        .map(|path| path.as_ref().to_owned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    Ok(MergedShards { dataset, missing })
+}
+
+/// Result of merging the shards of a benchmark run with [`shards()`]
+#[derive(Clone, Debug, Default)]
+pub struct MergedShards {
+    /// Benchmarks of all shards
+    pub dataset: Dataset,
+
+    /// Expected benchmarks that were not found in any shard, sorted by path
+    pub missing: Vec<String>,
+}
+//
+impl MergedShards {
+    /// Truth that all expected benchmarks were found
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Benchmarks that were measured by several shards of a run
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OverlapError {
+    /// Benchmarks that appear in several shards, sorted by path
+    pub overlaps: Vec<Overlap>,
+}
+//
+impl Display for OverlapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} benchmark(s) appear in several shards",
+            self.overlaps.len()
+        )?;
+        for overlap in &self.overlaps {
+            write!(f, "\n- {overlap}")?;
+        }
+        Ok(())
+    }
+}
+//
+impl std::error::Error for OverlapError {}
+
+/// Benchmark that was measured by several shards of a run
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Overlap {
+    /// `/`-separated path of the benchmark
+    pub benchmark: String,
+
+    /// Indices of the shards that contain the benchmark
+    pub shards: Vec<usize>,
+}
+//
+impl Display for Overlap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let shards = self
+            .shards
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{} (shards {})", self.benchmark, shards.join(", "))
+    }
+}
@@ -0,0 +1,204 @@
+//! cargo-criterion's machine-readable message stream
+//!
+//! When run with `--message-format=json`, cargo-criterion prints one JSON
+//! message per line on stdout as benchmarks complete. This module parses this
+//! stream into typed [`Message`]s, which is useful for monitoring a benchmark
+//! run while it is in progress, and lets you [`correlate()`] the completed
+//! benchmarks with the data that cargo-criterion recorded on disk.
+
+use crate::{layout, Benchmark, ChangeDirection, Error, Search};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io::{self, BufRead},
+    path::{Component, Path, PathBuf},
+};
+
+/// Message from cargo-criterion's JSON message stream
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message {
+    /// A benchmark has been measured and analyzed
+    BenchmarkComplete(Box<BenchmarkComplete>),
+
+    /// All benchmarks of a benchmark group have completed
+    GroupComplete(GroupComplete),
+
+    /// Message that this library does not know about
+    ///
+    /// cargo-criterion also forwards cargo's own build messages, which end up
+    /// here, and future versions may introduce new kinds of messages.
+    #[serde(other)]
+    Other,
+}
+
+/// Results of a completed benchmark
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct BenchmarkComplete {
+    /// Full benchmark identifier, e.g. `group/function/value`
+    pub id: String,
+
+    /// Directory where the HTML report of this benchmark is written
+    pub report_directory: PathBuf,
+
+    /// Number of iterations of each sample
+    pub iteration_count: Vec<u64>,
+
+    /// Measured value of each sample, in [`unit`](Self::unit)
+    pub measured_values: Vec<f64>,
+
+    /// Unit of the measured values (e.g. `ns`)
+    pub unit: String,
+
+    /// Throughputs of the benchmark, if any
+    pub throughput: Vec<MessageThroughput>,
+
+    /// Typical value of one iteration, as reported by Criterion
+    pub typical: MessageEstimate,
+
+    /// Mean value of one iteration
+    pub mean: MessageEstimate,
+
+    /// Median value of one iteration
+    pub median: MessageEstimate,
+
+    /// Median absolute deviation of the value of one iteration
+    pub median_abs_dev: MessageEstimate,
+
+    /// Slope of the linear regression, if the sampling mode allowed for it
+    pub slope: Option<MessageEstimate>,
+
+    /// Change with respect to the previous run, if any
+    pub change: Option<MessageChange>,
+}
+//
+impl BenchmarkComplete {
+    /// Path from the data root to the data directory of this benchmark
+    ///
+    /// This is deduced from the report directory, which cargo-criterion lays
+    /// out like the data directory. Returns `None` if the report directory does
+    /// not follow the expected layout.
+    pub fn path_from_data_root(&self) -> Option<PathBuf> {
+        let components = self.report_directory.components().collect::<Vec<_>>();
+        let reports_dir =
+            layout::REPORTS_DIR_COMPONENTS.map(|name| Component::Normal(name.as_ref()));
+        let start = components
+            .windows(reports_dir.len())
+            .position(|window| window == reports_dir)?
+            + reports_dir.len();
+        let path = components[start..].iter().collect::<PathBuf>();
+        (path != Path::new("")).then_some(path)
+    }
+}
+
+/// Throughput of a benchmark, as reported in messages
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct MessageThroughput {
+    /// Amount of work performed per iteration
+    pub per_iteration: u64,
+
+    /// Unit of work (e.g. `bytes` or `elements`)
+    pub unit: String,
+}
+
+/// Statistical estimate, as reported in messages
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct MessageEstimate {
+    /// Point estimate
+    pub estimate: f64,
+
+    /// Lower bound of the confidence interval
+    pub lower_bound: f64,
+
+    /// Upper bound of the confidence interval
+    pub upper_bound: f64,
+
+    /// Unit of the estimate (`%` for relative changes)
+    pub unit: String,
+}
+
+/// Change of a benchmark with respect to the previous run
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct MessageChange {
+    /// Relative change of the mean, in percent
+    pub mean: MessageEstimate,
+
+    /// Relative change of the median, in percent
+    pub median: MessageEstimate,
+
+    /// Significance of the change
+    pub change: ChangeDirection,
+}
+
+/// Completion of a benchmark group
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct GroupComplete {
+    /// Name of the benchmark group
+    pub group_name: String,
+
+    /// Full identifiers of the benchmarks within the group
+    pub benchmarks: Vec<String>,
+
+    /// Directory where the HTML report of this group is written
+    pub report_directory: PathBuf,
+}
+
+/// Parse cargo-criterion's JSON message stream, one message per line
+///
+/// Blank lines are ignored. Lines that are not valid messages are reported as
+/// errors of kind [`io::ErrorKind::InvalidData`], after which parsing can
+/// carry on with the next line.
+pub fn read_messages(reader: impl BufRead) -> impl Iterator<Item = io::Result<Message>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(parse_message(&line)),
+        Err(e) => Some(Err(e)),
+    })
+}
+
+/// Parse a single line of cargo-criterion's JSON message stream
+pub fn parse_message(line: &str) -> io::Result<Message> {
+    serde_json::from_str(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Completed benchmark, along with its data on disk
+#[derive(Debug)]
+pub struct Correlation {
+    /// Message announcing the benchmark's completion
+    pub message: Box<BenchmarkComplete>,
+
+    /// Recorded data of the benchmark, if it could be found
+    ///
+    /// The measurement from the run that emitted the message is normally the
+    /// [latest measurement](Benchmark::latest_measurement) of this benchmark.
+    pub benchmark: Option<Benchmark>,
+}
+
+/// Associate completed benchmarks with the data that cargo-criterion recorded
+///
+/// `search` should look into the `target` directory that cargo-criterion
+/// wrote to. Messages other than [`Message::BenchmarkComplete`] are ignored,
+/// and results are returned in message order. If a benchmark was completed
+/// several times, its data is only associated with the first message.
+pub fn correlate(
+    messages: impl IntoIterator<Item = Message>,
+    search: Search,
+) -> Result<Vec<Correlation>, Error> {
+    let mut benchmarks = search
+        .find_all()
+        .map(|bench| bench.map(|bench| (bench.path_from_data_root().as_path().to_owned(), bench)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(messages
+        .into_iter()
+        .filter_map(|message| match message {
+            Message::BenchmarkComplete(message) => Some(message),
+            _ => None,
+        })
+        .map(|message| {
+            let benchmark = message
+                .path_from_data_root()
+                .and_then(|path| benchmarks.remove(&path));
+            Correlation { message, benchmark }
+        })
+        .collect())
+}
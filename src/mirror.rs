@@ -0,0 +1,205 @@
+//! Machine-local copy of benchmark data that survives `cargo clean`
+//!
+//! cargo-criterion stores its data inside of the `target` directory, so the
+//! whole benchmark history is lost whenever `cargo clean` is run. A [`Mirror`]
+//! is a directory outside of `target` with the same layout, into which new
+//! measurements can be copied with [`Mirror::push()`] after each benchmark
+//! run, and from which a wiped `target` directory can be replenished with
+//! [`Mirror::pull()`]. Mirrored data can also be read directly with
+//! [`Search::in_mirror()`]. Measurement [artifacts](crate::layout) are
+//! mirrored along with their measurements.
+//!
+//! This module is only available when the `mirror` feature is enabled.
+
+use crate::{decode, layout, BenchmarkMetadata, Error, Search};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// Name of the directory that holds all mirrors, within the user data directory
+const MIRRORS_DIR_NAME: &str = "criterion-cbor";
+
+/// Machine-local copy of a project's benchmark data
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mirror {
+    /// Root of the mirror, which is laid out like a `target` directory
+    root: PathBuf,
+}
+//
+impl Mirror {
+    /// Use the default mirror location for a Cargo project/workspace
+    ///
+    /// Mirrors are stored under `$XDG_DATA_HOME/criterion-cbor/`, or
+    /// `~/.local/share/criterion-cbor/` if `XDG_DATA_HOME` is not set, in a
+    /// directory whose name is derived from the absolute path of the Cargo
+    /// root. The mirror directory is created if it does not exist yet.
+    pub fn for_cargo_root(cargo_root: impl AsRef<Path>) -> io::Result<Self> {
+        let cargo_root = fs::canonicalize(cargo_root)?;
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "neither XDG_DATA_HOME nor HOME is set",
+                )
+            })?;
+        let project_hash = format!("{:016x}", fnv1a(cargo_root.as_os_str().as_encoded_bytes()));
+        Self::at(data_home.join(MIRRORS_DIR_NAME).join(project_hash))
+    }
+
+    /// Use a mirror at a custom location
+    ///
+    /// The directory is created if it does not exist yet.
+    pub fn at(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().to_owned();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Location of this mirror
+    ///
+    /// This directory is laid out like a `target` directory, so it can be used
+    /// anywhere a `target` directory is expected.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Copy new benchmark data from a search into this mirror
+    pub fn push(&self, search: Search) -> Result<SyncReport, Error> {
+        sync(search, &self.root)
+    }
+
+    /// Copy mirrored benchmark data that is missing from a `target` directory
+    /// back into it
+    ///
+    /// The `target` directory is created if it does not exist yet.
+    pub fn pull(&self, target_dir: impl AsRef<Path>) -> Result<SyncReport, Error> {
+        let target_dir = target_dir.as_ref();
+        fs::create_dir_all(target_dir).map_err(io_error(target_dir))?;
+        sync(Search::in_mirror(self)?, target_dir)
+    }
+}
+
+/// Outcome of copying benchmark data between two locations
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SyncReport {
+    /// Number of benchmarks that were inspected
+    pub benchmarks: usize,
+
+    /// Number of measurement files that were copied
+    pub copied_measurements: usize,
+
+    /// Number of measurement artifact files that were copied
+    pub copied_artifacts: usize,
+}
+//
+impl Display for SyncReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "copied {} measurements and {} artifacts from {} benchmarks",
+            self.copied_measurements, self.copied_artifacts, self.benchmarks
+        )
+    }
+}
+
+/// Copy the benchmark data found by a search into another `target` directory
+///
+/// Measurement and artifact files that already exist at the destination are
+/// left alone. Benchmark metadata files are only replaced if the source file points to a
+/// newer measurement, so that they keep pointing to the latest measurement of
+/// the benchmark.
+fn sync(search: Search, dest_target_dir: &Path) -> Result<SyncReport, Error> {
+    let dest_data_root = layout::data_root(dest_target_dir);
+    let mut report = SyncReport::default();
+    for bench in search.find_all() {
+        let bench = bench?;
+        report.benchmarks += 1;
+        let dest_dir = dest_data_root.join(bench.path_from_data_root());
+        fs::create_dir_all(&dest_dir).map_err(io_error(&dest_dir))?;
+        for measurement in bench.measurements() {
+            let source = measurement.path();
+            let dest = dest_dir.join(
+                source
+                    .file_name()
+                    .expect("Measurement paths should have a file name"),
+            );
+            if !dest.exists() {
+                fs::copy(source, &dest).map_err(io_error(&dest))?;
+                report.copied_measurements += 1;
+            }
+            report.copied_artifacts +=
+                copy_missing(&measurement.artifacts_dir(), &layout::artifacts_dir(&dest))?;
+        }
+        let source = bench.dir_path().join(layout::BENCHMARK_FILE_NAME);
+        let dest = dest_dir.join(layout::BENCHMARK_FILE_NAME);
+        let is_newer = match decode::read_cbor::<BenchmarkMetadata>(&dest) {
+            // Mirrored metadata with an unusable latest record gets replaced
+            Ok(dest_metadata) => {
+                bench.metadata()?.latest_local_datetime()?.earliest()
+                    > dest_metadata
+                        .latest_local_datetime()
+                        .ok()
+                        .and_then(|datetime| datetime.earliest())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => true,
+            Err(e) => return Err(Error::reading(dest, e)),
+        };
+        if is_newer {
+            fs::copy(source, &dest).map_err(io_error(&dest))?;
+        }
+    }
+    Ok(report)
+}
+
+/// Recursively copy the files of a directory that are missing at the
+/// destination, returning how many files were copied
+///
+/// Nothing is copied if the source directory does not exist.
+fn copy_missing(source_dir: &Path, dest_dir: &Path) -> Result<usize, Error> {
+    if !source_dir.exists() {
+        return Ok(0);
+    }
+    let mut copied = 0;
+    for entry in WalkDir::new(source_dir).min_depth(1) {
+        let entry = entry?;
+        let dest = dest_dir.join(
+            entry
+                .path()
+                .strip_prefix(source_dir)
+                .expect("Walkdir should prefix entry paths with the walk root"),
+        );
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest).map_err(io_error(&dest))?;
+        } else if entry.file_type().is_file() && !dest.exists() {
+            let parent = dest.parent().expect("Copied files should have a parent");
+            fs::create_dir_all(parent).map_err(io_error(parent))?;
+            fs::copy(entry.path(), &dest).map_err(io_error(&dest))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Attribute an I/O error to the file or directory that was being written
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+/// 64-bit FNV-1a hash
+///
+/// Unlike the hashers of the standard library, this is guaranteed to produce
+/// the same output across Rust versions, which is needed for stable mirror
+/// locations.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,14 @@
+//! Commonly used items, for glob import
+//!
+//! ```
+//! use criterion_cbor::prelude::*;
+//! ```
+//!
+//! This brings the main entry points of the library into scope, along with the
+//! types that are needed to consume their output.
+
+pub use crate::{
+    dataset::Dataset, decode::DecodeErrorPolicy, load_latest, units::ValueUnit, Benchmark,
+    BenchmarkId, BenchmarkMetadata, ChangeDirection, Error, Estimate, Estimates, Measurement,
+    MeasurementData, MemberId, RawBenchmarkId, Search, SearchError, Statistic,
+};
@@ -0,0 +1,235 @@
+//! Association of profiler outputs with benchmark measurements
+//!
+//! A regression report is much more actionable when it comes with a profile
+//! of the regressed code. When benchmarks are run with `--profile-time` and a
+//! Criterion profiler like `pprof`'s flamegraph output, each benchmark gets a
+//! flamegraph in a `profile/` subdirectory of its Criterion output directory,
+//! which mirrors the layout of the data root. [`link_flamegraphs()`] finds these
+//! flamegraphs, along with those that were attached to measurements as
+//! [artifacts](crate::layout::artifacts_dir), and associates each of them with
+//! a measurement. Reports like
+//! [`single_bench_html_with_flamegraphs()`](crate::report::single_bench_html_with_flamegraphs)
+//! can then link regressed measurements to their flamegraph.
+//!
+//! This module is only available when the `profile` feature is enabled.
+
+use crate::{Error, Search};
+use chrono::{DateTime, Utc};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the directory where Criterion profilers write their outputs, within
+/// the output directory of each benchmark
+const PROFILE_DIR_NAME: &str = "profile";
+
+/// Extension of flamegraph files
+const FLAMEGRAPH_EXTENSION: &str = "svg";
+
+/// Find the flamegraphs of the benchmarks of a search, and associate each of
+/// them with a measurement
+///
+/// `profiles_dir` is the directory below which profiler outputs are laid out
+/// like the data root, which is `target/criterion` for Criterion's own output
+/// directory. Flamegraphs found there are associated with the measurement of
+/// their benchmark that was taken closest to their modification time, since
+/// profiling runs do not record measurements of their own. Flamegraphs that
+/// were attached to a measurement as artifacts are associated with that
+/// measurement.
+pub fn link_flamegraphs(
+    search: Search,
+    profiles_dir: impl AsRef<Path>,
+) -> Result<FlamegraphLinks, Error> {
+    let profiles_dir = profiles_dir.as_ref();
+    let mut links = FlamegraphLinks::default();
+    for bench in search.find_all() {
+        let bench = bench?;
+        let mut measurements = Vec::new();
+        let mut flamegraphs = Vec::new();
+        for measurement in bench.measurements() {
+            let datetime = measurement.precise_datetime()?;
+            for artifact in measurement.artifacts()? {
+                if is_flamegraph(&artifact) {
+                    flamegraphs.push(Flamegraph {
+                        modified: modification_time(&artifact)?,
+                        path: artifact,
+                        measurement: measurement.path().to_owned(),
+                        measurement_datetime: datetime,
+                        attached: true,
+                    });
+                }
+            }
+            measurements.push((datetime, measurement.path().to_owned()));
+        }
+
+        let profile_dir = profiles_dir
+            .join(bench.path_from_data_root())
+            .join(PROFILE_DIR_NAME);
+        for path in list_flamegraphs(&profile_dir)? {
+            let modified = modification_time(&path)?;
+            let (measurement_datetime, measurement) = measurements
+                .iter()
+                .min_by_key(|(datetime, _)| (*datetime - modified).abs())
+                .cloned()
+                .expect("Benchmarks should have at least one measurement");
+            flamegraphs.push(Flamegraph {
+                path,
+                measurement,
+                measurement_datetime,
+                modified,
+                attached: false,
+            });
+        }
+
+        if !flamegraphs.is_empty() {
+            flamegraphs.sort_by(|flamegraph1, flamegraph2| {
+                (flamegraph1.measurement_datetime, &flamegraph1.path)
+                    .cmp(&(flamegraph2.measurement_datetime, &flamegraph2.path))
+            });
+            links.0.insert(bench.slash_path(), flamegraphs);
+        }
+    }
+    Ok(links)
+}
+
+/// Flamegraphs of a set of benchmarks, produced by [`link_flamegraphs()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlamegraphLinks(BTreeMap<String, Vec<Flamegraph>>);
+//
+impl FlamegraphLinks {
+    /// Number of flamegraphs
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+
+    /// Truth that no flamegraph was found
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Flamegraphs of a benchmark, designated by its `/`-separated path, from
+    /// the oldest measurement to the latest one
+    pub fn for_benchmark(&self, benchmark: &str) -> &[Flamegraph] {
+        self.0.get(benchmark).map_or(&[], Vec::as_slice)
+    }
+
+    /// Flamegraphs of the measurement of a benchmark that was taken at a
+    /// certain date and time
+    ///
+    /// The date and time must be the precise one that is recorded in the
+    /// measurement file, i.e. [`MeasurementData::datetime`](crate::MeasurementData::datetime).
+    pub fn for_measurement(
+        &self,
+        benchmark: &str,
+        datetime: DateTime<Utc>,
+    ) -> impl Iterator<Item = &Flamegraph> {
+        self.for_benchmark(benchmark)
+            .iter()
+            .filter(move |flamegraph| flamegraph.measurement_datetime == datetime)
+    }
+
+    /// Flamegraph of the latest profiled measurement of a benchmark
+    ///
+    /// If several flamegraphs are associated with that measurement, attached
+    /// ones are preferred, then the most recently modified one is returned.
+    pub fn latest(&self, benchmark: &str) -> Option<&Flamegraph> {
+        let flamegraphs = self.for_benchmark(benchmark);
+        let latest = flamegraphs.last()?.measurement_datetime;
+        flamegraphs
+            .iter()
+            .filter(|flamegraph| flamegraph.measurement_datetime == latest)
+            .max_by_key(|flamegraph| (flamegraph.attached, flamegraph.modified))
+    }
+
+    /// Enumerate the flamegraphs, sorted by benchmark path and measurement
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Flamegraph)> {
+        self.0.iter().flat_map(|(benchmark, flamegraphs)| {
+            flamegraphs
+                .iter()
+                .map(move |flamegraph| (benchmark.as_str(), flamegraph))
+        })
+    }
+}
+//
+impl Display for FlamegraphLinks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (benchmark, flamegraph) in self.iter() {
+            writeln!(f, "{benchmark}: {flamegraph}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Flamegraph associated with a measurement
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Flamegraph {
+    /// Location of the flamegraph file
+    pub path: PathBuf,
+
+    /// Location of the associated measurement file
+    pub measurement: PathBuf,
+
+    /// Precise date and time of the associated measurement
+    pub measurement_datetime: DateTime<Utc>,
+
+    /// Last modification time of the flamegraph file
+    pub modified: DateTime<Utc>,
+
+    /// Truth that the flamegraph was attached to the measurement as an
+    /// artifact, rather than associated with it by modification time
+    pub attached: bool,
+}
+//
+impl Display for Flamegraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} measurement of {})",
+            self.path.display(),
+            if self.attached { "attached to" } else { "near" },
+            self.measurement_datetime.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+}
+
+/// Truth that a file looks like a flamegraph
+fn is_flamegraph(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == FLAMEGRAPH_EXTENSION)
+        && path.is_file()
+}
+
+/// List the flamegraphs of a profiler output directory, if it exists
+fn list_flamegraphs(profile_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let io_error = |source| Error::Io {
+        path: profile_dir.to_owned(),
+        source,
+    };
+    let entries = match std::fs::read_dir(profile_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(e)),
+    };
+    let mut flamegraphs = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(io_error)?.path();
+        if is_flamegraph(&path) {
+            flamegraphs.push(path);
+        }
+    }
+    Ok(flamegraphs)
+}
+
+/// Last modification time of a file
+fn modification_time(path: &Path) -> Result<DateTime<Utc>, Error> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::from)
+        .map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })
+}
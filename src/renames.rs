@@ -0,0 +1,156 @@
+//! Benchmark renames tracked in a version-controlled file
+//!
+//! Renaming a benchmark makes cargo-criterion record its data in a new
+//! directory, so its history appears to end abruptly while a new benchmark
+//! with no history appears. A rename file, conventionally named
+//! `benchmark-renames.toml` and committed at the root of a Cargo project,
+//! records such renames so that histories can be stitched back together:
+//!
+//! ```toml
+//! [[renames]]
+//! from = "parser/small"
+//! to = "parser/small_input"
+//! effective = "2024-05-01T00:00:00Z"
+//! ```
+//!
+//! Benchmarks are identified by their `/`-separated path from the Criterion
+//! data root. Measurements of the `from` benchmark that were taken before the
+//! `effective` date are attributed to the `to` benchmark, while later ones are
+//! left alone, so that the old name can be reused for a new benchmark. Renames
+//! can be chained, e.g. `a` to `b` and then `b` to `c`.
+//!
+//! Use [`Dataset::apply_renames()`](crate::dataset::Dataset::apply_renames)
+//! to apply renames to a dataset, after which its
+//! [histories](crate::dataset::LoadedBenchmark::history) and
+//! [comparisons](crate::dataset::Dataset::compare) follow renamed benchmarks.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{io, path::Path};
+
+/// Conventional name of the benchmark rename file
+pub const DEFAULT_FILE_NAME: &str = "benchmark-renames.toml";
+
+/// Set of benchmark renames, as stored in a rename file
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Renames {
+    /// Renames, in no particular order
+    #[serde(default)]
+    pub renames: Vec<Rename>,
+}
+//
+impl Renames {
+    /// Load benchmark renames from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save benchmark renames to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = toml::to_string_pretty(self)
+            .expect("Benchmark renames should always be serializable to TOML");
+        std::fs::write(path, text)
+    }
+
+    /// Current path of the benchmark that a measurement belongs to
+    ///
+    /// `path` is the path of the benchmark under which the measurement was
+    /// recorded, and `datetime` is the date and time at which it was taken.
+    /// Renames are followed as long as they took effect after the measurement.
+    pub fn resolve<'a>(&'a self, mut path: &'a str, datetime: DateTime<Utc>) -> &'a str {
+        // Following more renames than there are means going around a cycle
+        for _ in 0..self.renames.len() {
+            let Some(rename) = self
+                .renames
+                .iter()
+                .find(|rename| rename.from == path && datetime < rename.effective)
+            else {
+                break;
+            };
+            path = &rename.to;
+        }
+        path
+    }
+}
+
+/// Rename of one benchmark
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Rename {
+    /// Path of the benchmark before the rename
+    pub from: String,
+
+    /// Path of the benchmark after the rename
+    pub to: String,
+
+    /// Date and time at which the rename took effect, as an RFC 3339 string
+    pub effective: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(text: &str) -> DateTime<Utc> {
+        text.parse().unwrap()
+    }
+
+    fn renames(text: &str) -> Renames {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn resolve_follows_chained_renames_until_they_take_effect() {
+        let renames = renames(
+            r#"
+            [[renames]]
+            from = "a"
+            to = "b"
+            effective = "2024-05-01T00:00:00Z"
+
+            [[renames]]
+            from = "b"
+            to = "c"
+            effective = "2024-08-01T00:00:00Z"
+            "#,
+        );
+        let resolve = |path, datetime| renames.resolve(path, self::datetime(datetime));
+        assert_eq!(resolve("a", "2024-04-30T23:59:59Z"), "c");
+        assert_eq!(resolve("a", "2024-05-01T00:00:00Z"), "a");
+        assert_eq!(resolve("b", "2024-06-01T00:00:00Z"), "c");
+        assert_eq!(resolve("b", "2024-09-01T00:00:00Z"), "b");
+        assert_eq!(resolve("d", "2024-01-01T00:00:00Z"), "d");
+    }
+
+    #[test]
+    fn resolve_terminates_on_cycles() {
+        let renames = renames(
+            r#"
+            [[renames]]
+            from = "a"
+            to = "b"
+            effective = "2030-01-01T00:00:00Z"
+
+            [[renames]]
+            from = "b"
+            to = "a"
+            effective = "2030-01-01T00:00:00Z"
+            "#,
+        );
+        assert_eq!(renames.resolve("a", datetime("2024-01-01T00:00:00Z")), "a");
+    }
+
+    #[test]
+    fn renames_round_trip_through_toml() {
+        let renames = Renames {
+            renames: vec![Rename {
+                from: "parser/small".to_owned(),
+                to: "parser/small_input".to_owned(),
+                effective: datetime("2024-05-01T00:00:00Z"),
+            }],
+        };
+        let text = toml::to_string_pretty(&renames).unwrap();
+        assert_eq!(self::renames(&text), renames);
+        assert_eq!(self::renames(""), Renames::default());
+    }
+}
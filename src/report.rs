@@ -0,0 +1,1218 @@
+//! Summaries of benchmark comparisons for human consumption
+//!
+//! Comparisons of large benchmark suites can report dozens of changes, most
+//! of which do not matter much. This module helps picking the ones that should
+//! be highlighted in a digest. [`leaderboard()`] assembles the usual weekly
+//! performance review: the slowest benchmarks, and the ones that improved or
+//! regressed the most recently. [`suite_cost()`] tells which benchmarks make
+//! a full `cargo criterion` run slow.
+//!
+//! Digests are often shared beyond English-speaking teams, so numbers and
+//! dates can be formatted according to a [`Locale`] before they are published.
+//!
+//! Dashboards may also want to display the distribution of each benchmark's
+//! samples, which can be far too numerous to be shipped as-is.
+//! [`sparklines()`] down-samples them to a bounded number of points.
+//!
+//! The history of a single benchmark can be exported as a self-contained HTML
+//! page with [`single_bench_html()`], which is convenient to attach to a bug
+//! report about a regression. Its colors can be adjusted to accessibility or
+//! branding requirements with a [`Theme`].
+//! There is no suite-wide page, so large suites get one page per benchmark
+//! and no page grows with the number of benchmarks.
+
+use crate::{
+    dataset::Dataset,
+    history::History,
+    snapshot::{SnapshotChange, SnapshotComparison},
+    stats::Rng,
+    units::ValueUnit,
+    Benchmark, ChangeDirection, Error, MeasurementData, Search, Statistic,
+};
+use chrono::{format::StrftimeItems, DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Criterion used to rank changes in [`top_changes()`]
+///
+/// Deltas are measured on the statistic that the changes were
+/// [compared on](SnapshotChange::statistic), while significance and effect
+/// size are always based on the mean, whose standard error is known.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RankBy {
+    /// Magnitude of the change, in nanoseconds
+    ///
+    /// This highlights the changes that save or cost the most time, which may
+    /// be small in relative terms on long-running benchmarks. Changes of
+    /// benchmarks whose values are not durations are ranked last.
+    AbsoluteTimeDelta,
+
+    /// Magnitude of the relative change
+    RelativeDelta,
+
+    /// Magnitude of the change of the mean with respect to its standard error
+    ///
+    /// Changes whose significance is unknown, because they come from a
+    /// snapshot that does not record standard errors, are ranked last.
+    Significance,
+
+    /// Magnitude of the change of the mean with respect to the standard
+    /// deviation of per-iteration values (Cohen's d)
+    ///
+    /// Unlike [`Significance`](Self::Significance), this does not favor
+    /// changes of benchmarks that have many samples or very little noise.
+    /// Changes whose effect size is unknown, because they come from a snapshot
+    /// that does not record standard deviations, are ranked last.
+    EffectSize,
+}
+
+/// Select the `n` most important changes of a comparison, most important first
+pub fn top_changes(
+    comparison: &SnapshotComparison,
+    n: usize,
+    rank_by: RankBy,
+) -> Vec<&SnapshotChange> {
+    let key = |change: &SnapshotChange| {
+        let key = match rank_by {
+            RankBy::AbsoluteTimeDelta => {
+                let timed = change.recorded.unit.is_time() && change.latest.unit.is_time();
+                timed.then(|| change.delta())
+            }
+            RankBy::RelativeDelta => Some(change.change()),
+            RankBy::Significance => change.mean_significance(),
+            RankBy::EffectSize => change.cohens_d(),
+        };
+        // Unknown and NaN keys are ranked last
+        key.map(f64::abs)
+            .filter(|key| !key.is_nan())
+            .unwrap_or(f64::NEG_INFINITY)
+    };
+    let mut changes = comparison.changed.iter().collect::<Vec<_>>();
+    changes.sort_by(|x, y| key(y).total_cmp(&key(x)));
+    changes.truncate(n);
+    changes
+}
+
+/// Benchmark rankings produced by [`leaderboard()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Leaderboard {
+    /// Statistic that benchmarks were ranked by
+    pub metric: Statistic,
+
+    /// Duration over which changes were measured
+    pub period: TimeDelta,
+
+    /// Benchmarks with the highest latest execution time, highest first
+    ///
+    /// Benchmarks whose values are not durations cannot be compared with
+    /// execution times, and are not listed here.
+    pub slowest: Vec<LeaderboardEntry>,
+
+    /// Benchmarks whose value decreased the most over the period, in relative
+    /// terms, most improved first
+    pub most_improved: Vec<LeaderboardEntry>,
+
+    /// Benchmarks whose value increased the most over the period, in relative
+    /// terms, most regressed first
+    pub most_regressed: Vec<LeaderboardEntry>,
+}
+//
+impl Display for Leaderboard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let days = self.period.num_days();
+        let sections = [
+            (
+                format!("Slowest benchmarks ({})", self.metric),
+                &self.slowest,
+            ),
+            (
+                format!("Most improved over the last {days} days"),
+                &self.most_improved,
+            ),
+            (
+                format!("Most regressed over the last {days} days"),
+                &self.most_regressed,
+            ),
+        ];
+        for (idx, (title, entries)) in sections.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{title}:")?;
+            if entries.is_empty() {
+                writeln!(f, "  (none)")?;
+            }
+            for (rank, entry) in entries.iter().enumerate() {
+                writeln!(f, "  {}. {entry}", rank + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark of a [`Leaderboard`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    /// `/`-separated path of the benchmark
+    pub benchmark: String,
+
+    /// Latest value of the ranked statistic
+    pub value: f64,
+
+    /// Unit of `value`
+    pub unit: ValueUnit,
+
+    /// Relative change of the ranked statistic over the period (e.g. 0.05 for
+    /// +5%), if the benchmark was already measured at the start of the period
+    pub change: Option<f64>,
+}
+//
+impl Display for LeaderboardEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.benchmark,
+            self.unit.format_value(self.value)
+        )?;
+        if let Some(change) = self.change {
+            write!(f, " ({:+.2}%)", change * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rank the benchmarks of a dataset for a periodic performance review
+///
+/// Each section of the leaderboard lists at most `n` benchmarks, ranked by the
+/// `metric` estimate of their latest measurement. Changes are measured over
+/// the `period` that ends with the newest measurement of the dataset, by
+/// comparing the latest measurement of each benchmark with the last one that
+/// was taken before the period started. Benchmarks that were not measured
+/// before the period started, or whose estimates are not finite, cannot be
+/// ranked by change. Only benchmarks whose values are durations are ranked by
+/// speed.
+pub fn leaderboard(
+    dataset: &Dataset,
+    metric: Statistic,
+    period: TimeDelta,
+    n: usize,
+) -> Leaderboard {
+    let value = |data: &MeasurementData| data.estimates.get(metric).point_estimate;
+    let end = dataset
+        .benchmarks()
+        .filter_map(|bench| Some(bench.latest()?.datetime))
+        .max();
+    let start = end.map(|end| end - period);
+    let entries = dataset
+        .benchmarks()
+        .filter_map(|bench| {
+            let latest = bench.latest()?;
+            let baseline = bench
+                .measurements
+                .iter()
+                .rev()
+                .find(|data| Some(data.datetime) <= start);
+            let change = baseline
+                .map(|baseline| value(latest) / value(baseline) - 1.0)
+                .filter(|change| change.is_finite());
+            Some(LeaderboardEntry {
+                benchmark: bench.path.clone(),
+                value: value(latest),
+                unit: bench.value_unit.clone(),
+                change,
+            })
+        })
+        .collect::<Vec<_>>();
+    let top = |key: &dyn Fn(&LeaderboardEntry) -> Option<f64>| {
+        let mut ranked = entries
+            .iter()
+            .filter_map(|entry| Some((key(entry).filter(|key| !key.is_nan())?, entry)))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(key1, _), (key2, _)| key2.total_cmp(key1));
+        ranked
+            .into_iter()
+            .take(n)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    };
+    Leaderboard {
+        metric,
+        period,
+        slowest: top(&|entry| entry.unit.is_time().then_some(entry.value)),
+        most_improved: top(&|entry| {
+            entry
+                .change
+                .filter(|&change| change < 0.0)
+                .map(|change| -change)
+        }),
+        most_regressed: top(&|entry| entry.change.filter(|&change| change > 0.0)),
+    }
+}
+
+/// Wall time spent measuring each benchmark, produced by [`suite_cost()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SuiteCost {
+    /// Unit of all measurement times, which is that of the values of the
+    /// timed benchmarks
+    pub unit: ValueUnit,
+
+    /// Time spent measuring each benchmark, indexed by `/`-separated
+    /// benchmark path
+    pub benchmarks: BTreeMap<String, f64>,
+
+    /// Time spent measuring the benchmarks of each top-level group or function
+    /// directory
+    pub groups: BTreeMap<String, f64>,
+
+    /// Time spent measuring all benchmarks
+    pub total: f64,
+
+    /// Benchmarks whose values are not durations, and whose cost is thus
+    /// unknown
+    pub not_timed: Vec<String>,
+}
+//
+impl SuiteCost {
+    /// Benchmarks sorted by decreasing cost
+    pub fn most_expensive(&self) -> Vec<(&str, f64)> {
+        sorted_by_cost(&self.benchmarks)
+    }
+
+    /// Groups sorted by decreasing cost
+    pub fn most_expensive_groups(&self) -> Vec<(&str, f64)> {
+        sorted_by_cost(&self.groups)
+    }
+}
+//
+impl Display for SuiteCost {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Total measurement time: {}",
+            self.unit.format_value(self.total)
+        )?;
+        let sections = [
+            ("Groups", self.most_expensive_groups()),
+            ("Benchmarks", self.most_expensive()),
+        ];
+        for (title, costs) in sections {
+            writeln!(f, "\n{title}:")?;
+            for (name, time) in costs {
+                writeln!(
+                    f,
+                    "  {name}: {} ({:.1}%)",
+                    self.unit.format_value(time),
+                    time / self.total * 100.0
+                )?;
+            }
+        }
+        if !self.not_timed.is_empty() {
+            writeln!(f, "\nNot timed: {}", self.not_timed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate how much wall time each benchmark takes in a full
+/// `cargo criterion` run
+///
+/// The cost of a benchmark is the sum of the values of all samples of its
+/// latest measurement, i.e. the time during which its iterations were being
+/// measured. This does not include the warm-up phase, or the time spent
+/// analyzing the samples, which are roughly the same for all benchmarks.
+/// Benchmarks whose values are not durations (see [`Search::value_unit()`])
+/// are listed separately.
+pub fn suite_cost(search: Search) -> Result<SuiteCost, Error> {
+    let mut cost = SuiteCost::default();
+    for bench in search.find_all() {
+        let bench = bench?;
+        let path = bench.slash_path();
+        if !bench.value_unit().is_time() {
+            cost.not_timed.push(path);
+            continue;
+        }
+        let data = bench.latest_measurement().data()?;
+        cost.unit = bench.value_unit().clone();
+        let time = data
+            .values
+            .iter()
+            .filter(|value| value.is_finite())
+            .sum::<f64>();
+        let group = bench
+            .path_from_data_root()
+            .group()
+            .expect("Benchmark directories should lie inside of the data root");
+        *cost.groups.entry(group.into_owned()).or_default() += time;
+        cost.total += time;
+        cost.benchmarks.insert(path, time);
+    }
+    Ok(cost)
+}
+
+/// Sort the entries of a cost map by decreasing cost
+fn sorted_by_cost(costs: &BTreeMap<String, f64>) -> Vec<(&str, f64)> {
+    let mut sorted = costs
+        .iter()
+        .map(|(name, &ns)| (name.as_str(), ns))
+        .collect::<Vec<_>>();
+    sorted.sort_by(|(_, ns1), (_, ns2)| ns2.total_cmp(ns1));
+    sorted
+}
+
+/// Down-sample the latest measurement of every benchmark of a dataset
+///
+/// The result maps `/`-separated benchmark paths to the output of
+/// [`sparkline_samples()`]. Benchmarks without any measurement are ignored.
+pub fn sparklines(dataset: &Dataset, max_points: usize) -> BTreeMap<String, Vec<f64>> {
+    dataset
+        .benchmarks()
+        .filter_map(|bench| {
+            let data = bench.latest()?;
+            Some((bench.path.clone(), sparkline_samples(data, max_points)))
+        })
+        .collect()
+}
+
+/// Down-sample the per-iteration values of a measurement to at most
+/// `max_points` values
+///
+/// Values are selected by reservoir sampling, so that every sample has the
+/// same probability of being kept, and are returned in their original order.
+/// The random number generator uses a fixed seed, so the same measurement is
+/// always down-sampled in the same way.
+pub fn sparkline_samples(data: &MeasurementData, max_points: usize) -> Vec<f64> {
+    let values = &data.avg_values;
+    if values.len() <= max_points {
+        return values.clone();
+    }
+    let mut rng = Rng::default();
+    let mut reservoir = (0..max_points).collect::<Vec<_>>();
+    for idx in max_points..values.len() {
+        let slot = rng.index(idx + 1);
+        if slot < max_points {
+            reservoir[slot] = idx;
+        }
+    }
+    reservoir.sort_unstable();
+    reservoir.into_iter().map(|idx| values[idx]).collect()
+}
+
+/// Conventions for formatting numbers and dates in reports
+///
+/// The library's `Display` implementations and
+/// [`ValueUnit::format_value()`] always use English conventions. Their output
+/// can be converted to another locale's conventions with
+/// [`localize()`](Self::localize).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Locale {
+    /// Character that separates the integer and fractional part of numbers
+    pub decimal_separator: char,
+
+    /// Character that separates groups of three digits in the integer part of
+    /// numbers, if any
+    pub group_separator: Option<char>,
+
+    /// [`chrono` format string](chrono::format::strftime) used for dates and
+    /// times, which are displayed in UTC
+    ///
+    /// Invalid format strings are reported by
+    /// [`format_datetime()`](Self::format_datetime).
+    pub datetime_format: String,
+}
+//
+impl Locale {
+    /// Conventions of most continental European languages, e.g. "1.234,5"
+    pub fn european() -> Self {
+        Self {
+            decimal_separator: ',',
+            group_separator: Some('.'),
+            datetime_format: "%d.%m.%Y %H:%M:%S UTC".to_owned(),
+        }
+    }
+
+    /// Format a value expressed in a certain unit
+    pub fn format_value(&self, unit: &ValueUnit, value: f64) -> String {
+        self.localize(&unit.format_value(value))
+    }
+
+    /// Format a date and time
+    ///
+    /// An invalid [`datetime_format`](Self::datetime_format) is reported as an
+    /// error of kind [`io::ErrorKind::InvalidInput`].
+    pub fn format_datetime(&self, datetime: DateTime<Utc>) -> io::Result<String> {
+        let items = StrftimeItems::new(&self.datetime_format)
+            .parse()
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid datetime format {:?}: {e}", self.datetime_format),
+                )
+            })?;
+        Ok(datetime.format_with_items(items.iter()).to_string())
+    }
+
+    /// Convert the numbers of some English-formatted text to this locale
+    ///
+    /// Every sequence of ASCII digits, optionally followed by a `.` and more
+    /// digits, is considered to be a number. This is meant for formatted
+    /// values, and will also rewrite digits that are not numbers, such as
+    /// those of benchmark names.
+    pub fn localize(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let int_len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (int_part, tail) = rest.split_at(int_len);
+            for (idx, digit) in int_part.chars().enumerate() {
+                let remaining = int_len - idx;
+                if idx > 0 && remaining % 3 == 0 {
+                    output.extend(self.group_separator);
+                }
+                output.push(digit);
+            }
+            rest = tail;
+            let frac_len = rest
+                .strip_prefix('.')
+                .map(|frac| {
+                    frac.find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(frac.len())
+                })
+                .unwrap_or(0);
+            if frac_len > 0 {
+                output.push(self.decimal_separator);
+                output.push_str(&rest[1..=frac_len]);
+                rest = &rest[frac_len + 1..];
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+//
+impl Default for Locale {
+    /// English conventions, e.g. "1,234.5"
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            group_separator: Some(','),
+            datetime_format: "%Y-%m-%d %H:%M:%S UTC".to_owned(),
+        }
+    }
+}
+
+/// Colors of the HTML pages generated by this module
+///
+/// The default theme uses the [Okabe-Ito
+/// palette](https://jfly.uni-koeln.de/color/), whose colors remain
+/// distinguishable for people with the common forms of color blindness.
+/// Themes can be loaded from a TOML file where every color is optional, e.g.
+///
+/// ```toml
+/// line = "#000000"
+/// regressed = "rgb(204, 0, 0)"
+/// ```
+///
+/// Colors are CSS colors. They may only contain ASCII alphanumeric
+/// characters, spaces and `#(),.%`, which covers hexadecimal, named and
+/// functional notations.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Page background
+    pub background: String,
+
+    /// Text and table borders
+    pub foreground: String,
+
+    /// Line connecting the typical estimates of successive measurements
+    pub line: String,
+
+    /// Confidence interval band around the line
+    pub band: String,
+
+    /// Measurements that did not change significantly
+    pub unchanged: String,
+
+    /// Measurements that improved significantly
+    pub improved: String,
+
+    /// Measurements that regressed significantly
+    pub regressed: String,
+}
+//
+impl Theme {
+    /// Load a theme from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let theme: Self =
+            toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        theme
+            .check()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(theme)
+    }
+
+    /// Save a theme to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text =
+            toml::to_string_pretty(self).expect("Themes should always be serializable to TOML");
+        std::fs::write(path, text)
+    }
+
+    /// Colors of the theme, along with the name of their template placeholder
+    fn colors(&self) -> [(&'static str, &str); 7] {
+        [
+            ("background", &self.background),
+            ("foreground", &self.foreground),
+            ("line", &self.line),
+            ("band", &self.band),
+            ("unchanged", &self.unchanged),
+            ("improved", &self.improved),
+            ("regressed", &self.regressed),
+        ]
+    }
+
+    /// Check that the colors of the theme cannot escape from the style sheet
+    fn check(&self) -> Result<(), String> {
+        for (name, color) in self.colors() {
+            let valid = !color.is_empty()
+                && color
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || " #(),.%".contains(c));
+            if !valid {
+                return Err(format!("invalid {name} color {color:?}"));
+            }
+        }
+        Ok(())
+    }
+}
+//
+impl Default for Theme {
+    /// Color-blind-safe colors from the Okabe-Ito palette on a white background
+    fn default() -> Self {
+        Self {
+            background: "#FFFFFF".to_owned(),
+            foreground: "#222222".to_owned(),
+            line: "#0072B2".to_owned(),
+            band: "#56B4E9".to_owned(),
+            unchanged: "#0072B2".to_owned(),
+            improved: "#009E73".to_owned(),
+            regressed: "#D55E00".to_owned(),
+        }
+    }
+}
+
+/// Write the measurement history of a benchmark into a standalone HTML file
+///
+/// The page shows the typical estimate of every measurement, along with its
+/// confidence interval, in a chart that displays the details of each
+/// measurement on hover, followed by a table of the same data. Data and
+/// scripts are embedded in the page, which can thus be opened offline or
+/// attached to a bug report. Its colors are taken from `theme`. Malformed
+/// measurement files are handled according to the [decoding error
+/// policy](Search::on_decode_error) of the search.
+pub fn single_bench_html(
+    benchmark: &Benchmark,
+    theme: &Theme,
+    path: impl AsRef<Path>,
+) -> Result<(), Error> {
+    write_single_bench_html(benchmark, &|_| None, theme, path.as_ref())
+}
+
+/// Like [`single_bench_html()`], but link each measurement to its flamegraph
+///
+/// Measurements that have a flamegraph in `flamegraphs` get a link to it in
+/// the table, and their point of the chart opens it when clicked. The
+/// flamegraphs themselves are not embedded, so the links only work on the
+/// machine where the page was generated.
+#[cfg(feature = "profile")]
+pub fn single_bench_html_with_flamegraphs(
+    benchmark: &Benchmark,
+    flamegraphs: &crate::profile::FlamegraphLinks,
+    theme: &Theme,
+    path: impl AsRef<Path>,
+) -> Result<(), Error> {
+    let bench_path = benchmark.slash_path();
+    let flamegraph = |datetime| {
+        flamegraphs
+            .for_measurement(&bench_path, datetime)
+            .max_by_key(|flamegraph| (flamegraph.attached, flamegraph.modified))
+            .map(|flamegraph| flamegraph.path.clone())
+    };
+    write_single_bench_html(benchmark, &flamegraph, theme, path.as_ref())
+}
+
+/// Implementation of [`single_bench_html()`], where `flamegraph` locates the
+/// flamegraph of the measurement taken at a certain date and time, if any
+fn write_single_bench_html(
+    benchmark: &Benchmark,
+    flamegraph: &dyn Fn(DateTime<Utc>) -> Option<PathBuf>,
+    theme: &Theme,
+    path: &Path,
+) -> Result<(), Error> {
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    theme
+        .check()
+        .map_err(|e| io_error(io::Error::new(io::ErrorKind::InvalidInput, e)))?;
+    let title = benchmark.metadata()?.id.full_id();
+    let unit = benchmark.value_unit();
+    let points = History::load(benchmark)?
+        .points()
+        .iter()
+        .map(|point| {
+            let typical = point.estimates.typical();
+            Ok(HtmlPoint {
+                datetime: point.datetime.to_rfc3339(),
+                value: typical.point_estimate,
+                lower_bound: typical.confidence_interval.lower_bound,
+                upper_bound: typical.confidence_interval.upper_bound,
+                label: unit.format_value(typical.point_estimate),
+                change: match point.change_direction {
+                    Some(ChangeDirection::Improved) => "improved",
+                    Some(ChangeDirection::Regressed) => "regressed",
+                    Some(ChangeDirection::NoChange | ChangeDirection::NotSignificant) => {
+                        "no change"
+                    }
+                    None => "",
+                },
+                history_id: point.history_id.clone().unwrap_or_default(),
+                flamegraph: flamegraph(point.datetime)
+                    .map(|path| file_url(&path).map_err(|source| Error::Io { path, source }))
+                    .transpose()?
+                    .unwrap_or_default(),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let has_flamegraphs = points.iter().any(|point| !point.flamegraph.is_empty());
+    let mut rows = String::new();
+    for point in &points {
+        write!(
+            rows,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>",
+            escape_html(&point.datetime),
+            escape_html(&point.label),
+            escape_html(&format!(
+                "{} – {}",
+                unit.format_value(point.lower_bound),
+                unit.format_value(point.upper_bound)
+            )),
+            point.change,
+            escape_html(&point.history_id)
+        )
+        .expect("Writing to a String cannot fail");
+        if has_flamegraphs {
+            rows.push_str("<td>");
+            if !point.flamegraph.is_empty() {
+                write!(
+                    rows,
+                    "<a href=\"{}\">flamegraph</a>",
+                    escape_html(&point.flamegraph)
+                )
+                .expect("Writing to a String cannot fail");
+            }
+            rows.push_str("</td>");
+        }
+        rows.push_str("</tr>\n");
+    }
+    // The data is embedded in a script element, which must not be closed early
+    let data = serde_json::to_string(&points)
+        .expect("History points should always be serializable to JSON")
+        .replace("</", "<\\/");
+    let extra_headers = if has_flamegraphs {
+        "<th>Flamegraph</th>"
+    } else {
+        ""
+    };
+    let title = escape_html(&title);
+    let bench_path = escape_html(&benchmark.slash_path());
+    let mut values = vec![
+        ("title", title.as_str()),
+        ("path", bench_path.as_str()),
+        ("extra_headers", extra_headers),
+        ("rows", rows.as_str()),
+        ("data", data.as_str()),
+    ];
+    values.extend(theme.colors());
+    let html = fill_template(SINGLE_BENCH_TEMPLATE, &values);
+    std::fs::write(path, html).map_err(io_error)
+}
+
+/// Replace the `{name}` placeholders of a template with their values
+///
+/// The template is scanned once, so placeholders that appear within the
+/// substituted values are left alone. Braces that do not enclose the name of
+/// a placeholder, like those of CSS rules, are kept as-is.
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest[1..].split_once('}').and_then(|(name, _)| {
+            values
+                .iter()
+                .find(|(placeholder, _)| *placeholder == name)
+                .map(|(placeholder, value)| (placeholder.len(), value))
+        });
+        match value {
+            Some((name_len, value)) => {
+                output.push_str(value);
+                rest = &rest[name_len + 2..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Measurement of a [`single_bench_html()`] page
+#[derive(Serialize)]
+struct HtmlPoint {
+    /// Date and time of the measurement, in RFC 3339 format
+    datetime: String,
+
+    /// Typical estimate
+    value: f64,
+
+    /// Lower bound of the confidence interval of the typical estimate
+    lower_bound: f64,
+
+    /// Upper bound of the confidence interval of the typical estimate
+    upper_bound: f64,
+
+    /// Typical estimate, formatted in the benchmark's value unit
+    label: String,
+
+    /// Change with respect to the previous measurement, if known
+    change: &'static str,
+
+    /// User-provided identifier of the measurement, if any
+    history_id: String,
+
+    /// URL of the flamegraph of the measurement, if any
+    flamegraph: String,
+}
+
+/// `file://` URL of a local file
+fn file_url(path: &Path) -> io::Result<String> {
+    let path = std::path::absolute(path)?
+        .to_string_lossy()
+        .replace('\\', "/");
+    let mut url = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    });
+    for c in path.chars() {
+        match c {
+            '%' | ' ' | '#' | '?' => {
+                write!(url, "%{:02X}", c as u32).expect("Writing to a String cannot fail")
+            }
+            c => url.push(c),
+        }
+    }
+    Ok(url)
+}
+
+/// Escape text for inclusion in HTML
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Page template of [`single_bench_html()`]
+///
+/// Placeholders in braces are replaced with escaped content by
+/// [`fill_template()`].
+const SINGLE_BENCH_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: {foreground}; background: {background}; }
+a { color: {line}; }
+#chart { width: 100%; height: 320px; }
+#tooltip { position: absolute; display: none; pointer-events: none; background: {background};
+  border: 1px solid {foreground}; padding: 4px 8px; font-size: 0.9em; white-space: pre; }
+table { border-collapse: collapse; margin-top: 2em; }
+td, th { border: 1px solid {foreground}; padding: 2px 8px; text-align: left; }
+.band { fill: {band}; fill-opacity: 0.3; }
+.line { fill: none; stroke: {line}; stroke-width: 1.5; }
+.point { fill: {unchanged}; }
+.point.regressed { fill: {regressed}; }
+.point.improved { fill: {improved}; }
+.axis { font-size: 11px; fill: {foreground}; }
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>Data directory: <code>{path}</code></p>
+<svg id="chart"></svg>
+<div id="tooltip"></div>
+<table>
+<tr><th>Date</th><th>Typical</th><th>Confidence interval</th><th>Change</th><th>History ID</th>{extra_headers}</tr>
+{rows}</table>
+<script id="data" type="application/json">{data}</script>
+<script>
+"use strict";
+const points = JSON.parse(document.getElementById("data").textContent);
+const svg = document.getElementById("chart");
+const tooltip = document.getElementById("tooltip");
+const ns = "http://www.w3.org/2000/svg";
+function element(name, attributes, text) {
+  const node = document.createElementNS(ns, name);
+  for (const [key, value] of Object.entries(attributes)) node.setAttribute(key, value);
+  if (text !== undefined) node.textContent = text;
+  svg.appendChild(node);
+  return node;
+}
+function draw() {
+  svg.replaceChildren();
+  if (points.length === 0) return;
+  const width = svg.clientWidth, height = svg.clientHeight, margin = 50;
+  const times = points.map(p => Date.parse(p.datetime));
+  const tMin = Math.min(...times), tMax = Math.max(...times);
+  const vMin = Math.min(...points.map(p => p.lower_bound));
+  const vMax = Math.max(...points.map(p => p.upper_bound));
+  const x = t => margin + (tMax > tMin ? (t - tMin) / (tMax - tMin) : 0.5) * (width - 2 * margin);
+  const y = v => height - margin - (vMax > vMin ? (v - vMin) / (vMax - vMin) : 0.5) * (height - 2 * margin);
+  const upper = points.map((p, i) => `${x(times[i])},${y(p.upper_bound)}`);
+  const lower = points.map((p, i) => `${x(times[i])},${y(p.lower_bound)}`).reverse();
+  element("polygon", { class: "band", points: upper.concat(lower).join(" ") });
+  element("polyline", { class: "line", points: points.map((p, i) => `${x(times[i])},${y(p.value)}`).join(" ") });
+  element("text", { class: "axis", x: 4, y: y(vMax) + 4 }, format(vMax));
+  element("text", { class: "axis", x: 4, y: y(vMin) + 4 }, format(vMin));
+  element("text", { class: "axis", x: x(tMin), y: height - margin + 20 }, points[0].datetime.slice(0, 10));
+  element("text", { class: "axis", x: x(tMax) - 60, y: height - margin + 20 }, points[points.length - 1].datetime.slice(0, 10));
+  points.forEach((p, i) => {
+    const dot = element("circle", { class: `point ${p.change}`, cx: x(times[i]), cy: y(p.value), r: 4 });
+    dot.addEventListener("mouseenter", event => {
+      const flamegraph = p.flamegraph ? "click to open flamegraph" : "";
+      tooltip.textContent = [p.datetime, p.label, p.change, p.history_id, flamegraph].filter(Boolean).join("\n");
+      tooltip.style.left = `${event.pageX + 12}px`;
+      tooltip.style.top = `${event.pageY + 12}px`;
+      tooltip.style.display = "block";
+    });
+    dot.addEventListener("mouseleave", () => { tooltip.style.display = "none"; });
+    if (p.flamegraph) {
+      dot.style.cursor = "pointer";
+      dot.addEventListener("click", () => window.open(p.flamegraph));
+    }
+  });
+}
+function format(value) {
+  return value.toPrecision(4);
+}
+window.addEventListener("resize", draw);
+draw();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        snapshot::SnapshotEntry,
+        testing::{bench_id, measurement, TempTarget},
+    };
+
+    #[test]
+    fn top_changes_ranking() {
+        let change = |benchmark: &str, unit: ValueUnit, before: f64, after: f64, std_error| {
+            let entry = |mean| SnapshotEntry {
+                mean,
+                median: mean,
+                typical: None,
+                mean_standard_error: std_error,
+                std_dev: None,
+                unit: unit.clone(),
+            };
+            SnapshotChange {
+                benchmark: benchmark.to_owned(),
+                recorded: entry(before),
+                latest: entry(after),
+                statistic: Statistic::Typical,
+            }
+        };
+        let ns = ValueUnit::Nanoseconds;
+        let comparison = SnapshotComparison {
+            changed: vec![
+                change("small", ns.clone(), 10.0, 20.0, Some(1.0)),
+                change("bytes", ValueUnit::Bytes, 1000.0, 5000.0, Some(1.0)),
+                change("large", ns.clone(), 1000.0, 900.0, Some(100.0)),
+                change("broken", ns.clone(), 0.0, 0.0, Some(0.0)),
+                change("legacy", ns.clone(), 100.0, 130.0, None),
+            ],
+            ..SnapshotComparison::default()
+        };
+        let ranked = |rank_by, n| {
+            top_changes(&comparison, n, rank_by)
+                .into_iter()
+                .map(|change| &change.benchmark[..])
+                .collect::<Vec<_>>()
+        };
+        // NaN and unknown keys are ranked last, in their original order:
+        // "broken" has a NaN relative change and significance, "legacy" has no
+        // standard error, and "bytes" has no execution time
+        assert_eq!(
+            ranked(RankBy::AbsoluteTimeDelta, 5),
+            ["large", "legacy", "small", "broken", "bytes"]
+        );
+        assert_eq!(
+            ranked(RankBy::RelativeDelta, 5),
+            ["bytes", "small", "legacy", "large", "broken"]
+        );
+        assert_eq!(
+            ranked(RankBy::Significance, 5),
+            ["bytes", "small", "large", "broken", "legacy"]
+        );
+        assert_eq!(ranked(RankBy::RelativeDelta, 2), ["bytes", "small"]);
+        assert!(ranked(RankBy::RelativeDelta, 0).is_empty());
+    }
+
+    #[test]
+    fn leaderboard_ranks_changes_since_the_period_start() {
+        let target = TempTarget::new("leaderboard");
+        let write = |name: &str, measurements: &[(&str, f64)]| {
+            let measurements = measurements
+                .iter()
+                .map(|&(datetime, value)| measurement(datetime, &[value]))
+                .collect::<Vec<_>>();
+            target.write_benchmark(&bench_id(name), &measurements);
+        };
+        // The period starts on January 3rd, so the measurement of January 5th
+        // is not the baseline
+        write(
+            "parse",
+            &[
+                ("2026-01-01T00:00:00Z", 100.0),
+                ("2026-01-05T00:00:00Z", 90.0),
+                ("2026-01-10T00:00:00Z", 80.0),
+            ],
+        );
+        write(
+            "render",
+            &[
+                ("2026-01-02T00:00:00Z", 50.0),
+                ("2026-01-09T00:00:00Z", 60.0),
+            ],
+        );
+        write("fresh", &[("2026-01-08T00:00:00Z", 1000.0)]);
+        write(
+            "alloc",
+            &[
+                ("2026-01-01T00:00:00Z", 4096.0),
+                ("2026-01-10T00:00:00Z", 8192.0),
+            ],
+        );
+        let timed = target.search().collect_all().unwrap();
+        let timed = timed.filter(|bench| bench.path != "alloc");
+        let bytes = target.search().value_unit(ValueUnit::Bytes);
+        let bytes = bytes.under_prefix("alloc").collect_all().unwrap();
+        let dataset = timed.join(&bytes);
+        assert_eq!(dataset.len(), 4);
+
+        let board = leaderboard(&dataset, Statistic::Typical, TimeDelta::days(7), 10);
+        let ranked = |entries: &[LeaderboardEntry]| {
+            entries
+                .iter()
+                .map(|entry| (entry.benchmark.clone(), entry.change))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked(&board.slowest),
+            [
+                ("fresh".to_owned(), None),
+                ("parse".to_owned(), Some(80.0 / 100.0 - 1.0)),
+                ("render".to_owned(), Some(60.0 / 50.0 - 1.0)),
+            ]
+        );
+        assert_eq!(
+            ranked(&board.most_improved),
+            [("parse".to_owned(), Some(80.0 / 100.0 - 1.0))]
+        );
+        assert_eq!(
+            ranked(&board.most_regressed),
+            [
+                ("alloc".to_owned(), Some(1.0)),
+                ("render".to_owned(), Some(60.0 / 50.0 - 1.0)),
+            ]
+        );
+        assert_eq!(board.most_regressed[0].unit, ValueUnit::Bytes);
+
+        let top1 = leaderboard(&dataset, Statistic::Typical, TimeDelta::days(7), 1);
+        assert_eq!(top1.slowest.len(), 1);
+        assert_eq!(top1.most_regressed[0].benchmark, "alloc");
+    }
+
+    #[test]
+    fn sparkline_samples_are_a_deterministic_ordered_subset() {
+        let values = (0..100).map(f64::from).collect::<Vec<_>>();
+        let data = measurement("2026-01-01T00:00:00Z", &values);
+        let samples = sparkline_samples(&data, 10);
+        assert_eq!(samples.len(), 10);
+        assert!(samples.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(samples.iter().all(|sample| values.contains(sample)));
+        assert_ne!(samples, values[..10]);
+        assert_eq!(sparkline_samples(&data, 10), samples);
+
+        assert_eq!(sparkline_samples(&data, 100), values);
+        assert_eq!(sparkline_samples(&data, 1000), values);
+        assert!(sparkline_samples(&data, 0).is_empty());
+    }
+
+    #[test]
+    fn suite_cost_uses_its_unit() {
+        let cost = SuiteCost {
+            unit: ValueUnit::Nanoseconds,
+            benchmarks: [
+                ("parser/small".to_owned(), 2.5e9),
+                ("codec".to_owned(), 7.5e9),
+            ]
+            .into_iter()
+            .collect(),
+            groups: [("parser".to_owned(), 2.5e9), ("codec".to_owned(), 7.5e9)]
+                .into_iter()
+                .collect(),
+            total: 1.0e10,
+            not_timed: vec!["alloc".to_owned()],
+        };
+        assert_eq!(
+            cost.to_string(),
+            "Total measurement time: 10.000 s\n\
+             \n\
+             Groups:\n  \
+             codec: 7.5000 s (75.0%)\n  \
+             parser: 2.5000 s (25.0%)\n\
+             \n\
+             Benchmarks:\n  \
+             codec: 7.5000 s (75.0%)\n  \
+             parser/small: 2.5000 s (25.0%)\n\
+             \n\
+             Not timed: alloc\n"
+        );
+    }
+
+    #[test]
+    fn locale_rewrites_numbers() {
+        let english = Locale::default();
+        let european = Locale::european();
+        assert_eq!(english.localize("1234567.891 ns"), "1,234,567.891 ns");
+        assert_eq!(european.localize("1234567.891 ns"), "1.234.567,891 ns");
+        assert_eq!(
+            european.localize("-12.5% after 3 runs."),
+            "-12,5% after 3 runs."
+        );
+        assert_eq!(european.localize("v1.2.3"), "v1,2.3");
+        let no_grouping = Locale {
+            group_separator: None,
+            ..Locale::european()
+        };
+        assert_eq!(no_grouping.localize("1234.5"), "1234,5");
+        assert_eq!(
+            european.format_value(&ValueUnit::Nanoseconds, 1500.0),
+            "1,5000 µs"
+        );
+    }
+
+    #[test]
+    fn locale_formats_dates_in_utc() {
+        let datetime = "2026-03-04T05:06:07Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            Locale::default().format_datetime(datetime).unwrap(),
+            "2026-03-04 05:06:07 UTC"
+        );
+        assert_eq!(
+            Locale::european().format_datetime(datetime).unwrap(),
+            "04.03.2026 05:06:07 UTC"
+        );
+    }
+
+    #[test]
+    fn locale_rejects_invalid_datetime_formats() {
+        let datetime = "2026-03-04T05:06:07Z".parse::<DateTime<Utc>>().unwrap();
+        for format in ["%Y-%m-%d %Q", "%Y-%m-%d %"] {
+            let locale = Locale {
+                datetime_format: format.to_owned(),
+                ..Locale::default()
+            };
+            let error = locale.format_datetime(datetime).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn fill_template_substitutes_in_one_pass() {
+        let filled = fill_template(
+            "<h1>{title}</h1>{rows}<p>{data}</p>",
+            &[
+                ("title", "a {rows} b"),
+                ("rows", "<tr>{data}</tr>"),
+                ("data", "[]"),
+            ],
+        );
+        assert_eq!(filled, "<h1>a {rows} b</h1><tr>{data}</tr><p>[]</p>");
+    }
+
+    #[test]
+    fn fill_template_keeps_other_braces() {
+        let template = "body { margin: 0; } {unknown} ${p.x} {";
+        assert_eq!(fill_template(template, &[("x", "y")]), template);
+        assert_eq!(fill_template("{x}}", &[("x", "y")]), "y}");
+    }
+
+    #[test]
+    fn theme_rejects_colors_that_escape_the_style_sheet() {
+        assert_eq!(Theme::default().check(), Ok(()));
+        let theme: Theme = toml::from_str("regressed = \"rgb(204, 0, 0)\"").unwrap();
+        assert_eq!(theme.regressed, "rgb(204, 0, 0)");
+        assert_eq!(theme.line, Theme::default().line);
+        assert!(theme.check().is_ok());
+        for color in ["red; } body { display: none", "</style>", ""] {
+            let theme = Theme {
+                line: color.to_owned(),
+                ..Theme::default()
+            };
+            assert!(theme.check().is_err(), "{color:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn escape_html_escapes_markup() {
+        assert_eq!(
+            escape_html(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
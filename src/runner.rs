@@ -0,0 +1,202 @@
+//! Programmatic execution of `cargo criterion`
+//!
+//! This module runs cargo-criterion as a child process, follows its
+//! [message stream](crate::messages) while benchmarks run, and hands you the
+//! [`Benchmark`]s that were recorded once the run is over. Together with the
+//! rest of the library, this makes it possible to automate a full
+//! "run benchmarks, then analyze their data" workflow from Rust code.
+//!
+//! Runs can be restricted to a [`Selection`] of benchmarks, e.g. in order to
+//! only rerun the benchmarks that regressed.
+
+use crate::{
+    messages::{self, Message},
+    Benchmark, RawBenchmarkId, Search,
+};
+use std::{
+    ffi::OsStr,
+    io::{self, BufReader},
+    process::{Command, Stdio},
+};
+
+/// Run `cargo criterion` and return the benchmarks that it recorded
+///
+/// This is a shorthand for [`spawn_with()`] when you do not need to follow
+/// the run's progress.
+pub fn spawn(
+    cargo_args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+) -> io::Result<Vec<Benchmark>> {
+    spawn_with(cargo_args, |_| {})
+}
+
+/// Run `cargo criterion`, following its progress, and return the benchmarks
+/// that it recorded
+///
+/// `cargo criterion --message-format=json` is run in the current directory
+/// with the extra arguments `cargo_args`, and `on_message` is called on every
+/// message that it emits as soon as it is received. Lines of output that are
+/// not valid messages, such as those printed by benchmarks themselves, are
+/// ignored. cargo-criterion's stderr is left untouched, so that its progress
+/// remains visible to the user.
+///
+/// Once the run is over, the benchmarks that completed during the run are
+/// returned in completion order, and their latest measurement is the one from
+/// this run. Benchmark data is located using [`Search::discover()`], so if the
+/// run writes to a non-default `target` directory, the
+/// [`DATA_ENV_VAR`](crate::DATA_ENV_VAR) environment variable should point to
+/// it.
+///
+/// If `cargo criterion` cannot be started or fails, an error is returned.
+pub fn spawn_with(
+    cargo_args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    mut on_message: impl FnMut(&Message),
+) -> io::Result<Vec<Benchmark>> {
+    let mut child = Command::new("cargo")
+        .args(["criterion", "--message-format=json"])
+        .args(cargo_args)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .expect("Child stdout should have been captured");
+    let mut completed = Vec::new();
+    for message in messages::read_messages(BufReader::new(stdout)) {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => continue,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
+        on_message(&message);
+        completed.push(message);
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "cargo criterion failed ({status})"
+        )));
+    }
+    Ok(messages::correlate(completed, Search::discover()?)?
+        .into_iter()
+        .filter_map(|correlation| correlation.benchmark)
+        .collect())
+}
+
+/// Run `cargo criterion` on a selection of benchmarks, and return the
+/// benchmarks that it recorded
+///
+/// This works like [`spawn()`], with the selection's
+/// [filter arguments](Selection::cargo_args) appended to `cargo_args`, which
+/// must thus not contain a `--` separator. If the selection is empty,
+/// `cargo criterion` is not run at all, and no benchmark is returned.
+pub fn spawn_selected(
+    cargo_args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    selection: &Selection,
+) -> io::Result<Vec<Benchmark>> {
+    if selection.is_empty() {
+        return Ok(Vec::new());
+    }
+    let cargo_args = cargo_args
+        .into_iter()
+        .map(|arg| arg.as_ref().to_owned())
+        .chain(selection.cargo_args().into_iter().map(Into::into));
+    spawn(cargo_args)
+}
+
+/// Set of benchmarks that a `cargo criterion` run should be restricted to
+///
+/// Benchmarks are selected by their [full identifier](RawBenchmarkId::full_id),
+/// and a benchmark is selected if it matches any of the selection's criteria.
+/// The selection is translated into the regular expression that Criterion
+/// benchmark binaries accept as a filter.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Selection {
+    /// Anchored regular expressions matching the selected identifiers
+    patterns: Vec<String>,
+}
+//
+impl Selection {
+    /// Start with an empty selection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select benchmarks whose identifier matches a glob pattern
+    ///
+    /// Patterns use the same syntax as [budgets](crate::budgets), where `?`
+    /// matches any single character and `*` matches any sequence of
+    /// characters, including `/` separators. Note that they are matched
+    /// against the original benchmark identifiers, not the filesystem-safe
+    /// paths of benchmark data directories.
+    pub fn glob(mut self, pattern: &str) -> Self {
+        let mut regex = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => push_escaped(&mut regex, c),
+            }
+        }
+        regex.push('$');
+        self.patterns.push(regex);
+        self
+    }
+
+    /// Select all benchmarks from the benchmark group called `group`
+    ///
+    /// Like [`Dataset::filter_group()`](crate::dataset::Dataset::filter_group),
+    /// this also selects benchmarks that were recorded with
+    /// [`Criterion::bench_with_input()`](criterion::Criterion::bench_with_input)
+    /// and a function name equal to `group`.
+    pub fn group(mut self, group: &str) -> Self {
+        let mut regex = String::from("^");
+        group.chars().for_each(|c| push_escaped(&mut regex, c));
+        regex.push('/');
+        self.patterns.push(regex);
+        self
+    }
+
+    /// Select one specific benchmark
+    pub fn id(mut self, id: &RawBenchmarkId) -> Self {
+        let mut regex = String::from("^");
+        id.full_id()
+            .chars()
+            .for_each(|c| push_escaped(&mut regex, c));
+        regex.push('$');
+        self.patterns.push(regex);
+        self
+    }
+
+    /// Truth that no benchmark was selected
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Benchmark filter that implements this selection, if not empty
+    pub fn filter(&self) -> Option<String> {
+        (!self.is_empty()).then(|| self.patterns.join("|"))
+    }
+
+    /// Arguments to be appended to a `cargo criterion` command line in order
+    /// to only run the selected benchmarks
+    ///
+    /// This is empty if no benchmark was selected, in which case
+    /// `cargo criterion` will run all benchmarks.
+    pub fn cargo_args(&self) -> Vec<String> {
+        self.filter()
+            .map(|filter| vec!["--".to_owned(), filter])
+            .unwrap_or_default()
+    }
+}
+
+/// Append a character to a regular expression, escaping it if needed
+fn push_escaped(regex: &mut String, c: char) {
+    if "\\.+*?()|[]{}^$#&-~".contains(c) {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
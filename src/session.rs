@@ -0,0 +1,176 @@
+//! Configuration of cargo-criterion runs
+//!
+//! Measurement files do not record how they were taken, so a change of sample
+//! size or warm-up time can easily be mistaken for a performance change when
+//! looking at a benchmark's history. To address this, this library can record
+//! a [`RunConfig`] sidecar for every cargo-criterion run (or "session") with
+//! [`writer::write_run_config()`](crate::writer::write_run_config), and read
+//! it back later on via [`Session::config()`].
+//!
+//! Sidecars are optional: cargo-criterion does not write them, and sessions
+//! without one simply have no known configuration.
+
+use crate::{decode, layout};
+use chrono::{DateTime, TimeDelta, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Settings that a cargo-criterion run was started with
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RunConfig {
+    /// Command-line arguments passed to `cargo criterion`
+    pub cargo_criterion_args: Vec<String>,
+
+    /// Number of samples per benchmark, if it was overridden
+    pub sample_size: Option<u64>,
+
+    /// Warm-up time per benchmark, if it was overridden
+    pub warm_up_time: Option<Duration>,
+
+    /// Measurement time per benchmark, if it was overridden
+    pub measurement_time: Option<Duration>,
+}
+
+/// One cargo-criterion run, as recorded in the session sidecar directory
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    /// Sidecar directory of this session
+    dir: PathBuf,
+
+    /// Date and time at which the session started
+    start: DateTime<Utc>,
+}
+//
+impl Session {
+    /// Describe a session from its start time
+    ///
+    /// Session directory names only have a precision of one second, so the
+    /// start time is truncated accordingly.
+    pub(crate) fn new(target_dir: &Path, start: DateTime<Utc>) -> Self {
+        let start = start
+            .with_nanosecond(0)
+            .expect("Zero nanoseconds is always valid");
+        let dir = layout::sessions_dir(target_dir).join(layout::session_dir_name(&start));
+        Self { dir, start }
+    }
+
+    /// Date and time at which the session started
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    /// Sidecar directory of this session
+    pub fn dir_path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Run configuration of this session, if it was recorded
+    pub fn config(&self) -> io::Result<Option<RunConfig>> {
+        match decode::read_cbor(&self.dir.join(layout::RUN_CONFIG_FILE_NAME)) {
+            Ok(config) => Ok(Some(config)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// List the recorded sessions of a `target` directory, from oldest to newest
+///
+/// Entries of the session sidecar directory that do not follow the session
+/// naming convention are ignored. If no session was ever recorded, an empty
+/// list is returned.
+pub fn sessions(target_dir: impl AsRef<Path>) -> io::Result<Vec<Session>> {
+    let target_dir = target_dir.as_ref();
+    let entries = match std::fs::read_dir(layout::sessions_dir(target_dir)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut sessions = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let start = entry
+            .file_name()
+            .to_str()
+            .and_then(layout::parse_session_dir_name);
+        if let (Some(start), true) = (start, entry.file_type()?.is_dir()) {
+            sessions.push(Session::new(target_dir, start));
+        }
+    }
+    sessions.sort_by_key(|session| session.start);
+    Ok(sessions)
+}
+
+/// Longest time that a session is assumed to last
+///
+/// Sidecars only record when a session started, not when it ended, so
+/// measurements taken after the end of the latest recorded session would
+/// otherwise be attributed to it no matter how much later they were taken.
+pub const MAX_SESSION_DURATION: TimeDelta = TimeDelta::hours(24);
+
+/// Find the session during which a measurement was taken
+///
+/// This is the latest session that started no later than `datetime`, if it
+/// started less than [`MAX_SESSION_DURATION`] before `datetime`. Measurements
+/// taken after that were taken by a run that was not recorded, and belong to
+/// no session. `sessions` must be sorted from oldest to newest, as returned by
+/// [`sessions()`].
+pub fn session_of(sessions: &[Session], datetime: DateTime<Utc>) -> Option<&Session> {
+    let idx = sessions.partition_point(|session| session.start <= datetime);
+    let session = &sessions[idx.checked_sub(1)?];
+    (datetime - session.start < MAX_SESSION_DURATION).then_some(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(start: &str) -> Session {
+        Session::new(Path::new("target"), start.parse().unwrap())
+    }
+
+    fn start_of(sessions: &[Session], datetime: &str) -> Option<DateTime<Utc>> {
+        session_of(sessions, datetime.parse().unwrap()).map(Session::start)
+    }
+
+    #[test]
+    fn session_of_picks_latest_started_session() {
+        let sessions = [
+            session("2026-01-01T00:00:00Z"),
+            session("2026-01-01T06:00:00Z"),
+        ];
+        assert_eq!(start_of(&sessions, "2025-12-31T23:59:59Z"), None);
+        assert_eq!(
+            start_of(&sessions, "2026-01-01T00:00:00Z"),
+            Some(sessions[0].start())
+        );
+        assert_eq!(
+            start_of(&sessions, "2026-01-01T05:59:59Z"),
+            Some(sessions[0].start())
+        );
+        assert_eq!(
+            start_of(&sessions, "2026-01-01T12:00:00Z"),
+            Some(sessions[1].start())
+        );
+    }
+
+    #[test]
+    fn session_of_bounds_session_duration() {
+        let sessions = [
+            session("2026-01-01T00:00:00Z"),
+            session("2026-01-05T00:00:00Z"),
+        ];
+        assert_eq!(
+            start_of(&sessions, "2026-01-01T23:59:59Z"),
+            Some(sessions[0].start())
+        );
+        assert_eq!(start_of(&sessions, "2026-01-02T00:00:00Z"), None);
+        assert_eq!(start_of(&sessions, "2026-01-04T00:00:00Z"), None);
+        assert_eq!(start_of(&sessions, "2026-01-07T00:00:00Z"), None);
+    }
+}
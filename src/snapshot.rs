@@ -0,0 +1,560 @@
+//! Snapshot testing of benchmark results
+//!
+//! This module lets you record a compact snapshot of the latest estimates of
+//! every benchmark into a version-controlled file, and later compare the latest
+//! benchmark data against it. This enables workflows similar to that of
+//! snapshot testing libraries like `insta`, where performance changes are
+//! detected by tests and accepted by re-recording the snapshot:
+//!
+//! ```no_run
+//! # use criterion_cbor::{snapshot, Search};
+//! let root = env!("CARGO_MANIFEST_DIR");
+//! let comparison = snapshot::compare(Search::in_cargo_root(root), "perf-snapshot.toml", 0.05)
+//!     .expect("Failed to compare with the performance snapshot");
+//! assert!(comparison.is_match(), "Performance changed:\n{comparison}");
+//! ```
+//!
+//! Beyond individual changes, [`Snapshot::geomean_change()`] summarizes how a
+//! whole benchmark suite changed, optionally giving more [`Weights`] to the
+//! benchmarks that matter most.
+//!
+//! Like Criterion's own reports, comparisons are based on the
+//! [`Statistic::Typical`] estimate by default. The `_by` variants of the
+//! comparison methods let you pick another [`Statistic`].
+
+use crate::{
+    dataset::Dataset, stats::EffectSize, units::ValueUnit, Error, Estimates, Search, Statistic,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    io,
+    path::Path,
+};
+
+/// Record a snapshot of the latest benchmark estimates into a file
+///
+/// Any pre-existing snapshot at this location will be overwritten.
+pub fn record(search: Search, path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    Snapshot::take(search)?
+        .save(path)
+        .map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+/// Compare the latest benchmark estimates with a snapshot file
+///
+/// Estimates are considered to have changed if they differ from the snapshot
+/// by more than the relative `tolerance` (e.g. 0.05 for 5%). Benchmarks with
+/// non-finite estimates cannot be compared, and are reported separately.
+pub fn compare(
+    search: Search,
+    path: impl AsRef<Path>,
+    tolerance: f64,
+) -> Result<SnapshotComparison, Error> {
+    let path = path.as_ref();
+    let recorded = Snapshot::load(path).map_err(|e| Error::reading(path, e))?;
+    let latest = Snapshot::take(search)?;
+    Ok(recorded.compare(&latest, tolerance))
+}
+
+/// Snapshot of the latest estimates of a set of benchmarks
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Latest estimates, indexed by `/`-separated benchmark path
+    #[serde(default)]
+    pub benchmarks: BTreeMap<String, SnapshotEntry>,
+}
+//
+impl Snapshot {
+    /// Take a snapshot of the latest estimates of all benchmarks
+    pub fn take(search: Search) -> Result<Self, Error> {
+        let mut benchmarks = BTreeMap::new();
+        for bench in search.find_all() {
+            let bench = bench?;
+            let data = bench.latest_measurement().data()?;
+            let entry = SnapshotEntry::new(&data.estimates, bench.value_unit());
+            benchmarks.insert(bench.slash_path(), entry);
+        }
+        Ok(Self { benchmarks })
+    }
+
+    /// Take a snapshot of the latest estimates of all benchmarks in a dataset
+    ///
+    /// Benchmarks without any measurement are ignored.
+    pub fn of_dataset(dataset: &Dataset) -> Self {
+        let benchmarks = dataset
+            .benchmarks()
+            .filter_map(|bench| {
+                let data = bench.latest()?;
+                let entry = SnapshotEntry::new(&data.estimates, &bench.value_unit);
+                Some((bench.path.clone(), entry))
+            })
+            .collect();
+        Self { benchmarks }
+    }
+
+    /// Load a snapshot from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save this snapshot to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text =
+            toml::to_string_pretty(self).expect("Snapshots should always be serializable to TOML");
+        std::fs::write(path, text)
+    }
+
+    /// Compare this snapshot with a newer one
+    ///
+    /// See [`compare()`] for the meaning of `tolerance`. Changes are measured
+    /// on the [`Statistic::Typical`] estimate and on the median.
+    pub fn compare(&self, latest: &Snapshot, tolerance: f64) -> SnapshotComparison {
+        self.compare_by(latest, tolerance, Statistic::Typical)
+    }
+
+    /// Compare this snapshot with a newer one, measuring changes on a certain
+    /// statistic and on the median
+    pub fn compare_by(
+        &self,
+        latest: &Snapshot,
+        tolerance: f64,
+        statistic: Statistic,
+    ) -> SnapshotComparison {
+        let mut comparison = SnapshotComparison::default();
+        for (benchmark, recorded) in &self.benchmarks {
+            let Some(latest) = latest.benchmarks.get(benchmark) else {
+                comparison.removed.push(benchmark.clone());
+                continue;
+            };
+            if !recorded.is_finite() || !latest.is_finite() {
+                comparison.non_finite.push(benchmark.clone());
+                continue;
+            }
+            let change = SnapshotChange {
+                benchmark: benchmark.clone(),
+                recorded: recorded.clone(),
+                latest: latest.clone(),
+                statistic,
+            };
+            if change.change().abs() > tolerance || change.median_change().abs() > tolerance {
+                comparison.changed.push(change);
+            }
+        }
+        comparison.added = latest
+            .benchmarks
+            .keys()
+            .filter(|benchmark| !self.benchmarks.contains_key(*benchmark))
+            .cloned()
+            .collect();
+        comparison
+    }
+
+    /// Weighted geometric mean of the relative change of the typical estimate
+    /// across all benchmarks, e.g. 0.05 for +5%
+    ///
+    /// This is the conventional headline number for the performance change
+    /// of a benchmark suite. Only benchmarks that appear in both snapshots and
+    /// have finite, positive estimates are taken into account. Returns `None`
+    /// if there is no such benchmark, or if their total weight is zero.
+    pub fn geomean_change(&self, latest: &Snapshot, weights: &Weights) -> Option<f64> {
+        self.geomean_change_by(latest, weights, Statistic::Typical)
+    }
+
+    /// Weighted geometric mean of the relative change of a certain statistic
+    /// across all benchmarks
+    ///
+    /// See [`geomean_change()`](Self::geomean_change) for details.
+    pub fn geomean_change_by(
+        &self,
+        latest: &Snapshot,
+        weights: &Weights,
+        statistic: Statistic,
+    ) -> Option<f64> {
+        let (sum_log, sum_weights) = self
+            .benchmarks
+            .iter()
+            .filter_map(|(benchmark, recorded)| {
+                let latest = latest.benchmarks.get(benchmark)?;
+                let ratio = latest.value(statistic) / recorded.value(statistic);
+                (ratio.is_finite() && ratio > 0.0).then(|| (weights.of(benchmark), ratio.ln()))
+            })
+            .fold((0.0, 0.0), |(sum_log, sum_weights), (weight, log)| {
+                (sum_log + weight * log, sum_weights + weight)
+            });
+        (sum_weights > 0.0).then(|| (sum_log / sum_weights).exp() - 1.0)
+    }
+}
+
+/// Relative importance of benchmarks in suite-wide summaries
+///
+/// Benchmarks get the weight specified for their `/`-separated path in
+/// `benchmarks` if any, otherwise the weight specified for their group (the
+/// first component of their path) in `groups` if any, otherwise a weight of 1.
+/// A weight of 0 excludes a benchmark from summaries.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Weights {
+    /// Weights of individual benchmarks, indexed by benchmark path
+    #[serde(default)]
+    pub benchmarks: BTreeMap<String, f64>,
+
+    /// Weights of benchmark groups, indexed by group name
+    #[serde(default)]
+    pub groups: BTreeMap<String, f64>,
+}
+//
+impl Weights {
+    /// Weight of the benchmark with a certain `/`-separated path
+    pub fn of(&self, benchmark: &str) -> f64 {
+        let group = benchmark.split('/').next().unwrap_or(benchmark);
+        self.benchmarks
+            .get(benchmark)
+            .or_else(|| self.groups.get(group))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// Estimates of one benchmark within a [`Snapshot`]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SnapshotEntry {
+    /// Point estimate of the mean
+    pub mean: f64,
+
+    /// Point estimate of the median
+    pub median: f64,
+
+    /// Point estimate of the [typical](Estimates::typical) value, if known
+    ///
+    /// Snapshots recorded by older versions of this library do not have it,
+    /// in which case the mean is used instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typical: Option<f64>,
+
+    /// Standard error of the mean, if known
+    ///
+    /// Snapshots recorded by older versions of this library do not have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_standard_error: Option<f64>,
+
+    /// Point estimate of the standard deviation of per-iteration values, if
+    /// known
+    ///
+    /// Snapshots recorded by older versions of this library do not have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub std_dev: Option<f64>,
+
+    /// Unit of the estimates
+    ///
+    /// This is only recorded for benchmarks whose values are not durations in
+    /// nanoseconds, which is what snapshots recorded by older versions of this
+    /// library contain.
+    #[serde(default, skip_serializing_if = "is_default_unit")]
+    pub unit: ValueUnit,
+}
+//
+impl SnapshotEntry {
+    /// Extract the snapshotted estimates from a measurement's estimates
+    fn new(estimates: &Estimates, unit: &ValueUnit) -> Self {
+        Self {
+            mean: estimates.mean.point_estimate,
+            median: estimates.median.point_estimate,
+            typical: Some(estimates.typical().point_estimate),
+            mean_standard_error: Some(estimates.mean.standard_error),
+            std_dev: Some(estimates.std_dev.point_estimate),
+            unit: unit.clone(),
+        }
+    }
+
+    /// Point estimate of a certain statistic
+    pub fn value(&self, statistic: Statistic) -> f64 {
+        match statistic {
+            Statistic::Typical => self.typical.unwrap_or(self.mean),
+            Statistic::Mean => self.mean,
+            Statistic::Median => self.median,
+        }
+    }
+
+    /// Truth that all point estimates are finite
+    pub fn is_finite(&self) -> bool {
+        self.mean.is_finite() && self.median.is_finite() && self.typical.is_none_or(f64::is_finite)
+    }
+}
+
+/// Differences between a recorded snapshot and the latest benchmark data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapshotComparison {
+    /// Benchmarks whose estimates changed beyond the tolerance
+    pub changed: Vec<SnapshotChange>,
+
+    /// Benchmarks that are present in the latest data but not in the snapshot
+    pub added: Vec<String>,
+
+    /// Benchmarks that are present in the snapshot but not in the latest data
+    pub removed: Vec<String>,
+
+    /// Benchmarks that could not be compared because either the snapshot or
+    /// the latest data contains non-finite estimates for them
+    pub non_finite: Vec<String>,
+}
+//
+impl SnapshotComparison {
+    /// Truth that the latest data matches the snapshot
+    pub fn is_match(&self) -> bool {
+        self.changed.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.non_finite.is_empty()
+    }
+}
+//
+impl Display for SnapshotComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for change in &self.changed {
+            writeln!(f, "changed: {change}")?;
+        }
+        for benchmark in &self.added {
+            writeln!(f, "added: {benchmark}")?;
+        }
+        for benchmark in &self.removed {
+            writeln!(f, "removed: {benchmark}")?;
+        }
+        for benchmark in &self.non_finite {
+            writeln!(f, "non-finite: {benchmark}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark whose estimates changed with respect to a snapshot
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotChange {
+    /// `/`-separated path of the benchmark from the Criterion data root
+    pub benchmark: String,
+
+    /// Estimates from the snapshot
+    pub recorded: SnapshotEntry,
+
+    /// Latest estimates
+    pub latest: SnapshotEntry,
+
+    /// Statistic that the change is measured on
+    pub statistic: Statistic,
+}
+//
+impl SnapshotChange {
+    /// Relative change of the compared statistic (e.g. 0.05 for +5%)
+    pub fn change(&self) -> f64 {
+        self.latest.value(self.statistic) / self.recorded.value(self.statistic) - 1.0
+    }
+
+    /// Absolute change of the compared statistic, in the benchmark's value
+    /// unit
+    pub fn delta(&self) -> f64 {
+        self.latest.value(self.statistic) - self.recorded.value(self.statistic)
+    }
+
+    /// Relative change of the mean (e.g. 0.05 for +5%)
+    pub fn mean_change(&self) -> f64 {
+        self.latest.mean / self.recorded.mean - 1.0
+    }
+
+    /// Relative change of the median (e.g. 0.05 for +5%)
+    pub fn median_change(&self) -> f64 {
+        self.latest.median / self.recorded.median - 1.0
+    }
+
+    /// Absolute change of the mean, in the benchmark's value unit
+    pub fn mean_delta(&self) -> f64 {
+        self.latest.mean - self.recorded.mean
+    }
+
+    /// Change of the mean in units of its standard error (z-score), if the
+    /// standard errors of both estimates are known
+    ///
+    /// Values above 2 in magnitude are unlikely to result from noise alone.
+    pub fn mean_significance(&self) -> Option<f64> {
+        let recorded = self.recorded.mean_standard_error?;
+        let latest = self.latest.mean_standard_error?;
+        Some(self.mean_delta() / recorded.hypot(latest))
+    }
+
+    /// Change of the mean in units of the standard deviation of per-iteration
+    /// values (Cohen's d), if the standard deviations of both estimates are
+    /// known
+    ///
+    /// Unlike [`mean_significance()`](Self::mean_significance), which tells
+    /// whether a change is detectable, this tells whether it is big. See
+    /// [`EffectSize`] for a conventional interpretation.
+    pub fn cohens_d(&self) -> Option<f64> {
+        let recorded = self.recorded.std_dev?;
+        let latest = self.latest.std_dev?;
+        Some(self.mean_delta() / ((recorded.powi(2) + latest.powi(2)) / 2.0).sqrt())
+    }
+}
+//
+impl Display for SnapshotChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} {:+.2}%",
+            self.benchmark,
+            self.statistic,
+            self.change() * 100.0
+        )?;
+        if self.statistic != Statistic::Median {
+            write!(f, ", median {:+.2}%", self.median_change() * 100.0)?;
+        }
+        if let Some(cohens_d) = self.cohens_d() {
+            write!(
+                f,
+                ", d = {cohens_d:+.2}, {} effect",
+                EffectSize::of_cohens_d(cohens_d)
+            )?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Truth that a unit is the default one, which need not be serialized
+fn is_default_unit(unit: &ValueUnit) -> bool {
+    *unit == ValueUnit::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_change_effect_size() {
+        let entry = |mean: f64, standard_error, std_dev| SnapshotEntry {
+            mean,
+            median: 10.0,
+            typical: None,
+            mean_standard_error: Some(standard_error),
+            std_dev: Some(std_dev),
+            unit: ValueUnit::Nanoseconds,
+        };
+        let change = SnapshotChange {
+            benchmark: "parser".to_owned(),
+            recorded: entry(10.0, 0.3, 3.0),
+            latest: entry(14.0, 0.4, 4.0),
+            statistic: Statistic::Mean,
+        };
+        assert_eq!(change.mean_delta(), 4.0);
+        assert_eq!(change.mean_significance(), Some(8.0));
+        let cohens_d = change.cohens_d().unwrap();
+        assert!((cohens_d - 4.0 / 12.5f64.sqrt()).abs() < 1e-12);
+        assert_eq!(
+            change.to_string(),
+            "parser (mean +40.00%, median +0.00%, d = +1.13, large effect)"
+        );
+
+        let unknown = SnapshotChange {
+            latest: SnapshotEntry {
+                std_dev: None,
+                ..change.latest
+            },
+            ..change
+        };
+        assert_eq!(unknown.cohens_d(), None);
+        assert_eq!(unknown.to_string(), "parser (mean +40.00%, median +0.00%)");
+    }
+
+    #[test]
+    fn snapshot_units_round_trip() {
+        let entry = |unit| SnapshotEntry {
+            mean: 10.0,
+            median: 10.0,
+            typical: None,
+            mean_standard_error: None,
+            std_dev: None,
+            unit,
+        };
+        let snapshot = Snapshot {
+            benchmarks: [
+                (
+                    "alloc".to_owned(),
+                    entry(ValueUnit::Custom("cycles".to_owned())),
+                ),
+                ("parser".to_owned(), entry(ValueUnit::Nanoseconds)),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let text = toml::to_string_pretty(&snapshot).unwrap();
+        assert_eq!(text.matches("unit").count(), 1);
+        assert_eq!(toml::from_str::<Snapshot>(&text).unwrap(), snapshot);
+        let legacy = "[benchmarks.parser]\nmean = 10.0\nmedian = 10.0\n";
+        let legacy = toml::from_str::<Snapshot>(legacy).unwrap();
+        assert_eq!(legacy.benchmarks["parser"].unit, ValueUnit::Nanoseconds);
+        assert_eq!(legacy.benchmarks["parser"].typical, None);
+    }
+
+    #[test]
+    fn snapshot_compares_typical_estimate() {
+        let entry = |mean, typical| SnapshotEntry {
+            mean,
+            median: 10.0,
+            typical,
+            mean_standard_error: None,
+            std_dev: None,
+            unit: ValueUnit::Nanoseconds,
+        };
+        let snapshot = |entry| Snapshot {
+            benchmarks: [("parser".to_owned(), entry)].into_iter().collect(),
+        };
+
+        // The slope changed while the mean did not
+        let recorded = snapshot(entry(10.0, Some(8.0)));
+        let latest = snapshot(entry(10.0, Some(12.0)));
+        let comparison = recorded.compare(&latest, 0.05);
+        assert_eq!(comparison.changed.len(), 1);
+        let change = &comparison.changed[0];
+        assert_eq!(change.statistic, Statistic::Typical);
+        assert_eq!(change.change(), 0.5);
+        assert_eq!(change.delta(), 4.0);
+        assert_eq!(
+            change.to_string(),
+            "parser (typical +50.00%, median +0.00%)"
+        );
+        assert!(recorded
+            .compare_by(&latest, 0.05, Statistic::Mean)
+            .is_match());
+        let weights = Weights::default();
+        assert_eq!(recorded.geomean_change(&latest, &weights), Some(0.5));
+        let mean_change = recorded.geomean_change_by(&latest, &weights, Statistic::Mean);
+        assert_eq!(mean_change, Some(0.0));
+
+        // Snapshots without a typical estimate fall back to the mean
+        let legacy = snapshot(entry(10.0, None));
+        let latest = snapshot(entry(11.0, Some(11.0)));
+        let change = &legacy.compare(&latest, 0.05).changed[0];
+        assert!((change.change() - 0.1).abs() < 1e-12);
+
+        // Non-finite typical estimates cannot be compared
+        let latest = snapshot(entry(10.0, Some(f64::NAN)));
+        let comparison = recorded.compare(&latest, 0.05);
+        assert_eq!(comparison.non_finite, ["parser"]);
+    }
+
+    #[test]
+    fn compare_reports_snapshot_file_errors() {
+        let target = crate::testing::TempTarget::new("snapshot-compare");
+        let path = target.path.join("perf-snapshot.toml");
+        let error = compare(target.search(), &path, 0.05).unwrap_err();
+        assert!(matches!(error, Error::Io { .. }));
+        assert_eq!(error.path(), Some(path.as_path()));
+
+        std::fs::write(&path, "benchmarks = 42").unwrap();
+        let error = compare(target.search(), &path, 0.05).unwrap_err();
+        assert!(error.is_decode_error());
+        assert_eq!(error.path(), Some(path.as_path()));
+    }
+}
@@ -0,0 +1,2145 @@
+//! Recomputation of statistical estimates from raw samples
+//!
+//! `cargo criterion` stores the [`Estimates`] that it computed at the time
+//! where a benchmark was run, along with the raw samples that they were derived
+//! from. This module lets you derive new estimates from these raw samples,
+//! using the same bootstrap methodology as Criterion, for example in order to
+//! exclude samples that are known to be contaminated by warmup effects, or in
+//! order to reinterpret them at another confidence level.
+//!
+//! Samples with a non-finite iteration count or value, which can be produced
+//! by benchmarks that crashed mid-run, are excluded from all computations.
+//! Use [`validation::measurement_issues()`](crate::validation::measurement_issues)
+//! to find out whether a measurement contains such samples.
+//!
+//! The [`linearity()`] diagnostic tells whether a measurement satisfies the
+//! assumption of a constant per-iteration value, which underlies Criterion's
+//! slope estimate. [`recommend_sample_size()`] uses the noise observed in a
+//! benchmark's history to tell how many samples are needed to reliably detect
+//! changes of a certain magnitude. Finally, [`estimated_total_time()`] turns
+//! per-benchmark changes into the time impact on a real-world workload.
+//!
+//! Criterion's own change detection assumes that both runs were recorded with
+//! the same settings. [`compare_measurements()`] re-tests the change between
+//! two measurements in a way that remains valid when their sample sizes or
+//! confidence levels differ, and tells when they do. [`compare_with_baseline()`]
+//! applies it to the latest measurement of a benchmark and a baseline chosen
+//! by a [`BaselineStrategy`]. Both can compare medians instead of means, in
+//! [`RobustnessMode::Robust`] mode, which is barely affected by occasional
+//! outliers. [`compare_distributions()`] goes beyond means,
+//! and reveals changes that only affect the tail of the distribution of
+//! per-iteration values. All comparisons report standardized effect sizes,
+//! which tell whether a change is big rather than merely detectable.
+//!
+//! As an alternative to significance tests, [`compare_bayesian()`] computes
+//! the posterior distribution of the ratio of the mean per-iteration values of
+//! two measurements, given a [`RatioPrior`]. It answers questions like "how
+//! likely is it that this benchmark got more than 2% slower?", which map more
+//! directly onto gating decisions than p-values.
+
+use crate::{
+    history::{BaselineStrategy, History, HistoryPoint, RobustnessMode},
+    snapshot::SnapshotComparison,
+    units::ValueUnit,
+    Benchmark, ConfidenceInterval, Error, Estimate, Estimates, MeasurementData,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// Number of bootstrap resamples used when recomputing estimates
+///
+/// This is the same as Criterion's default `nresamples` setting.
+pub const DEFAULT_RESAMPLES: usize = 100_000;
+
+/// Selection of raw samples to be excluded before recomputing estimates
+///
+/// The default configuration keeps all samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrimOptions {
+    /// Number of samples to drop at the beginning of the run
+    ///
+    /// Criterion collects samples in a sequential fashion, so the first samples
+    /// are the ones that are most likely to be affected by warmup effects
+    /// (cold caches, CPU frequency scaling...) that the warmup phase did not
+    /// fully eliminate.
+    pub skip_first: usize,
+
+    /// Fraction of the remaining samples with the lowest per-iteration value
+    /// that should be dropped, in range `0.0..0.5`
+    pub low_tail: f64,
+
+    /// Fraction of the remaining samples with the highest per-iteration value
+    /// that should be dropped, in range `0.0..0.5`
+    pub high_tail: f64,
+}
+//
+impl TrimOptions {
+    /// Truth that the tail fractions are in their expected range
+    pub fn is_valid(&self) -> bool {
+        (0.0..0.5).contains(&self.low_tail) && (0.0..0.5).contains(&self.high_tail)
+    }
+
+    /// Extract the (iterations, value) pairs that survive trimming
+    ///
+    /// Samples with a non-finite iteration count or value are always dropped,
+    /// after the first `skip_first` samples have been skipped. The options
+    /// must be [valid](Self::is_valid).
+    fn apply(&self, data: &MeasurementData) -> Vec<(f64, f64)> {
+        debug_assert!(self.is_valid(), "Trim options should have been validated");
+        let mut samples = data
+            .iterations
+            .iter()
+            .copied()
+            .zip(data.values.iter().copied())
+            .skip(self.skip_first)
+            .filter(|(iterations, value)| iterations.is_finite() && value.is_finite())
+            .collect::<Vec<_>>();
+        // Statistics do not depend on sample order, so we can just sort samples
+        // by per-iteration value and drop both ends of the sorted list.
+        let num_samples = samples.len();
+        let num_low = (num_samples as f64 * self.low_tail).floor() as usize;
+        let num_high = (num_samples as f64 * self.high_tail).floor() as usize;
+        samples.sort_unstable_by(|&x, &y| avg_value(x).total_cmp(&avg_value(y)));
+        samples.truncate(num_samples - num_high);
+        samples.drain(..num_low);
+        samples
+    }
+}
+
+/// Recompute a measurement's estimates after trimming some of its samples
+///
+/// Estimates are recomputed using the same confidence level as the original
+/// measurement, and a slope estimate is only computed if the original
+/// measurement had one (i.e. if Criterion used linear sampling).
+///
+/// Returns `None` if the trimming options are not
+/// [valid](TrimOptions::is_valid), or if fewer than two samples remain after
+/// trimming, as no meaningful statistics can be computed in this case.
+pub fn recompute_estimates(data: &MeasurementData, trim: &TrimOptions) -> Option<Estimates> {
+    if !trim.is_valid() {
+        return None;
+    }
+    let samples = trim.apply(data);
+    bootstrap(
+        &[samples],
+        data.estimates.slope.is_some(),
+        data.estimates.mean.confidence_interval.confidence_level,
+        DEFAULT_RESAMPLES,
+    )
+}
+
+/// Recompute a measurement's estimates at another confidence level
+///
+/// Criterion computes confidence intervals at a 95% confidence level by
+/// default. This bootstraps new estimates from the measurement's raw samples,
+/// so that measurements recorded at different confidence levels can be
+/// reinterpreted at a common one. A slope estimate is only computed if the
+/// original measurement had one.
+///
+/// Returns `None` if `confidence_level` is not in range `0.0..1.0` or if the
+/// measurement has fewer than two usable samples.
+pub fn releveled_estimates(data: &MeasurementData, confidence_level: f64) -> Option<Estimates> {
+    if !(confidence_level > 0.0 && confidence_level < 1.0) {
+        return None;
+    }
+    bootstrap(
+        &[TrimOptions::default().apply(data)],
+        data.estimates.slope.is_some(),
+        confidence_level,
+        DEFAULT_RESAMPLES,
+    )
+}
+
+/// Compute estimates from the pooled samples of several runs
+///
+/// This is useful for very short benchmarks, whose individual runs are too
+/// noisy to provide stable estimates. Runs are not simply concatenated, as that
+/// would neglect run-to-run variability and lead to overconfident estimates.
+/// Instead, a two-level bootstrap is performed, where runs are resampled first,
+/// then samples are resampled within each selected run.
+///
+/// Estimates are computed using the confidence level of the first run, and a
+/// slope estimate is only computed if all runs have one.
+///
+/// Returns `None` if no runs are provided, if one of the runs has no samples,
+/// or if there are fewer than two samples overall.
+pub fn pool_runs(runs: &[MeasurementData]) -> Option<Estimates> {
+    let first = runs.first()?;
+    let samples = runs
+        .iter()
+        .map(|run| TrimOptions::default().apply(run))
+        .collect::<Vec<_>>();
+    bootstrap(
+        &samples,
+        runs.iter().all(|run| run.estimates.slope.is_some()),
+        first.estimates.mean.confidence_interval.confidence_level,
+        DEFAULT_RESAMPLES,
+    )
+}
+
+/// Minimal coefficient of determination of a linear measurement
+pub const MIN_LINEAR_R_SQUARED: f64 = 0.95;
+
+/// Maximal magnitude of the per-iteration trend of a linear measurement
+pub const MAX_LINEAR_TREND: f64 = 0.5;
+
+/// Minimal residual runs test statistic of a linear measurement
+pub const MIN_LINEAR_RUNS_Z: f64 = -3.0;
+
+/// Diagnostics of the linear relationship between iteration counts and
+/// measured values within a measurement
+///
+/// Criterion's slope estimate assumes that each sample's value is proportional
+/// to its iteration count, i.e. that every iteration takes the same time. This
+/// assumption is broken by effects like caches warming up or allocator pools
+/// growing over the course of a sample, in which case the slope estimate is
+/// not meaningful.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Linearity {
+    /// Coefficient of determination of Criterion's linear model
+    ///
+    /// This is computed like Criterion does, with respect to a regression line
+    /// that goes through the origin. Values close to 1 indicate a good fit.
+    pub r_squared: f64,
+
+    /// Correlation between iteration counts and per-iteration values
+    ///
+    /// This is close to zero when the per-iteration value is constant.
+    /// Strongly negative values indicate that iterations get faster as more of
+    /// them are run in a row, and strongly positive values indicate the
+    /// opposite.
+    pub per_iteration_trend: f64,
+
+    /// Statistic of the Wald-Wolfowitz runs test on the signs of residuals,
+    /// ordered by iteration count
+    ///
+    /// Residuals of a good fit change sign randomly, which leads to values
+    /// around zero. Strongly negative values reveal long runs of residuals of
+    /// the same sign, which is the signature of a curved relationship.
+    pub residual_runs_z: f64,
+}
+//
+impl Linearity {
+    /// Truth that the measurement is consistent with a constant per-iteration
+    /// value, according to the thresholds defined in this module
+    pub fn is_linear(&self) -> bool {
+        self.r_squared >= MIN_LINEAR_R_SQUARED
+            && self.per_iteration_trend.abs() <= MAX_LINEAR_TREND
+            && self.residual_runs_z >= MIN_LINEAR_RUNS_Z
+    }
+}
+
+/// Check how well a measurement's values scale with iteration counts
+///
+/// Returns `None` if there are fewer than three samples, or if all samples
+/// have the same iteration count, as happens with Criterion's flat sampling
+/// mode, where no slope is estimated.
+pub fn linearity(data: &MeasurementData) -> Option<Linearity> {
+    let mut samples = TrimOptions::default().apply(data);
+    if samples.len() < 3 || samples.iter().all(|&(iters, _)| iters == samples[0].0) {
+        return None;
+    }
+    samples.sort_unstable_by(|(x1, _), (x2, _)| x1.total_cmp(x2));
+
+    // Coefficient of determination, as computed by Criterion
+    let (sum_xy, sum_x2) = samples
+        .iter()
+        .fold((0.0, 0.0), |(sum_xy, sum_x2), &(iters, value)| {
+            (sum_xy + iters * value, sum_x2 + iters * iters)
+        });
+    let slope = sum_xy / sum_x2;
+    let residuals = samples
+        .iter()
+        .map(|&(iters, value)| value - slope * iters)
+        .collect::<Vec<_>>();
+    let values = samples.iter().map(|&(_, value)| value).collect::<Vec<_>>();
+    let mean_value = mean(&values);
+    let ss_res = residuals.iter().map(|r| r * r).sum::<f64>();
+    let ss_tot = values.iter().map(|y| (y - mean_value).powi(2)).sum::<f64>();
+    let r_squared = 1.0 - ss_res / ss_tot;
+
+    // Pearson correlation between iteration counts and per-iteration values
+    let iters = samples.iter().map(|&(iters, _)| iters).collect::<Vec<_>>();
+    let avg_values = samples.iter().copied().map(avg_value).collect::<Vec<_>>();
+    let (mean_x, mean_y) = (mean(&iters), mean(&avg_values));
+    let (cov, var_x, var_y) =
+        iters
+            .iter()
+            .zip(&avg_values)
+            .fold((0.0, 0.0, 0.0), |(cov, var_x, var_y), (x, y)| {
+                let (dx, dy) = (x - mean_x, y - mean_y);
+                (cov + dx * dy, var_x + dx * dx, var_y + dy * dy)
+            });
+    let per_iteration_trend = if var_y > 0.0 {
+        cov / (var_x * var_y).sqrt()
+    } else {
+        0.0
+    };
+
+    // Runs test on residual signs
+    let num_pos = residuals.iter().filter(|&&r| r > 0.0).count() as f64;
+    let num_neg = residuals.len() as f64 - num_pos;
+    let num_runs = 1 + residuals
+        .windows(2)
+        .filter(|pair| (pair[0] > 0.0) != (pair[1] > 0.0))
+        .count();
+    let n = num_pos + num_neg;
+    let expected_runs = 2.0 * num_pos * num_neg / n + 1.0;
+    let runs_var = (expected_runs - 1.0) * (expected_runs - 2.0) / (n - 1.0);
+    let residual_runs_z = if runs_var > 0.0 {
+        (num_runs as f64 - expected_runs) / runs_var.sqrt()
+    } else {
+        0.0
+    };
+
+    Some(Linearity {
+        r_squared,
+        per_iteration_trend,
+        residual_runs_z,
+    })
+}
+
+/// Minimal sample size accepted by Criterion
+const MIN_SAMPLE_SIZE: usize = 10;
+
+/// Sample size needed to reliably detect a change of a benchmark's mean
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleSizeAdvice {
+    /// Recommended number of samples per run, i.e. Criterion's `sample_size`
+    ///
+    /// This is `None` if the run-to-run variability of the benchmark is too
+    /// large for the change to be reliably detected, however many samples are
+    /// taken. Such benchmarks need a quieter machine or more work per
+    /// iteration rather than more samples.
+    pub sample_size: Option<usize>,
+
+    /// Relative change that should be detected, in percent
+    pub detectable_change_pct: f64,
+
+    /// Probability of detecting the change, in range `0.0..1.0`
+    pub power: f64,
+
+    /// Standard deviation of the per-iteration value across the samples of the
+    /// latest run, relative to its mean, in percent
+    pub sample_noise_pct: f64,
+
+    /// Estimated standard deviation of the mean across runs that is not
+    /// explained by sample noise, relative to the latest mean, in percent
+    pub run_to_run_noise_pct: f64,
+}
+//
+impl Display for SampleSizeAdvice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.sample_size {
+            Some(sample_size) => write!(f, "sample_size >= {sample_size}")?,
+            None => write!(f, "no sample_size")?,
+        }
+        write!(
+            f,
+            " to detect {:.1}% changes with {:.0}% power \
+            (sample noise {:.1}%, run-to-run noise {:.1}%)",
+            self.detectable_change_pct,
+            self.power * 100.0,
+            self.sample_noise_pct,
+            self.run_to_run_noise_pct
+        )
+    }
+}
+
+/// Suggest a sample size that lets Criterion detect a certain relative change
+/// of a benchmark's mean with a certain probability
+///
+/// The sample noise of the latest measurement and the run-to-run variability
+/// of the mean across the history are combined into a power analysis for a
+/// comparison between two runs, using the significance level that matches the
+/// confidence level of the latest measurement. Increasing Criterion's
+/// measurement time also reduces sample noise, roughly by the square root of
+/// the time increase, and can be used instead of a larger sample size.
+///
+/// At least two measurements are needed to estimate run-to-run variability,
+/// which is otherwise assumed to be zero. Returns `None` if the history is
+/// empty, if `detectable_change_pct` is not a positive finite number, or if
+/// `power` is not in range `0.0..1.0`.
+pub fn recommend_sample_size(
+    history: &History,
+    detectable_change_pct: f64,
+    power: f64,
+) -> Option<SampleSizeAdvice> {
+    let valid_change = detectable_change_pct.is_finite() && detectable_change_pct > 0.0;
+    let valid_power = power > 0.0 && power < 1.0;
+    if !(valid_change && valid_power) {
+        return None;
+    }
+    let latest = history.latest()?.estimates;
+    let latest_mean = latest.mean.point_estimate;
+    let sample_std_dev = latest.std_dev.point_estimate;
+
+    // The spread of past means is caused by sample noise, which is measured by
+    // the standard error of each mean, and run-to-run noise, which is the rest
+    let points = history.points();
+    let run_to_run_var = if points.len() >= 2 {
+        let means = points
+            .iter()
+            .map(|point| point.estimates.mean.point_estimate)
+            .collect::<Vec<_>>();
+        let means_var = std_dev(&means, mean(&means)).powi(2);
+        let sample_var = points
+            .iter()
+            .map(|point| point.estimates.mean.standard_error.powi(2))
+            .sum::<f64>()
+            / points.len() as f64;
+        (means_var - sample_var).max(0.0)
+    } else {
+        0.0
+    };
+
+    // Two runs' means must differ by the detectable change with a standard
+    // deviation sqrt(2 * (sample_var / n + run_to_run_var)) that is small
+    // enough for the test to reach the desired power
+    let alpha = 1.0 - latest.mean.confidence_interval.confidence_level;
+    let z = normal_quantile(1.0 - alpha / 2.0) + normal_quantile(power);
+    let delta = detectable_change_pct / 100.0 * latest_mean;
+    let max_sample_var = (delta / z).powi(2) / 2.0 - run_to_run_var;
+    let sample_size = (max_sample_var > 0.0).then(|| {
+        let sample_size = (sample_std_dev.powi(2) / max_sample_var).ceil();
+        (sample_size as usize).max(MIN_SAMPLE_SIZE)
+    });
+    Some(SampleSizeAdvice {
+        sample_size,
+        detectable_change_pct,
+        power,
+        sample_noise_pct: sample_std_dev / latest_mean * 100.0,
+        run_to_run_noise_pct: run_to_run_var.sqrt() / latest_mean * 100.0,
+    })
+}
+
+/// Estimated impact of benchmark changes on the execution time of a workload
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorkloadImpact {
+    /// Unit of `delta` and `contributions`
+    pub unit: ValueUnit,
+
+    /// Change of the total workload execution time
+    pub delta: f64,
+
+    /// Contribution of each changed benchmark to `delta`, indexed by
+    /// benchmark path
+    pub contributions: BTreeMap<String, f64>,
+
+    /// Changed benchmarks for which no call frequency was specified, and which
+    /// are thus not accounted for
+    pub unweighted: Vec<String>,
+
+    /// Changed benchmarks whose values are not durations, and which are thus
+    /// not accounted for
+    pub not_timed: Vec<String>,
+}
+//
+impl Display for WorkloadImpact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let sign = if self.delta > 0.0 { "+" } else { "" };
+        write!(
+            f,
+            "estimated workload time change: {sign}{}",
+            self.unit.format_value(self.delta)
+        )?;
+        if !self.unweighted.is_empty() {
+            write!(f, " (not accounting for {})", self.unweighted.join(", "))?;
+        }
+        if !self.not_timed.is_empty() {
+            write!(f, " (not timed: {})", self.not_timed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate how benchmark changes affect the execution time of a workload
+///
+/// `workload_weights` maps `/`-separated benchmark paths to the number of
+/// times the benchmarked code runs in the workload of interest, e.g. in one
+/// request or one frame. The change of the execution time of each changed
+/// benchmark, as measured on the statistic that it was
+/// [compared on](crate::snapshot::SnapshotChange::statistic), is multiplied by its call
+/// frequency, and the results are summed
+/// up, so that large relative changes of rarely called code are put into
+/// perspective.
+///
+/// Benchmarks whose values are not durations, according to the unit recorded
+/// in the snapshots, are listed separately.
+pub fn estimated_total_time(
+    comparison: &SnapshotComparison,
+    workload_weights: &BTreeMap<String, f64>,
+) -> WorkloadImpact {
+    let mut impact = WorkloadImpact::default();
+    for change in &comparison.changed {
+        if !(change.recorded.unit.is_time() && change.latest.unit.is_time()) {
+            impact.not_timed.push(change.benchmark.clone());
+            continue;
+        }
+        let Some(calls) = workload_weights.get(&change.benchmark) else {
+            impact.unweighted.push(change.benchmark.clone());
+            continue;
+        };
+        let contribution = calls * change.delta();
+        impact.delta += contribution;
+        impact
+            .contributions
+            .insert(change.benchmark.clone(), contribution);
+    }
+    impact
+}
+
+/// Reason why two measurements may not be directly comparable
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ComparabilityNote {
+    /// The number of usable samples changed, e.g. because Criterion's
+    /// `sample_size` setting was changed
+    SampleSizeChanged {
+        /// Number of samples of the older measurement
+        before: usize,
+
+        /// Number of samples of the newer measurement
+        after: usize,
+    },
+
+    /// The confidence level of the estimates changed, e.g. because Criterion's
+    /// `confidence_level` setting was changed
+    ConfidenceLevelChanged {
+        /// Confidence level of the older measurement
+        before: f64,
+
+        /// Confidence level of the newer measurement
+        after: f64,
+    },
+}
+//
+impl Display for ComparabilityNote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SampleSizeChanged { before, after } => {
+                write!(f, "sample size changed from {before} to {after}")
+            }
+            Self::ConfidenceLevelChanged { before, after } => write!(
+                f,
+                "confidence level changed from {:.1}% to {:.1}%",
+                before * 100.0,
+                after * 100.0
+            ),
+        }
+    }
+}
+
+/// Outcome of [`compare_measurements()`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeasurementComparison {
+    /// Statistics that the measurements were compared with
+    ///
+    /// In [`RobustnessMode::Classic`] mode, the test and effect size are based
+    /// on the mean and standard deviation of per-iteration values. In
+    /// [`RobustnessMode::Robust`] mode, they are based on the median and the
+    /// median absolute deviation (MAD) instead.
+    pub mode: RobustnessMode,
+
+    /// Relative change of the mean per-iteration value (e.g. 0.05 for +5%)
+    pub mean_change: f64,
+
+    /// Relative change of the median per-iteration value
+    pub median_change: f64,
+
+    /// Welch's t statistic of the change
+    ///
+    /// If neither measurement has any noise, this is zero when their means
+    /// (or medians) are equal, and infinite otherwise.
+    pub t_statistic: f64,
+
+    /// Welch–Satterthwaite degrees of freedom of the test
+    ///
+    /// These are undefined if neither measurement has any noise, in which
+    /// case this is the total sample size minus two.
+    pub degrees_of_freedom: f64,
+
+    /// Two-sided p-value of the test, i.e. the probability that a t statistic
+    /// at least this large is observed if the mean did not change
+    ///
+    /// This is the quantity that must be corrected when many benchmarks are
+    /// tested at once, see [`ci::Gate`](crate::ci::Gate).
+    pub p_value: f64,
+
+    /// Confidence level at which the test was performed
+    ///
+    /// If both measurements have different confidence levels, this is the
+    /// highest one, so that the test is not less conservative than either.
+    pub confidence_level: f64,
+
+    /// Truth that the change is statistically significant at
+    /// `confidence_level`, i.e. that `p_value` is below `1 - confidence_level`
+    pub significant: bool,
+
+    /// Cohen's d, i.e. the change of the mean in units of the pooled standard
+    /// deviation of per-iteration values
+    ///
+    /// Unlike `significant`, which tells whether a change is detectable, this
+    /// tells whether it is big: benchmarks with very little noise can have
+    /// significant changes that are negligible with respect to their spread.
+    ///
+    /// In [`RobustnessMode::Robust`] mode, this is the change of the median in
+    /// units of the pooled MAD, scaled to estimate the standard deviation.
+    pub cohens_d: f64,
+
+    /// Cliff's delta, i.e. the probability that a per-iteration value of the
+    /// newer measurement is higher than one of the older measurement, minus
+    /// the probability that it is lower
+    ///
+    /// This non-parametric effect size, from -1 to 1, is robust to outliers.
+    pub cliffs_delta: f64,
+
+    /// Differences between the settings of both measurements
+    ///
+    /// These are accounted for by the test, but should be displayed alongside
+    /// its result, since they also affect Criterion's own change estimates.
+    pub notes: Vec<ComparabilityNote>,
+}
+//
+impl MeasurementComparison {
+    /// Relative change of the statistic that the measurements were compared
+    /// with, i.e. the mean or the median depending on the [`mode`](Self::mode)
+    pub fn change(&self) -> f64 {
+        match self.mode {
+            RobustnessMode::Classic => self.mean_change,
+            RobustnessMode::Robust => self.median_change,
+        }
+    }
+
+    /// Magnitude of the change, according to its Cohen's d
+    pub fn effect_size(&self) -> EffectSize {
+        EffectSize::of_cohens_d(self.cohens_d)
+    }
+}
+//
+impl Display for MeasurementComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:+.2}% ({} at {:.1}% confidence, t = {:.2}; d = {:+.2}, {} effect; Cliff's delta = {:+.2})",
+            match self.mode {
+                RobustnessMode::Classic => "mean",
+                RobustnessMode::Robust => "median",
+            },
+            self.change() * 100.0,
+            if self.significant {
+                "significant"
+            } else {
+                "not significant"
+            },
+            self.confidence_level * 100.0,
+            self.t_statistic,
+            self.cohens_d,
+            self.effect_size(),
+            self.cliffs_delta
+        )?;
+        for note in &self.notes {
+            write!(f, "; {note}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Test whether the latest measurement of a benchmark changed with respect to
+/// a baseline measurement
+///
+/// The baseline is picked among the earlier measurements of the benchmark by
+/// `strategy`, and compared with the latest measurement as in
+/// [`compare_measurements_with()`]. Every measurement of the benchmark is decoded
+/// to find the baseline, and malformed measurement files are handled according
+/// to the [decoding error policy](crate::Search::on_decode_error) of the
+/// search.
+///
+/// Returns `None` if no measurement matches the strategy, or if the comparison
+/// cannot be performed.
+pub fn compare_with_baseline(
+    benchmark: &Benchmark,
+    strategy: BaselineStrategy,
+    mode: RobustnessMode,
+) -> Result<Option<MeasurementComparison>, Error> {
+    let mut measurements = benchmark
+        .decoded_measurements()
+        .map(|result| result.map(|(_, data)| data))
+        .collect::<Result<Vec<_>, _>>()?;
+    measurements.sort_by_key(|data| data.datetime);
+    let points = measurements
+        .iter()
+        .map(HistoryPoint::new)
+        .collect::<Vec<_>>();
+    let Some(baseline) = strategy.select_index(&points) else {
+        return Ok(None);
+    };
+    let latest = measurements.last().expect("A baseline was found above");
+    Ok(compare_measurements_with(
+        &measurements[baseline],
+        latest,
+        mode,
+    ))
+}
+
+/// Test whether the mean per-iteration value changed between two measurements
+///
+/// Unlike Criterion, which assumes that both runs have the same number of
+/// samples, this uses Welch's t-test, which remains valid when the sample
+/// sizes and variances of both measurements differ. Any difference between
+/// the sample sizes or confidence levels of both measurements is reported in
+/// the result's [notes](MeasurementComparison::notes).
+///
+/// Returns `None` if either measurement has fewer than two usable samples.
+/// This is a shorthand for [`compare_measurements_with()`] in
+/// [`RobustnessMode::Classic`] mode.
+pub fn compare_measurements(
+    before: &MeasurementData,
+    after: &MeasurementData,
+) -> Option<MeasurementComparison> {
+    compare_measurements_with(before, after, RobustnessMode::Classic)
+}
+
+/// Test whether the mean or median per-iteration value changed between two
+/// measurements
+///
+/// This works like [`compare_measurements()`], but lets you choose which
+/// statistics are compared. In [`RobustnessMode::Robust`] mode, Welch's
+/// t-test is applied to the medians of both measurements, whose standard
+/// errors are estimated from their median absolute deviation (MAD) as
+/// `sqrt(pi / 2) * 1.4826 * MAD / sqrt(n)`. This is the asymptotic standard
+/// error of the median of normally distributed data, but unlike the standard
+/// error of the mean, it is barely affected by a few extreme samples.
+pub fn compare_measurements_with(
+    before: &MeasurementData,
+    after: &MeasurementData,
+    mode: RobustnessMode,
+) -> Option<MeasurementComparison> {
+    let avg_values = |data: &MeasurementData| {
+        TrimOptions::default()
+            .apply(data)
+            .into_iter()
+            .map(avg_value)
+            .collect::<Vec<_>>()
+    };
+    let (before_values, after_values) = (avg_values(before), avg_values(after));
+    let (n1, n2) = (before_values.len(), after_values.len());
+    if n1 < 2 || n2 < 2 {
+        return None;
+    }
+    let (mean1, mean2) = (mean(&before_values), mean(&after_values));
+    let (median1, median2) = (
+        median(&mut before_values.clone()),
+        median(&mut after_values.clone()),
+    );
+    // Center, variance of per-iteration values, and variance of the center
+    // estimator relative to that of a single value
+    let (center1, center2, var1, var2, efficiency) = match mode {
+        RobustnessMode::Classic => (
+            mean1,
+            mean2,
+            std_dev(&before_values, mean1).powi(2),
+            std_dev(&after_values, mean2).powi(2),
+            1.0,
+        ),
+        RobustnessMode::Robust => (
+            median1,
+            median2,
+            scaled_mad(&before_values, median1).powi(2),
+            scaled_mad(&after_values, median2).powi(2),
+            std::f64::consts::FRAC_PI_2,
+        ),
+    };
+    let var_of_center1 = efficiency * var1 / n1 as f64;
+    let var_of_center2 = efficiency * var2 / n2 as f64;
+    let var_of_change = var_of_center1 + var_of_center2;
+    let (t_statistic, degrees_of_freedom) = if var_of_change > 0.0 {
+        (
+            (center2 - center1) / var_of_change.sqrt(),
+            var_of_change.powi(2)
+                / (var_of_center1.powi(2) / (n1 - 1) as f64
+                    + var_of_center2.powi(2) / (n2 - 1) as f64),
+        )
+    } else {
+        // Values without noise, like allocation counts, changed for sure if
+        // their centers differ, and did not change otherwise
+        let t_statistic = if center2 == center1 {
+            0.0
+        } else {
+            (center2 - center1).signum() * f64::INFINITY
+        };
+        (t_statistic, (n1 + n2 - 2) as f64)
+    };
+    let p_value = student_two_sided_p_value(t_statistic, degrees_of_freedom);
+
+    let level = |data: &MeasurementData| data.estimates.mean.confidence_interval.confidence_level;
+    let (level1, level2) = (level(before), level(after));
+    let confidence_level = level1.max(level2);
+
+    let mut notes = Vec::new();
+    if n1 != n2 {
+        notes.push(ComparabilityNote::SampleSizeChanged {
+            before: n1,
+            after: n2,
+        });
+    }
+    if level1 != level2 {
+        notes.push(ComparabilityNote::ConfidenceLevelChanged {
+            before: level1,
+            after: level2,
+        });
+    }
+    Some(MeasurementComparison {
+        mode,
+        mean_change: mean2 / mean1 - 1.0,
+        median_change: median2 / median1 - 1.0,
+        t_statistic,
+        degrees_of_freedom,
+        p_value,
+        confidence_level,
+        significant: p_value < 1.0 - confidence_level,
+        cohens_d: (center2 - center1)
+            / (((n1 - 1) as f64 * var1 + (n2 - 1) as f64 * var2) / (n1 + n2 - 2) as f64).sqrt(),
+        cliffs_delta: cliffs_delta(&before_values, &after_values),
+        notes,
+    })
+}
+
+/// Conventional classification of effect sizes
+///
+/// Thresholds are those of Cohen (1988) for Cohen's d, and those of Romano et
+/// al. (2006) for Cliff's delta. They are rules of thumb rather than universal
+/// truths, but make it possible to gate on changes that are big, rather than
+/// merely detectable.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EffectSize {
+    /// |d| < 0.2, or |delta| < 0.147
+    Negligible,
+
+    /// |d| < 0.5, or |delta| < 0.33
+    Small,
+
+    /// |d| < 0.8, or |delta| < 0.474
+    Medium,
+
+    /// Anything bigger
+    Large,
+}
+//
+impl EffectSize {
+    /// Classify a Cohen's d
+    ///
+    /// NaN, which is the Cohen's d of identical constant samples, is
+    /// negligible.
+    pub fn of_cohens_d(cohens_d: f64) -> Self {
+        Self::classify(cohens_d, [0.2, 0.5, 0.8])
+    }
+
+    /// Classify a Cliff's delta
+    pub fn of_cliffs_delta(cliffs_delta: f64) -> Self {
+        Self::classify(cliffs_delta, [0.147, 0.33, 0.474])
+    }
+
+    /// Classify an effect size according to the thresholds of the small,
+    /// medium and large classes
+    fn classify(effect: f64, [small, medium, large]: [f64; 3]) -> Self {
+        let effect = effect.abs();
+        if effect >= large {
+            Self::Large
+        } else if effect >= medium {
+            Self::Medium
+        } else if effect >= small {
+            Self::Small
+        } else {
+            Self::Negligible
+        }
+    }
+}
+//
+impl Display for EffectSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Negligible => "negligible",
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+        })
+    }
+}
+
+/// Cliff's delta of two sets of values, see
+/// [`MeasurementComparison::cliffs_delta`]
+fn cliffs_delta(before: &[f64], after: &[f64]) -> f64 {
+    let mut before = before.to_vec();
+    before.sort_unstable_by(f64::total_cmp);
+    let balance = after
+        .iter()
+        .map(|&value| {
+            let lower = before.partition_point(|&x| x < value);
+            let higher = before.len() - before.partition_point(|&x| x <= value);
+            lower as f64 - higher as f64
+        })
+        .sum::<f64>();
+    balance / (before.len() * after.len()) as f64
+}
+
+/// Quantiles at which [`compare_distributions()`] evaluates the shift function
+pub const SHIFT_QUANTILES: [f64; 9] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9];
+
+/// Outcome of [`compare_distributions()`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistributionComparison {
+    /// Kolmogorov-Smirnov distance between both distributions
+    ///
+    /// This is the largest difference between their empirical cumulative
+    /// distribution functions, from 0 for identical distributions to 1 for
+    /// distributions that do not overlap.
+    pub ks_distance: f64,
+
+    /// Probability that a distance at least this large is observed between
+    /// two samples of the same distribution, according to the asymptotic
+    /// Kolmogorov-Smirnov test
+    ///
+    /// Small values mean that the distribution changed.
+    pub ks_p_value: f64,
+
+    /// Cliff's delta, see [`MeasurementComparison::cliffs_delta`]
+    pub cliffs_delta: f64,
+
+    /// Shift function, i.e. the change of each of the [`SHIFT_QUANTILES`]
+    pub shift: Vec<QuantileShift>,
+}
+//
+impl DistributionComparison {
+    /// Largest relative change of a quantile, in absolute value
+    pub fn max_relative_shift(&self) -> f64 {
+        self.shift
+            .iter()
+            .map(|shift| shift.relative_change().abs())
+            .fold(0.0, f64::max)
+    }
+}
+//
+impl Display for DistributionComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KS distance {:.3} (p = {:.3}), Cliff's delta = {:+.2} ({} effect), shift:",
+            self.ks_distance,
+            self.ks_p_value,
+            self.cliffs_delta,
+            EffectSize::of_cliffs_delta(self.cliffs_delta)
+        )?;
+        for shift in &self.shift {
+            write!(f, " {shift}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Change of one quantile of the per-iteration value distribution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantileShift {
+    /// Quantile, as a fraction (e.g. 0.9 for the 90th percentile)
+    pub quantile: f64,
+
+    /// Value of the quantile in the older measurement
+    pub before: f64,
+
+    /// Value of the quantile in the newer measurement
+    pub after: f64,
+}
+//
+impl QuantileShift {
+    /// Relative change of the quantile (e.g. 0.05 for +5%)
+    pub fn relative_change(&self) -> f64 {
+        self.after / self.before - 1.0
+    }
+}
+//
+impl Display for QuantileShift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "p{:.0} {:+.2}%",
+            self.quantile * 100.0,
+            self.relative_change() * 100.0
+        )
+    }
+}
+
+/// Compare the distributions of per-iteration values of two measurements
+///
+/// Mean-based comparisons like [`compare_measurements()`] can miss changes
+/// that only affect part of the distribution, e.g. a regression that only
+/// slows down the slowest 10% of samples. This computes the Kolmogorov-Smirnov
+/// distance between both distributions, which detects any kind of change, and
+/// the shift function, which tells which quantiles changed and by how much.
+///
+/// Returns `None` if either measurement has fewer than two usable samples.
+pub fn compare_distributions(
+    before: &MeasurementData,
+    after: &MeasurementData,
+) -> Option<DistributionComparison> {
+    let sorted_avg_values = |data: &MeasurementData| {
+        let mut values = TrimOptions::default()
+            .apply(data)
+            .into_iter()
+            .map(avg_value)
+            .collect::<Vec<_>>();
+        values.sort_unstable_by(f64::total_cmp);
+        values
+    };
+    let (before_values, after_values) = (sorted_avg_values(before), sorted_avg_values(after));
+    let (n1, n2) = (before_values.len(), after_values.len());
+    if n1 < 2 || n2 < 2 {
+        return None;
+    }
+
+    // Walk both empirical CDFs in increasing value order, stepping over tied
+    // values at once so that ties do not count as differences
+    let (mut idx1, mut idx2, mut ks_distance) = (0, 0, 0.0f64);
+    while idx1 < n1 && idx2 < n2 {
+        let value = before_values[idx1].min(after_values[idx2]);
+        idx1 += before_values[idx1..].partition_point(|&x| x <= value);
+        idx2 += after_values[idx2..].partition_point(|&x| x <= value);
+        ks_distance = ks_distance.max((idx1 as f64 / n1 as f64 - idx2 as f64 / n2 as f64).abs());
+    }
+    let effective_len = (n1 * n2) as f64 / (n1 + n2) as f64;
+    let ks_p_value = kolmogorov_survival(
+        (effective_len.sqrt() + 0.12 + 0.11 / effective_len.sqrt()) * ks_distance,
+    );
+
+    let shift = SHIFT_QUANTILES
+        .iter()
+        .map(|&quantile| QuantileShift {
+            quantile,
+            before: percentile(&before_values, quantile),
+            after: percentile(&after_values, quantile),
+        })
+        .collect();
+    Some(DistributionComparison {
+        ks_distance,
+        ks_p_value,
+        cliffs_delta: cliffs_delta(&before_values, &after_values),
+        shift,
+    })
+}
+
+/// Survival function of the Kolmogorov distribution
+///
+/// This sums the alternating series from Numerical Recipes, which converges
+/// quickly except for small arguments, where the result is close to 1.
+fn kolmogorov_survival(lambda: f64) -> f64 {
+    let mut sum = 0.0;
+    let mut sign = 2.0;
+    let mut previous_term = 0.0f64;
+    for k in 1..=100 {
+        let k = f64::from(k);
+        let term = sign * (-2.0 * k * k * lambda * lambda).exp();
+        sum += term;
+        if term.abs() <= 1e-3 * previous_term.abs() || term.abs() <= 1e-8 * sum {
+            return sum.clamp(0.0, 1.0);
+        }
+        sign = -sign;
+        previous_term = term;
+    }
+    1.0
+}
+
+/// Prior belief about the ratio of mean per-iteration values, used by
+/// [`compare_bayesian()`]
+///
+/// This is a normal distribution of the logarithm of the ratio. The default
+/// prior is centered on no change, with a standard deviation of 0.25, which
+/// means that changes beyond ±40% are considered unlikely but possible. It is
+/// quickly overwhelmed by the data of typical Criterion measurements.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RatioPrior {
+    /// Expected logarithm of the ratio
+    pub log_ratio_mean: f64,
+
+    /// Uncertainty on the logarithm of the ratio
+    ///
+    /// An infinite value gives an uninformative prior, where the posterior
+    /// only depends on the data.
+    pub log_ratio_std_dev: f64,
+}
+//
+impl RatioPrior {
+    /// Uninformative prior
+    pub fn uninformative() -> Self {
+        Self {
+            log_ratio_mean: 0.0,
+            log_ratio_std_dev: f64::INFINITY,
+        }
+    }
+}
+//
+impl Default for RatioPrior {
+    fn default() -> Self {
+        Self {
+            log_ratio_mean: 0.0,
+            log_ratio_std_dev: 0.25,
+        }
+    }
+}
+
+/// Outcome of [`compare_bayesian()`]
+///
+/// The posterior distribution of the logarithm of the ratio of the newer mean
+/// per-iteration value to the older one is normal, with the parameters below.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BayesianComparison {
+    /// Posterior mean of the logarithm of the ratio
+    pub log_ratio_mean: f64,
+
+    /// Posterior standard deviation of the logarithm of the ratio
+    pub log_ratio_std_dev: f64,
+}
+//
+impl BayesianComparison {
+    /// Most likely ratio of the newer mean to the older one
+    pub fn ratio(&self) -> f64 {
+        self.log_ratio_mean.exp()
+    }
+
+    /// Symmetric credible interval of the ratio at some probability level
+    /// (e.g. 0.95)
+    ///
+    /// Returns `None` if `level` is not in range `0.0..1.0`.
+    pub fn credible_interval(&self, level: f64) -> Option<(f64, f64)> {
+        if !(level > 0.0 && level < 1.0) {
+            return None;
+        }
+        let half_width = normal_quantile(0.5 + level / 2.0) * self.log_ratio_std_dev;
+        Some((
+            (self.log_ratio_mean - half_width).exp(),
+            (self.log_ratio_mean + half_width).exp(),
+        ))
+    }
+
+    /// Probability that the mean per-iteration value increased by more than
+    /// `threshold` (e.g. 0.02 for 2%)
+    pub fn probability_of_regression(&self, threshold: f64) -> f64 {
+        1.0 - self.probability_of_ratio_below(1.0 + threshold)
+    }
+
+    /// Probability that the mean per-iteration value decreased by more than
+    /// `threshold` (e.g. 0.02 for 2%)
+    pub fn probability_of_improvement(&self, threshold: f64) -> f64 {
+        self.probability_of_ratio_below(1.0 - threshold)
+    }
+
+    /// Posterior probability that the ratio is below some value
+    fn probability_of_ratio_below(&self, ratio: f64) -> f64 {
+        normal_cdf((ratio.ln() - self.log_ratio_mean) / self.log_ratio_std_dev)
+    }
+}
+//
+impl Display for BayesianComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (lower, upper) = self
+            .credible_interval(0.95)
+            .expect("0.95 is a valid credible level");
+        write!(
+            f,
+            "ratio {:.4} (95% credible interval {lower:.4}..{upper:.4})",
+            self.ratio()
+        )
+    }
+}
+
+/// Compute the posterior distribution of the ratio of the mean per-iteration
+/// values of two measurements
+///
+/// The data enter through the logarithm of the ratio of the sample means,
+/// whose sampling distribution is approximated as normal, with a standard
+/// error derived from the spread of both measurements' samples. This
+/// approximation is accurate for the sample sizes that Criterion uses, but
+/// becomes overconfident for measurements with only a handful of samples.
+///
+/// If neither measurement has any noise, the posterior is the observed ratio,
+/// with no uncertainty, whatever the prior.
+///
+/// Returns `None` if either measurement has fewer than two usable samples, or
+/// a non-positive mean.
+pub fn compare_bayesian(
+    before: &MeasurementData,
+    after: &MeasurementData,
+    prior: &RatioPrior,
+) -> Option<BayesianComparison> {
+    let avg_values = |data: &MeasurementData| {
+        TrimOptions::default()
+            .apply(data)
+            .into_iter()
+            .map(avg_value)
+            .collect::<Vec<_>>()
+    };
+    let (before_values, after_values) = (avg_values(before), avg_values(after));
+    let (n1, n2) = (before_values.len(), after_values.len());
+    if n1 < 2 || n2 < 2 {
+        return None;
+    }
+    let (mean1, mean2) = (mean(&before_values), mean(&after_values));
+    if mean1 <= 0.0 || mean2 <= 0.0 {
+        return None;
+    }
+    // Delta method: the variance of ln(mean) is that of mean, over mean²
+    let relative_variance =
+        |values: &[f64], mean: f64| (std_dev(values, mean) / mean).powi(2) / values.len() as f64;
+    let data_precision =
+        1.0 / (relative_variance(&before_values, mean1) + relative_variance(&after_values, mean2));
+    let log_ratio = (mean2 / mean1).ln();
+    if data_precision.is_infinite() {
+        // Values without noise, like allocation counts, leave no uncertainty
+        return Some(BayesianComparison {
+            log_ratio_mean: log_ratio,
+            log_ratio_std_dev: 0.0,
+        });
+    }
+    let prior_precision = prior.log_ratio_std_dev.powi(-2);
+    let precision = data_precision + prior_precision;
+    Some(BayesianComparison {
+        log_ratio_mean: (log_ratio * data_precision + prior.log_ratio_mean * prior_precision)
+            / precision,
+        log_ratio_std_dev: precision.sqrt().recip(),
+    })
+}
+
+/// Probability that the magnitude of a Student t variable is at least that of
+/// `t_statistic`
+///
+/// A NaN t statistic, which results from non-finite samples, has a p-value
+/// of 1.
+fn student_two_sided_p_value(t_statistic: f64, degrees_of_freedom: f64) -> f64 {
+    if t_statistic.is_nan() {
+        return 1.0;
+    }
+    let x = degrees_of_freedom / (degrees_of_freedom + t_statistic.powi(2));
+    regularized_incomplete_beta(degrees_of_freedom / 2.0, 0.5, x)
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+///
+/// This evaluates the continued fraction from Numerical Recipes with the
+/// modified Lentz method, on whichever side of the distribution converges
+/// fastest.
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    let continued_fraction = |a: f64, b: f64, x: f64| {
+        const TINY: f64 = 1e-300;
+        let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+        let mut c = 1.0;
+        let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+        let mut result = d;
+        for m in 1..=300 {
+            let m = f64::from(m);
+            let even_coeff = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+            d = 1.0 / clamp(1.0 + even_coeff * d);
+            c = clamp(1.0 + even_coeff / c);
+            result *= d * c;
+            let odd_coeff = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+            d = 1.0 / clamp(1.0 + odd_coeff * d);
+            c = clamp(1.0 + odd_coeff / c);
+            let delta = d * c;
+            result *= delta;
+            if (delta - 1.0).abs() < 1e-12 {
+                break;
+            }
+        }
+        result
+    };
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Natural logarithm of the gamma function, for positive arguments
+///
+/// This uses the Lanczos approximation with g = 7, which is accurate to about
+/// 15 significant digits, and the reflection formula below 0.5.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFS[0], |acc, (idx, coeff)| {
+            acc + coeff / (x + idx as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Cumulative distribution function of the standard normal distribution
+///
+/// This uses the Chebyshev approximation of erfc from Numerical Recipes, whose
+/// relative error is below 1.2e-7 everywhere.
+fn normal_cdf(x: f64) -> f64 {
+    const COEFFS: [f64; 10] = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ];
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = COEFFS.iter().rev().fold(0.0, |acc, coeff| acc * t + coeff);
+    let erfc = t * (-z * z + polynomial).exp();
+    if x >= 0.0 {
+        1.0 - erfc / 2.0
+    } else {
+        erfc / 2.0
+    }
+}
+
+/// Quantile function of the standard normal distribution
+///
+/// This uses Acklam's rational approximation, whose relative error is below
+/// 1.2e-9 over the whole `0.0..1.0` range.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Compute bootstrapped estimates from one or more runs' worth of samples
+///
+/// When several runs are provided, a two-level bootstrap is performed (see
+/// [`pool_runs()`]), so that confidence intervals account for run-to-run
+/// variability in addition to sample-to-sample variability.
+fn bootstrap(
+    runs: &[Vec<(f64, f64)>],
+    with_slope: bool,
+    confidence_level: f64,
+    resamples: usize,
+) -> Option<Estimates> {
+    let num_samples = runs.iter().map(Vec::len).sum::<usize>();
+    if num_samples < 2 || runs.iter().any(Vec::is_empty) {
+        return None;
+    }
+
+    // Compute point estimates using all samples
+    let mut scratch = Vec::with_capacity(num_samples);
+    let mut resample = runs.concat();
+    let point = Statistics::new(&resample, &mut scratch);
+
+    // Compute the bootstrap distribution of each statistic
+    let mut rng = Rng::default();
+    let mut distributions = Statistics::<Vec<f64>>::with_capacity(resamples);
+    for _ in 0..resamples {
+        resample.clear();
+        for _ in 0..runs.len() {
+            let run = &runs[rng.index(runs.len())];
+            resample.extend((0..run.len()).map(|_| run[rng.index(run.len())]));
+        }
+        distributions.push(Statistics::new(&resample, &mut scratch));
+    }
+
+    // Turn these into estimates
+    let estimate =
+        |point: f64, distribution: Vec<f64>| make_estimate(point, distribution, confidence_level);
+    Some(Estimates {
+        mean: estimate(point.mean, distributions.mean),
+        median: estimate(point.median, distributions.median),
+        median_abs_dev: estimate(point.median_abs_dev, distributions.median_abs_dev),
+        slope: with_slope.then(|| estimate(point.slope, distributions.slope)),
+        std_dev: estimate(point.std_dev, distributions.std_dev),
+    })
+}
+
+/// Turn a point estimate and its bootstrap distribution into an [`Estimate`]
+fn make_estimate(
+    point_estimate: f64,
+    mut distribution: Vec<f64>,
+    confidence_level: f64,
+) -> Estimate {
+    distribution.sort_unstable_by(f64::total_cmp);
+    let alpha = 1.0 - confidence_level;
+    Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level,
+            lower_bound: percentile(&distribution, alpha / 2.0),
+            upper_bound: percentile(&distribution, 1.0 - alpha / 2.0),
+        },
+        point_estimate,
+        standard_error: std_dev(&distribution, mean(&distribution)),
+    }
+}
+
+/// Statistics that Criterion computes over a set of samples
+#[derive(Debug)]
+struct Statistics<T = f64> {
+    mean: T,
+    median: T,
+    median_abs_dev: T,
+    slope: T,
+    std_dev: T,
+}
+//
+impl Statistics {
+    /// Compute statistics over a set of (iterations, value) samples
+    fn new(samples: &[(f64, f64)], scratch: &mut Vec<f64>) -> Self {
+        scratch.clear();
+        scratch.extend(samples.iter().copied().map(avg_value));
+        let avg_mean = mean(scratch);
+        let avg_std_dev = std_dev(scratch, avg_mean);
+        let avg_median = median(scratch);
+        for x in scratch.iter_mut() {
+            *x = (*x - avg_median).abs();
+        }
+        // Like Criterion, scale the MAD to make it a consistent estimator of
+        // the standard deviation for normally distributed data.
+        let median_abs_dev = median(scratch) * 1.4826;
+        let (sum_xy, sum_x2) = samples
+            .iter()
+            .fold((0.0, 0.0), |(sum_xy, sum_x2), &(iters, value)| {
+                (sum_xy + iters * value, sum_x2 + iters * iters)
+            });
+        Self {
+            mean: avg_mean,
+            median: avg_median,
+            median_abs_dev,
+            slope: sum_xy / sum_x2,
+            std_dev: avg_std_dev,
+        }
+    }
+}
+//
+impl Statistics<Vec<f64>> {
+    /// Prepare to collect bootstrap distributions
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            mean: Vec::with_capacity(capacity),
+            median: Vec::with_capacity(capacity),
+            median_abs_dev: Vec::with_capacity(capacity),
+            slope: Vec::with_capacity(capacity),
+            std_dev: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Record the statistics of one bootstrap resample
+    fn push(&mut self, stats: Statistics) {
+        self.mean.push(stats.mean);
+        self.median.push(stats.median);
+        self.median_abs_dev.push(stats.median_abs_dev);
+        self.slope.push(stats.slope);
+        self.std_dev.push(stats.std_dev);
+    }
+}
+
+/// Per-iteration value of an (iterations, value) sample
+fn avg_value((iters, value): (f64, f64)) -> f64 {
+    value / iters
+}
+
+/// Arithmetic mean of a dataset
+fn mean(data: &[f64]) -> f64 {
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+/// Sample standard deviation of a dataset with a known mean
+fn std_dev(data: &[f64], mean: f64) -> f64 {
+    let sum_sq = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+    (sum_sq / (data.len() - 1) as f64).sqrt()
+}
+
+/// Median absolute deviation of a non-empty dataset with a known median,
+/// scaled so that it estimates the standard deviation of normally distributed
+/// data
+fn scaled_mad(data: &[f64], median_value: f64) -> f64 {
+    let mut deviations = data
+        .iter()
+        .map(|x| (x - median_value).abs())
+        .collect::<Vec<_>>();
+    median(&mut deviations) * 1.4826
+}
+
+/// Median of a non-empty dataset, which will be reordered in the process
+pub(crate) fn median(data: &mut [f64]) -> f64 {
+    let len = data.len();
+    let (below, &mut mid, _) = data.select_nth_unstable_by(len / 2, f64::total_cmp);
+    if len % 2 == 1 {
+        mid
+    } else {
+        let below_mid = below.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (below_mid + mid) / 2.0
+    }
+}
+
+/// Percentile of a sorted dataset, with linear interpolation between points
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = fraction * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+/// Deterministic pseudorandom number generator (SplitMix64)
+///
+/// Bootstrap resampling does not need cryptographic-quality randomness, and
+/// using a fixed seed makes recomputed estimates reproducible.
+pub(crate) struct Rng(u64);
+//
+impl Default for Rng {
+    fn default() -> Self {
+        Self(0x5EED_CB0F_C217_E210)
+    }
+}
+//
+impl Rng {
+    /// Generate a uniformly distributed 64-bit integer
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a uniformly distributed index in range `0..len`
+    pub(crate) fn index(&mut self, len: usize) -> usize {
+        ((u128::from(self.next_u64()) * len as u128) >> 64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        snapshot::{SnapshotChange, SnapshotEntry},
+        testing::heavy_tailed,
+        Statistic,
+    };
+
+    /// Measurement with one iteration per sample
+    fn measurement(values: &[f64], confidence_level: f64) -> MeasurementData {
+        let estimate = Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level,
+                lower_bound: 0.0,
+                upper_bound: 0.0,
+            },
+            point_estimate: 0.0,
+            standard_error: 0.0,
+        };
+        MeasurementData {
+            datetime: chrono::DateTime::UNIX_EPOCH,
+            iterations: vec![1.0; values.len()],
+            values: values.to_vec(),
+            avg_values: values.to_vec(),
+            estimates: Estimates {
+                mean: estimate,
+                median: estimate,
+                median_abs_dev: estimate,
+                slope: None,
+                std_dev: estimate,
+            },
+            throughput: None,
+            changes: None,
+            change_direction: None,
+            history_id: None,
+            history_description: None,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn trim_options_skip_warmup_and_tails() {
+        let mut values = vec![50.0, 3.0, 1.0, f64::NAN, 4.0, 2.0];
+        values.extend((5..=10).map(f64::from));
+        let trim = TrimOptions {
+            skip_first: 1,
+            low_tail: 0.1,
+            high_tail: 0.2,
+        };
+        let kept = trim.apply(&measurement(&values, 0.95));
+        let kept = kept.into_iter().map(avg_value).collect::<Vec<_>>();
+        assert_eq!(kept, [2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    }
+
+    #[test]
+    fn recompute_estimates_matches_reference_statistics() {
+        let values = (1..=8).map(f64::from).collect::<Vec<_>>();
+        let data = measurement(&values, 0.9);
+        let estimates = recompute_estimates(&data, &TrimOptions::default()).unwrap();
+        assert_eq!(estimates.mean.point_estimate, 4.5);
+        assert_eq!(estimates.median.point_estimate, 4.5);
+        assert_close(estimates.std_dev.point_estimate, 6.0f64.sqrt(), 1e-12);
+        assert_close(estimates.median_abs_dev.point_estimate, 2.0 * 1.4826, 1e-12);
+        assert_eq!(estimates.slope, None);
+        for estimate in [estimates.mean, estimates.median, estimates.std_dev] {
+            let interval = estimate.confidence_interval;
+            assert_eq!(interval.confidence_level, 0.9);
+            assert!(interval.lower_bound <= estimate.point_estimate);
+            assert!(estimate.point_estimate <= interval.upper_bound);
+            assert!(estimate.standard_error > 0.0);
+        }
+
+        // The bootstrap uses a fixed seed, so results are reproducible
+        let again = recompute_estimates(&data, &TrimOptions::default()).unwrap();
+        assert_eq!(again, estimates);
+
+        // Skipping all samples but one leaves too few samples
+        let trim = TrimOptions {
+            skip_first: 7,
+            ..TrimOptions::default()
+        };
+        assert_eq!(recompute_estimates(&data, &trim), None);
+    }
+
+    #[test]
+    fn recompute_estimates_rejects_invalid_trim_options() {
+        let data = measurement(&[1.0, 2.0, 3.0, 4.0], 0.95);
+        for (low_tail, high_tail) in [(0.5, 0.0), (0.0, -0.1), (f64::NAN, 0.0), (0.0, 1.0)] {
+            let trim = TrimOptions {
+                skip_first: 0,
+                low_tail,
+                high_tail,
+            };
+            assert!(!trim.is_valid());
+            assert_eq!(recompute_estimates(&data, &trim), None);
+        }
+        assert!(TrimOptions::default().is_valid());
+    }
+
+    #[test]
+    fn recompute_estimates_computes_slope_of_linear_samples() {
+        let mut data = measurement(&[2.0, 4.0, 6.0, 8.0], 0.95);
+        data.iterations = vec![1.0, 2.0, 3.0, 4.0];
+        data.estimates.slope = Some(data.estimates.mean);
+        let estimates = recompute_estimates(&data, &TrimOptions::default()).unwrap();
+        let slope = estimates.slope.unwrap();
+        assert_eq!(slope.point_estimate, 2.0);
+        assert_eq!(slope.confidence_interval.lower_bound, 2.0);
+        assert_eq!(slope.confidence_interval.upper_bound, 2.0);
+    }
+
+    #[test]
+    fn releveled_estimates_widen_with_confidence_level() {
+        let values = [9.0, 12.0, 10.0, 11.0, 14.0, 8.0, 10.0, 13.0, 9.0, 11.0];
+        let data = measurement(&values, 0.95);
+        let at_95 = releveled_estimates(&data, 0.95).unwrap();
+        let at_99 = releveled_estimates(&data, 0.99).unwrap();
+        assert_eq!(at_99.mean.point_estimate, at_95.mean.point_estimate);
+        assert_eq!(at_99.mean.confidence_interval.confidence_level, 0.99);
+        assert!(
+            at_99.mean.confidence_interval.lower_bound < at_95.mean.confidence_interval.lower_bound
+        );
+        assert!(
+            at_99.mean.confidence_interval.upper_bound > at_95.mean.confidence_interval.upper_bound
+        );
+        for level in [0.0, 1.0, -0.5, f64::NAN] {
+            assert_eq!(releveled_estimates(&data, level), None);
+        }
+        assert_eq!(releveled_estimates(&measurement(&[1.0], 0.95), 0.99), None);
+    }
+
+    #[test]
+    fn estimate_at_confidence_bootstraps_raw_samples() {
+        let values = [9.0, 12.0, 10.0, 11.0, 14.0, 8.0, 10.0, 13.0, 9.0, 11.0];
+        let mut data = measurement(&values, 0.95);
+        data.estimates = releveled_estimates(&data, 0.95).unwrap();
+        let at_99 = releveled_estimates(&data, 0.99).unwrap();
+        let median = data.estimates.median.at_confidence(0.99, &data);
+        assert_eq!(median, Some(at_99.median));
+        let std_dev = data.estimates.std_dev.at_confidence(0.99, &data);
+        assert_eq!(std_dev, Some(at_99.std_dev));
+
+        let foreign = Estimate {
+            point_estimate: 42.0,
+            ..data.estimates.mean
+        };
+        assert_eq!(foreign.at_confidence(0.99, &data), None);
+        assert_eq!(data.estimates.mean.at_confidence(1.5, &data), None);
+    }
+
+    #[test]
+    fn workload_impact_display_uses_its_unit() {
+        let impact = WorkloadImpact {
+            unit: ValueUnit::Nanoseconds,
+            delta: 2500.0,
+            contributions: BTreeMap::new(),
+            unweighted: vec!["codec".to_owned()],
+            not_timed: vec!["alloc".to_owned()],
+        };
+        assert_eq!(
+            impact.to_string(),
+            "estimated workload time change: +2.5000 µs (not accounting for codec) (not timed: alloc)"
+        );
+        let impact = WorkloadImpact {
+            unit: ValueUnit::Custom("cycles".to_owned()),
+            delta: -42.0,
+            ..WorkloadImpact::default()
+        };
+        assert_eq!(
+            impact.to_string(),
+            "estimated workload time change: -42.000 cycles"
+        );
+    }
+
+    #[test]
+    fn estimated_total_time_only_sums_durations() {
+        let change = |benchmark: &str, unit: ValueUnit, before, after| {
+            let entry = |mean| SnapshotEntry {
+                mean,
+                median: mean,
+                typical: None,
+                mean_standard_error: None,
+                std_dev: None,
+                unit: unit.clone(),
+            };
+            SnapshotChange {
+                benchmark: benchmark.to_owned(),
+                recorded: entry(before),
+                latest: entry(after),
+                statistic: Statistic::Typical,
+            }
+        };
+        let comparison = SnapshotComparison {
+            changed: vec![
+                change("parse", ValueUnit::Nanoseconds, 100.0, 120.0),
+                change("render", ValueUnit::Nanoseconds, 50.0, 40.0),
+                change("alloc", ValueUnit::Bytes, 1000.0, 4000.0),
+                change("codec", ValueUnit::Nanoseconds, 10.0, 20.0),
+            ],
+            ..SnapshotComparison::default()
+        };
+        let weights = [("parse", 3.0), ("render", 2.0), ("alloc", 1.0)]
+            .into_iter()
+            .map(|(benchmark, calls)| (benchmark.to_owned(), calls))
+            .collect();
+
+        let impact = estimated_total_time(&comparison, &weights);
+        assert_eq!(impact.unit, ValueUnit::Nanoseconds);
+        assert_eq!(impact.delta, 40.0);
+        assert_eq!(impact.contributions["parse"], 60.0);
+        assert_eq!(impact.contributions["render"], -20.0);
+        assert_eq!(impact.unweighted, ["codec"]);
+        assert_eq!(impact.not_timed, ["alloc"]);
+    }
+
+    #[test]
+    fn pool_runs_accounts_for_run_to_run_variability() {
+        let runs = [
+            measurement(&[9.0, 10.0, 11.0, 10.0], 0.95),
+            measurement(&[19.0, 20.0, 21.0, 20.0], 0.99),
+        ];
+        let pooled = pool_runs(&runs).unwrap();
+        assert_eq!(pooled.mean.point_estimate, 15.0);
+        assert_eq!(pooled.median.point_estimate, 15.0);
+        assert_eq!(pooled.mean.confidence_interval.confidence_level, 0.95);
+        assert_eq!(pooled.slope, None);
+
+        // Naively concatenating both runs yields a narrower interval, since
+        // the bootstrap then never draws samples from a single run
+        let concatenated = measurement(&[9.0, 10.0, 11.0, 10.0, 19.0, 20.0, 21.0, 20.0], 0.95);
+        let naive = releveled_estimates(&concatenated, 0.95).unwrap();
+        assert_eq!(naive.mean.point_estimate, 15.0);
+        let width = |estimate: &Estimate| {
+            estimate.confidence_interval.upper_bound - estimate.confidence_interval.lower_bound
+        };
+        assert!(width(&pooled.mean) > width(&naive.mean));
+    }
+
+    #[test]
+    fn pool_runs_needs_samples_in_every_run() {
+        assert_eq!(pool_runs(&[]), None);
+        assert_eq!(pool_runs(&[measurement(&[1.0], 0.95)]), None);
+        let runs = [measurement(&[1.0, 2.0], 0.95), measurement(&[], 0.95)];
+        assert_eq!(pool_runs(&runs), None);
+        let runs = [measurement(&[1.0], 0.95), measurement(&[2.0], 0.95)];
+        assert_eq!(pool_runs(&runs).unwrap().mean.point_estimate, 1.5);
+    }
+
+    #[test]
+    fn kolmogorov_survival_matches_reference_values() {
+        assert_close(kolmogorov_survival(0.5), 0.963_945_244, 1e-6);
+        assert_close(kolmogorov_survival(1.0), 0.269_999_672, 1e-8);
+        assert_close(kolmogorov_survival(1.36), 0.049_485_877, 1e-8);
+        assert_close(kolmogorov_survival(2.0), 0.000_670_925, 1e-8);
+        assert_eq!(kolmogorov_survival(0.0), 1.0);
+    }
+
+    #[test]
+    fn compare_distributions_detects_tail_changes() {
+        let before = (1..=10).map(f64::from).collect::<Vec<_>>();
+        let same = compare_distributions(&measurement(&before, 0.95), &measurement(&before, 0.95))
+            .unwrap();
+        assert_eq!(same.ks_distance, 0.0);
+        assert_eq!(same.ks_p_value, 1.0);
+        assert_eq!(same.cliffs_delta, 0.0);
+        assert_eq!(same.max_relative_shift(), 0.0);
+
+        // Only the slowest sample regressed
+        let mut after = before.clone();
+        after[9] = 20.0;
+        let tail =
+            compare_distributions(&measurement(&before, 0.95), &measurement(&after, 0.95)).unwrap();
+        assert_close(tail.ks_distance, 0.1, 1e-12);
+        assert_eq!(tail.shift.len(), SHIFT_QUANTILES.len());
+        let median = tail.shift[4];
+        assert_eq!(
+            (median.quantile, median.before, median.after),
+            (0.5, 5.5, 5.5)
+        );
+        let p90 = tail.shift[8];
+        assert_close(p90.before, 9.1, 1e-12);
+        assert_close(p90.after, 10.1, 1e-12);
+        assert_close(tail.max_relative_shift(), 1.0 / 9.1, 1e-12);
+
+        // Disjoint distributions
+        let slower = before.iter().map(|x| x + 10.0).collect::<Vec<_>>();
+        let shifted =
+            compare_distributions(&measurement(&before, 0.95), &measurement(&slower, 0.95))
+                .unwrap();
+        assert_eq!(shifted.ks_distance, 1.0);
+        assert_close(shifted.ks_p_value, 1.887_979_366e-5, 1e-12);
+        assert_eq!(shifted.cliffs_delta, 1.0);
+    }
+
+    #[test]
+    fn cliffs_delta_counts_dominance() {
+        // After > before for 6 pairs, after < before for 1 pair, 2 ties
+        assert_close(
+            cliffs_delta(&[3.0, 1.0, 2.0], &[2.0, 3.0, 4.0]),
+            5.0 / 9.0,
+            1e-12,
+        );
+        assert_eq!(cliffs_delta(&[1.0, 2.0], &[3.0, 4.0]), 1.0);
+        assert_eq!(cliffs_delta(&[3.0, 4.0], &[1.0, 2.0]), -1.0);
+        assert_eq!(cliffs_delta(&[1.0, 1.0], &[1.0]), 0.0);
+    }
+
+    #[test]
+    fn effect_sizes_follow_conventional_thresholds() {
+        assert_eq!(EffectSize::of_cohens_d(0.199), EffectSize::Negligible);
+        assert_eq!(EffectSize::of_cohens_d(0.2), EffectSize::Small);
+        assert_eq!(EffectSize::of_cohens_d(-0.79), EffectSize::Medium);
+        assert_eq!(EffectSize::of_cohens_d(0.8), EffectSize::Large);
+        assert_eq!(EffectSize::of_cohens_d(f64::NAN), EffectSize::Negligible);
+        assert_eq!(EffectSize::of_cliffs_delta(0.146), EffectSize::Negligible);
+        assert_eq!(EffectSize::of_cliffs_delta(-0.147), EffectSize::Small);
+        assert_eq!(EffectSize::of_cliffs_delta(0.33), EffectSize::Medium);
+        assert_eq!(EffectSize::of_cliffs_delta(1.0), EffectSize::Large);
+        assert!(EffectSize::Small < EffectSize::Large);
+    }
+
+    #[test]
+    fn normal_distribution_matches_reference_values() {
+        assert_close(normal_cdf(0.0), 0.5, 1e-7);
+        assert_close(normal_cdf(1.959_963_985), 0.975, 1e-7);
+        assert_close(normal_cdf(-1.0), 0.158_655_254, 1e-7);
+        assert_close(normal_quantile(0.975), 1.959_963_985, 1e-8);
+        assert_close(normal_quantile(0.01), -2.326_347_874, 1e-8);
+        assert_close(normal_quantile(0.5), 0.0, 1e-12);
+    }
+
+    #[test]
+    fn compare_bayesian_updates_normal_prior() {
+        // Relative variances of the means are 0.01 / 3 and 0.0025 / 3, so the
+        // data precision is 240
+        let before = measurement(&[9.0, 10.0, 11.0], 0.95);
+        let after = measurement(&[19.0, 20.0, 21.0], 0.95);
+
+        let posterior = compare_bayesian(&before, &after, &RatioPrior::uninformative()).unwrap();
+        assert_close(posterior.ratio(), 2.0, 1e-12);
+        assert_close(posterior.log_ratio_std_dev, 240.0f64.sqrt().recip(), 1e-12);
+        let (lower, upper) = posterior.credible_interval(0.95).unwrap();
+        let half_width = 1.959_963_985 / 240.0f64.sqrt();
+        assert_close(lower, 2.0 * (-half_width).exp(), 1e-8);
+        assert_close(upper, 2.0 * half_width.exp(), 1e-8);
+        assert!(posterior.probability_of_regression(0.5) > 0.999);
+        assert!(posterior.probability_of_improvement(0.0) < 1e-6);
+
+        // The default prior has a precision of 16, and shrinks towards 1
+        let posterior = compare_bayesian(&before, &after, &RatioPrior::default()).unwrap();
+        assert_close(posterior.log_ratio_mean, 2.0f64.ln() * 240.0 / 256.0, 1e-12);
+        assert_close(posterior.log_ratio_std_dev, 1.0 / 16.0, 1e-12);
+
+        for level in [0.0, 1.0, -0.5, 1.5, f64::NAN] {
+            assert_eq!(posterior.credible_interval(level), None);
+        }
+    }
+
+    #[test]
+    fn compare_bayesian_handles_noiseless_and_invalid_data() {
+        let before = measurement(&[100.0; 5], 0.95);
+        let after = measurement(&[110.0; 5], 0.95);
+        let posterior = compare_bayesian(&before, &after, &RatioPrior::default()).unwrap();
+        assert_close(posterior.ratio(), 1.1, 1e-12);
+        assert_eq!(posterior.log_ratio_std_dev, 0.0);
+        assert_eq!(posterior.probability_of_regression(0.05), 1.0);
+
+        let negative = measurement(&[-1.0, -2.0], 0.95);
+        assert_eq!(
+            compare_bayesian(&negative, &after, &RatioPrior::default()),
+            None
+        );
+    }
+
+    /// Example 1 of the Wikipedia article on Welch's t-test
+    const WELCH_BEFORE: [f64; 15] = [
+        27.5, 21.0, 19.0, 23.6, 17.0, 17.9, 16.9, 20.1, 21.9, 22.6, 23.1, 19.6, 19.0, 21.7, 21.4,
+    ];
+    const WELCH_AFTER: [f64; 15] = [
+        27.1, 22.0, 20.8, 23.4, 23.4, 23.5, 25.8, 22.0, 24.8, 20.2, 21.9, 22.1, 22.9, 20.5, 24.4,
+    ];
+
+    #[test]
+    fn compare_measurements_matches_welch_reference() {
+        let before = measurement(&WELCH_BEFORE, 0.95);
+        let after = measurement(&WELCH_AFTER, 0.95);
+        let comparison = compare_measurements(&before, &after).unwrap();
+        assert_close(comparison.mean_change, 22.986_666_666 / 20.82 - 1.0, 1e-9);
+        assert_close(comparison.t_statistic, 2.455_356_398, 1e-8);
+        assert_close(comparison.degrees_of_freedom, 24.988_529_290, 1e-8);
+        assert_close(comparison.p_value, 0.021_378_001, 1e-7);
+        assert_close(comparison.cohens_d, 0.896_569_391, 1e-8);
+        assert!(comparison.significant);
+        assert!(comparison.notes.is_empty());
+
+        // p = 0.021 is not significant at 99% confidence
+        let strict_after = measurement(&WELCH_AFTER, 0.99);
+        let comparison = compare_measurements(&before, &strict_after).unwrap();
+        assert_eq!(comparison.confidence_level, 0.99);
+        assert!(!comparison.significant);
+        assert_eq!(
+            comparison.notes,
+            [ComparabilityNote::ConfidenceLevelChanged {
+                before: 0.95,
+                after: 0.99
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_measurements_handles_constant_values() {
+        let before = measurement(&[100.0; 10], 0.95);
+        let unchanged = compare_measurements(&before, &measurement(&[100.0; 10], 0.95)).unwrap();
+        assert_eq!(unchanged.t_statistic, 0.0);
+        assert_eq!(unchanged.p_value, 1.0);
+        assert!(!unchanged.significant);
+
+        let grown = compare_measurements(&before, &measurement(&[101.0; 20], 0.95)).unwrap();
+        assert_eq!(grown.t_statistic, f64::INFINITY);
+        assert_eq!(grown.degrees_of_freedom, 28.0);
+        assert_eq!(grown.p_value, 0.0);
+        assert!(grown.significant);
+        assert_eq!(grown.effect_size(), EffectSize::Large);
+        assert_eq!(
+            grown.notes,
+            [ComparabilityNote::SampleSizeChanged {
+                before: 10,
+                after: 20
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_measurements_robust_ignores_outliers() {
+        // A few slow outliers inflate the mean, but not the median
+        let before = measurement(&heavy_tailed(100.0, &[]), 0.95);
+        let after = measurement(&heavy_tailed(100.0, &[130.0; 5]), 0.95);
+        let classic = compare_measurements(&before, &after).unwrap();
+        assert_eq!(classic.mode, RobustnessMode::Classic);
+        assert_close(classic.mean_change, 0.07375, 1e-12);
+        assert!(classic.significant);
+        let robust = compare_measurements_with(&before, &after, RobustnessMode::Robust).unwrap();
+        assert_eq!(robust.median_change, 0.0);
+        assert_eq!(robust.change(), 0.0);
+        assert_eq!(robust.t_statistic, 0.0);
+        assert!(!robust.significant);
+        assert!(robust
+            .to_string()
+            .starts_with("median +0.00% (not significant"));
+
+        // A few fast outliers hide a shift of the median from the mean
+        let before = measurement(&heavy_tailed(100.0, &[1000.0; 2]), 0.95);
+        let after = measurement(&heavy_tailed(105.0, &[]), 0.95);
+        let classic = compare_measurements(&before, &after).unwrap();
+        assert!(classic.change() < 0.0);
+        assert!(!classic.significant);
+        let robust = compare_measurements_with(&before, &after, RobustnessMode::Robust).unwrap();
+        assert_close(robust.change(), 0.05, 1e-12);
+        assert!(robust.significant);
+        // Scaled MADs are 0.5 * 1.4826 on both sides
+        let std_err = (std::f64::consts::FRAC_PI_2 * 2.0 / 20.0).sqrt() * 0.5 * 1.4826;
+        assert_close(robust.t_statistic, 5.0 / std_err, 1e-9);
+        assert_close(robust.degrees_of_freedom, 38.0, 1e-9);
+        assert_close(robust.cohens_d, 5.0 / (0.5 * 1.4826), 1e-9);
+    }
+
+    /// Measurement with linear sampling, from 1 to `values.len()` iterations
+    fn linear_sampling(values: impl IntoIterator<Item = f64>) -> MeasurementData {
+        let values = values.into_iter().collect::<Vec<_>>();
+        let mut data = measurement(&values, 0.95);
+        data.iterations = (1..=values.len()).map(|iters| iters as f64).collect();
+        data
+    }
+
+    #[test]
+    fn linearity_of_linear_data() {
+        let data = linear_sampling((1..=20).map(|iters| 10.0 * iters as f64));
+        let linearity = linearity(&data).unwrap();
+        assert_eq!(linearity.r_squared, 1.0);
+        assert_eq!(linearity.per_iteration_trend, 0.0);
+        assert_eq!(linearity.residual_runs_z, 0.0);
+        assert!(linearity.is_linear());
+    }
+
+    #[test]
+    fn linearity_flags_warmup_overhead() {
+        // A fixed overhead of 50 per sample makes the first iterations look
+        // slow: per-iteration values decrease as 10 + 50 / iters, and the
+        // residuals of the fit through the origin are positive for the first
+        // 13 samples and negative for the last 7, i.e. there are only 2 runs
+        // where 10.1 are expected, with a standard deviation of 1.97
+        let data = linear_sampling((1..=20).map(|iters| 10.0 * iters as f64 + 50.0));
+        let linearity = linearity(&data).unwrap();
+        assert!(linearity.per_iteration_trend < -MAX_LINEAR_TREND);
+        let expected_runs_z = (2.0 - 10.1) / (9.1f64 * 8.1 / 19.0).sqrt();
+        assert_close(linearity.residual_runs_z, expected_runs_z, 1e-12);
+        assert!(linearity.residual_runs_z < MIN_LINEAR_RUNS_Z);
+        assert!(!linearity.is_linear());
+    }
+
+    #[test]
+    fn linearity_needs_varying_iteration_counts() {
+        assert_eq!(linearity(&linear_sampling([10.0, 20.0])), None);
+        assert_eq!(linearity(&measurement(&[10.0; 20], 0.95)), None);
+    }
+
+    /// History point with a certain mean, standard error and standard
+    /// deviation, at 95% confidence
+    fn noisy_point(mean: f64, standard_error: f64, std_dev: f64) -> HistoryPoint {
+        let mut data = measurement(&[mean], 0.95);
+        data.estimates.mean.point_estimate = mean;
+        data.estimates.mean.standard_error = standard_error;
+        data.estimates.std_dev.point_estimate = std_dev;
+        HistoryPoint::new(&data)
+    }
+
+    #[test]
+    fn recommend_sample_size_matches_power_analysis() {
+        // Two-sample z-test power analysis: n = 2 * (sigma * (z_a + z_b) / delta)^2
+        // with sigma = 10, delta = 5, z_a = 1.959964 (alpha = 0.05, two-sided)
+        // and z_b = 0.841621 (80% power), i.e. n = 62.79
+        let history = [noisy_point(100.0, 1.0, 10.0)].into_iter().collect();
+        let advice = recommend_sample_size(&history, 5.0, 0.8).unwrap();
+        assert_eq!(advice.sample_size, Some(63));
+        assert_close(advice.sample_noise_pct, 10.0, 1e-12);
+        assert_eq!(advice.run_to_run_noise_pct, 0.0);
+
+        // Large changes are detected with Criterion's minimal sample size
+        let advice = recommend_sample_size(&history, 50.0, 0.8).unwrap();
+        assert_eq!(advice.sample_size, Some(MIN_SAMPLE_SIZE));
+
+        // Means of 100 and 104 have a variance of 8, of which the standard
+        // errors explain 1, so the run-to-run variance of 7 is too large to
+        // detect a 5% change with any sample size
+        let history = [noisy_point(100.0, 1.0, 10.0), noisy_point(104.0, 1.0, 10.0)]
+            .into_iter()
+            .collect();
+        let advice = recommend_sample_size(&history, 5.0, 0.8).unwrap();
+        assert_eq!(advice.sample_size, None);
+        assert_close(advice.run_to_run_noise_pct, 7.0f64.sqrt() / 1.04, 1e-12);
+    }
+
+    #[test]
+    fn recommend_sample_size_rejects_invalid_parameters() {
+        let history = [noisy_point(100.0, 1.0, 10.0)].into_iter().collect();
+        for change in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(recommend_sample_size(&history, change, 0.8), None);
+        }
+        for power in [0.0, 1.0, 1.5, f64::NAN] {
+            assert_eq!(recommend_sample_size(&history, 5.0, power), None);
+        }
+        assert_eq!(recommend_sample_size(&History::default(), 5.0, 0.8), None);
+    }
+
+    #[test]
+    fn compare_measurements_needs_two_samples() {
+        let one = measurement(&[1.0], 0.95);
+        let two = measurement(&[1.0, 2.0], 0.95);
+        assert_eq!(compare_measurements(&one, &two), None);
+        assert_eq!(compare_measurements(&two, &one), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Benchmark tags tracked in a version-controlled file
+//!
+//! A tag file, conventionally named `tags.toml` and committed at the root of a
+//! Cargo project, maps benchmark path patterns to free-form tags, which can
+//! describe what benchmarks measure or who owns them:
+//!
+//! ```toml
+//! [tags]
+//! "parser/*" = ["hot-path", "team-frontend"]
+//! "storage/*" = ["io-bound", "team-storage"]
+//! "storage/compaction" = ["hot-path"]
+//! ```
+//!
+//! Patterns are matched like in [performance budget files](crate::budgets),
+//! and a benchmark has the tags of every pattern that matches its path. Tags
+//! can then be used to narrow down datasets with [`Dataset::filter_tagged()`],
+//! or budget reports with [`BudgetReport::filter_tagged()`], and to route the
+//! failures of a budget check to the teams that own the affected benchmarks
+//! with [`BudgetReport::failures_by_tag()`].
+//!
+//! Tags that designate teams can additionally list the people to notify about
+//! regressions of their benchmarks, which [`ci::route()`] uses to split CI
+//! output into per-team sections:
+//!
+//! ```toml
+//! [owners]
+//! "team-storage" = ["@alice", "@org/storage"]
+//! ```
+//!
+//! [`BudgetReport::failures_by_tag()`]: crate::budgets::BudgetReport::failures_by_tag
+//! [`ci::route()`]: crate::ci::route
+//! [`BudgetReport::filter_tagged()`]: crate::budgets::BudgetReport::filter_tagged
+//! [`Dataset::filter_tagged()`]: crate::dataset::Dataset::filter_tagged
+
+use crate::glob;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::Path,
+};
+
+/// Conventional name of the tag file
+pub const DEFAULT_FILE_NAME: &str = "tags.toml";
+
+/// Set of benchmark tags, as stored in a tag file
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Tags {
+    /// Tags, indexed by benchmark path pattern
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+
+    /// People to notify about the benchmarks of a tag, indexed by tag
+    ///
+    /// Tags that are listed here designate the owners of their benchmarks.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub owners: BTreeMap<String, Vec<String>>,
+}
+//
+impl Tags {
+    /// Load benchmark tags from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Load the tag file of a Cargo project, if any
+    ///
+    /// The tag file is optional, so if there is no [`DEFAULT_FILE_NAME`] file
+    /// at the root of the project, no benchmark has any tag.
+    pub fn in_cargo_root(cargo_root: impl AsRef<Path>) -> io::Result<Self> {
+        match Self::load(cargo_root.as_ref().join(DEFAULT_FILE_NAME)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    /// Save benchmark tags to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text =
+            toml::to_string_pretty(self).expect("Tags should always be serializable to TOML");
+        std::fs::write(path, text)
+    }
+
+    /// Tags of the benchmark with a certain `/`-separated path
+    pub fn of(&self, bench_path: &str) -> BTreeSet<&str> {
+        self.tags
+            .iter()
+            .filter(|(pattern, _)| glob::matches(pattern, bench_path))
+            .flat_map(|(_, tags)| tags.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Truth that the benchmark with a certain `/`-separated path has a tag
+    pub fn has(&self, bench_path: &str, tag: &str) -> bool {
+        self.tags.iter().any(|(pattern, tags)| {
+            tags.iter().any(|t| t == tag) && glob::matches(pattern, bench_path)
+        })
+    }
+}
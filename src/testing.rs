@@ -0,0 +1,112 @@
+//! Fixtures for tests that need Criterion data on disk
+
+use crate::{
+    layout, BenchmarkMetadata, ConfidenceInterval, Estimate, Estimates, MeasurementData,
+    RawBenchmarkId, Search,
+};
+use std::path::PathBuf;
+
+/// Temporary Cargo target directory, which is deleted when dropped
+pub(crate) struct TempTarget {
+    /// Path to the target directory
+    pub path: PathBuf,
+}
+//
+impl TempTarget {
+    /// Create an empty target directory, whose name must be unique across tests
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("criterion-cbor-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// Search for benchmarks in this target directory
+    pub fn search(&self) -> Search {
+        Search::in_target_dir(&self.path)
+    }
+
+    /// Write a benchmark and its measurements, oldest first, and return the
+    /// path to its directory
+    pub fn write_benchmark(
+        &self,
+        id: &RawBenchmarkId,
+        measurements: &[MeasurementData],
+    ) -> PathBuf {
+        let bench_dir = layout::data_root(&self.path).join(layout::benchmark_dir(id));
+        std::fs::create_dir_all(&bench_dir).unwrap();
+        let mut latest_record = PathBuf::new();
+        for data in measurements {
+            let file_name = layout::measurement_file_name(&data.datetime);
+            let bytes = serde_cbor::to_vec(data).unwrap();
+            std::fs::write(bench_dir.join(&file_name), bytes).unwrap();
+            latest_record = layout::benchmark_dir(id).join(file_name);
+        }
+        let metadata = BenchmarkMetadata {
+            id: id.clone(),
+            latest_record,
+        };
+        let bytes = serde_cbor::to_vec(&metadata).unwrap();
+        std::fs::write(bench_dir.join(layout::BENCHMARK_FILE_NAME), bytes).unwrap();
+        bench_dir
+    }
+}
+//
+impl Drop for TempTarget {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Identifier of a benchmark function that is not part of a group
+pub(crate) fn bench_id(name: &str) -> RawBenchmarkId {
+    RawBenchmarkId {
+        group_or_function_id: name.to_owned(),
+        function_id_in_group: None,
+        value_str: None,
+        throughput: None,
+    }
+}
+
+/// Measurement with one iteration per sample, whose estimates are the mean of
+/// the sample values without any uncertainty
+pub(crate) fn measurement(datetime: &str, values: &[f64]) -> MeasurementData {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let estimate = Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level: 0.95,
+            lower_bound: mean,
+            upper_bound: mean,
+        },
+        point_estimate: mean,
+        standard_error: 0.0,
+    };
+    MeasurementData {
+        datetime: datetime.parse().unwrap(),
+        iterations: vec![1.0; values.len()],
+        values: values.to_vec(),
+        avg_values: values.to_vec(),
+        estimates: Estimates {
+            mean: estimate,
+            median: estimate,
+            median_abs_dev: estimate,
+            slope: None,
+            std_dev: estimate,
+        },
+        throughput: None,
+        changes: None,
+        change_direction: None,
+        history_id: None,
+        history_description: None,
+    }
+}
+
+/// 20 values whose median is `center` and whose MAD is 0.5, including a few
+/// high outliers
+pub(crate) fn heavy_tailed(center: f64, outliers: &[f64]) -> Vec<f64> {
+    let mut values = vec![center - 0.5; 10];
+    values.resize(20 - outliers.len(), center + 0.5);
+    values.extend_from_slice(outliers);
+    values
+}
@@ -0,0 +1,319 @@
+//! Units in which benchmark measurements are expressed
+//!
+//! `cargo criterion` does not record which [`criterion::measurement::Measurement`]
+//! was used to produce a benchmark's data. With the default `WallTime`
+//! measurement, values are durations in nanoseconds, but custom measurements
+//! can record anything from bytes to CPU cycles. This module lets you tell the
+//! library what the values mean, so that they are not misreported as durations.
+//!
+//! It also provides [`ThroughputRate`], which turns a benchmark's
+//! [`Throughput`] and execution time into an amount of work per second that can
+//! be expressed in any [`RateUnit`].
+
+use criterion::Throughput;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Unit of the values recorded by a benchmark
+///
+/// This applies to the raw `values` of a measurement as well as to the
+/// per-iteration `avg_values` and to all statistical estimates derived from
+/// them. It defaults to [`ValueUnit::Nanoseconds`], which is what Criterion's
+/// default `WallTime` measurement records.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ValueUnit {
+    /// Durations in nanoseconds (Criterion's `WallTime` measurement)
+    #[default]
+    Nanoseconds,
+
+    /// Amounts of bytes (e.g. memory allocation measurements)
+    Bytes,
+
+    /// Any other unit, identified by the symbol that should be used to display
+    /// it (e.g. "cycles" or "instructions")
+    Custom(String),
+}
+//
+impl ValueUnit {
+    /// Truth that values in this unit are durations
+    pub fn is_time(&self) -> bool {
+        matches!(self, Self::Nanoseconds)
+    }
+
+    /// Format a value expressed in this unit for human consumption
+    ///
+    /// Durations and byte counts are scaled to the most appropriate multiple
+    /// (µs, ms, KiB, MiB...) in the same way that Criterion would do it, while
+    /// values in custom units are displayed as-is, followed by the unit symbol.
+    pub fn format_value(&self, value: f64) -> String {
+        match self {
+            Self::Nanoseconds => format_time(value),
+            Self::Bytes => format_bytes(value),
+            Self::Custom(symbol) => format!("{} {symbol}", short(value)),
+        }
+    }
+}
+//
+impl Display for ValueUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nanoseconds => f.write_str("ns"),
+            Self::Bytes => f.write_str("B"),
+            Self::Custom(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+/// Amount of work performed per second by a benchmark
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThroughputRate {
+    /// Amount of bytes or elements processed per second
+    pub per_second: f64,
+
+    /// What the amount is expressed in
+    pub kind: ThroughputKind,
+}
+//
+impl ThroughputRate {
+    /// Compute the rate of a benchmark with a certain per-iteration throughput
+    /// and execution time in nanoseconds
+    pub fn new(throughput: &Throughput, time_ns: f64) -> Self {
+        let (kind, per_iteration) = ThroughputKind::of(throughput);
+        Self {
+            per_second: per_iteration as f64 / (time_ns * 1e-9),
+            kind,
+        }
+    }
+
+    /// Express this rate in a certain unit
+    ///
+    /// Binary and decimal byte units can be used regardless of whether the
+    /// benchmark's throughput was declared as [`Throughput::Bytes`] or
+    /// [`Throughput::BytesDecimal`], as these only differ in how Criterion
+    /// displays them. Returns `None` if the unit measures bytes and this rate
+    /// measures elements, or vice versa.
+    pub fn convert_to(&self, unit: RateUnit) -> Option<f64> {
+        let is_bytes = !matches!(self.kind, ThroughputKind::Elements);
+        (is_bytes == unit.is_bytes()).then(|| self.per_second / unit.per_second())
+    }
+}
+//
+impl Display for ThroughputRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let units: [RateUnit; 4] = match self.kind {
+            ThroughputKind::Bytes => [
+                RateUnit::BytesPerSec,
+                RateUnit::KiBps,
+                RateUnit::MiBps,
+                RateUnit::GiBps,
+            ],
+            ThroughputKind::BytesDecimal => [
+                RateUnit::BytesPerSec,
+                RateUnit::KBps,
+                RateUnit::MBps,
+                RateUnit::GBps,
+            ],
+            ThroughputKind::Elements => [
+                RateUnit::ElemsPerSec,
+                RateUnit::KElemsPerSec,
+                RateUnit::MElemsPerSec,
+                RateUnit::GElemsPerSec,
+            ],
+        };
+        let unit = units
+            .into_iter()
+            .rev()
+            .find(|unit| self.per_second.abs() >= unit.per_second())
+            .unwrap_or(units[0]);
+        write!(f, "{} {unit}", short(self.per_second / unit.per_second()))
+    }
+}
+
+/// What a [`Throughput`] is expressed in
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ThroughputKind {
+    /// Bytes, displayed using binary multiples (KiB, MiB...)
+    Bytes,
+
+    /// Bytes, displayed using decimal multiples (KB, MB...)
+    BytesDecimal,
+
+    /// Elements
+    Elements,
+}
+//
+impl ThroughputKind {
+    /// Split a throughput into its kind and its per-iteration amount
+    pub fn of(throughput: &Throughput) -> (Self, u64) {
+        match *throughput {
+            Throughput::Bytes(n) => (Self::Bytes, n),
+            Throughput::BytesDecimal(n) => (Self::BytesDecimal, n),
+            Throughput::Elements(n) => (Self::Elements, n),
+        }
+    }
+}
+
+/// Unit in which a [`ThroughputRate`] can be expressed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RateUnit {
+    /// Bytes per second
+    BytesPerSec,
+
+    /// Kibibytes (1024 bytes) per second
+    KiBps,
+
+    /// Mebibytes (1024² bytes) per second
+    MiBps,
+
+    /// Gibibytes (1024³ bytes) per second
+    GiBps,
+
+    /// Kilobytes (1000 bytes) per second
+    KBps,
+
+    /// Megabytes (1000² bytes) per second
+    MBps,
+
+    /// Gigabytes (1000³ bytes) per second
+    GBps,
+
+    /// Elements per second
+    ElemsPerSec,
+
+    /// Thousands of elements per second
+    KElemsPerSec,
+
+    /// Millions of elements per second
+    MElemsPerSec,
+
+    /// Billions of elements per second
+    GElemsPerSec,
+}
+//
+impl RateUnit {
+    /// Truth that this unit measures bytes rather than elements
+    pub fn is_bytes(self) -> bool {
+        !matches!(
+            self,
+            Self::ElemsPerSec | Self::KElemsPerSec | Self::MElemsPerSec | Self::GElemsPerSec
+        )
+    }
+
+    /// Number of bytes or elements per second that this unit represents
+    fn per_second(self) -> f64 {
+        match self {
+            Self::BytesPerSec | Self::ElemsPerSec => 1.0,
+            Self::KiBps => 1024.0,
+            Self::MiBps => 1024.0 * 1024.0,
+            Self::GiBps => 1024.0 * 1024.0 * 1024.0,
+            Self::KBps | Self::KElemsPerSec => 1e3,
+            Self::MBps | Self::MElemsPerSec => 1e6,
+            Self::GBps | Self::GElemsPerSec => 1e9,
+        }
+    }
+}
+//
+impl Display for RateUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BytesPerSec => "B/s",
+            Self::KiBps => "KiB/s",
+            Self::MiBps => "MiB/s",
+            Self::GiBps => "GiB/s",
+            Self::KBps => "KB/s",
+            Self::MBps => "MB/s",
+            Self::GBps => "GB/s",
+            Self::ElemsPerSec => "elem/s",
+            Self::KElemsPerSec => "Kelem/s",
+            Self::MElemsPerSec => "Melem/s",
+            Self::GElemsPerSec => "Gelem/s",
+        })
+    }
+}
+
+/// Format a duration in nanoseconds using the most appropriate time unit
+fn format_time(ns: f64) -> String {
+    let abs = ns.abs();
+    if abs < 1.0 {
+        format!("{} ps", short(ns * 1e3))
+    } else if abs < 1e3 {
+        format!("{} ns", short(ns))
+    } else if abs < 1e6 {
+        format!("{} µs", short(ns / 1e3))
+    } else if abs < 1e9 {
+        format!("{} ms", short(ns / 1e6))
+    } else {
+        format!("{} s", short(ns / 1e9))
+    }
+}
+
+/// Format an amount of bytes using the most appropriate binary multiple
+fn format_bytes(bytes: f64) -> String {
+    const KIB: f64 = 1024.0;
+    let abs = bytes.abs();
+    if abs < KIB {
+        format!("{} B", short(bytes))
+    } else if abs < KIB * KIB {
+        format!("{} KiB", short(bytes / KIB))
+    } else if abs < KIB * KIB * KIB {
+        format!("{} MiB", short(bytes / (KIB * KIB)))
+    } else {
+        format!("{} GiB", short(bytes / (KIB * KIB * KIB)))
+    }
+}
+
+/// Format a number with ~4 significant digits, like Criterion does
+fn short(n: f64) -> String {
+    let abs = n.abs();
+    if abs < 10.0 {
+        format!("{n:.4}")
+    } else if abs < 100.0 {
+        format!("{n:.3}")
+    } else if abs < 1000.0 {
+        format!("{n:.2}")
+    } else if abs < 10000.0 {
+        format!("{n:.1}")
+    } else {
+        format!("{n:.0}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throughput_rate_converts_between_units() {
+        // 1 MiB per iteration, with 1 ms per iteration
+        let rate = ThroughputRate::new(&Throughput::Bytes(1024 * 1024), 1e6);
+        assert_eq!(rate.kind, ThroughputKind::Bytes);
+        assert_eq!(rate.per_second, 1024.0 * 1024.0 * 1000.0);
+        assert_eq!(rate.convert_to(RateUnit::MiBps), Some(1000.0));
+        assert_eq!(rate.convert_to(RateUnit::MBps), Some(1.048_576 * 1000.0));
+        assert_eq!(rate.convert_to(RateUnit::ElemsPerSec), None);
+        assert_eq!(rate.to_string(), "1000.0 MiB/s");
+
+        let rate = ThroughputRate::new(&Throughput::BytesDecimal(1000), 1e6);
+        assert_eq!(rate.convert_to(RateUnit::KBps), Some(1000.0));
+        assert_eq!(rate.to_string(), "1.0000 MB/s");
+
+        let rate = ThroughputRate::new(&Throughput::Elements(3), 2.0);
+        assert_eq!(rate.convert_to(RateUnit::GElemsPerSec), Some(1.5));
+        assert_eq!(rate.convert_to(RateUnit::BytesPerSec), None);
+        assert_eq!(rate.to_string(), "1.5000 Gelem/s");
+    }
+
+    #[test]
+    fn format_value_scales_like_criterion() {
+        let ns = ValueUnit::Nanoseconds;
+        assert_eq!(ns.format_value(0.5), "500.00 ps");
+        assert_eq!(ns.format_value(12.345), "12.345 ns");
+        assert_eq!(ns.format_value(1_500.0), "1.5000 µs");
+        assert_eq!(ns.format_value(2.5e9), "2.5000 s");
+        assert_eq!(ValueUnit::Bytes.format_value(512.0), "512.00 B");
+        assert_eq!(ValueUnit::Bytes.format_value(1536.0), "1.5000 KiB");
+        let cycles = ValueUnit::Custom("cycles".to_owned());
+        assert_eq!(cycles.format_value(12345.0), "12345 cycles");
+        assert!(ns.is_time() && !ValueUnit::Bytes.is_time() && !cycles.is_time());
+    }
+}
@@ -0,0 +1,526 @@
+//! Detection of suspicious benchmark data
+//!
+//! Benchmarks that crashed or misbehaved during a run can leave behind data
+//! that decodes fine but is not meaningful, like NaN sample values or
+//! estimates. This module detects such problems, so that affected
+//! measurements can be excluded from analysis or reported to the user.
+//!
+//! The same checks are performed when decoding files in
+//! [strict mode](crate::decode::DecodeOptions::strict).
+//!
+//! This library also makes assumptions about how cargo-criterion lays out and
+//! fills data directories, e.g. that benchmark directories are named after
+//! benchmark identifiers. [`check_model()`] verifies these assumptions against
+//! existing benchmark data, so that violations can be reported upstream.
+
+use crate::{layout, BenchmarkMetadata, Error, Estimate, MeasurementData, Search};
+use chrono::{NaiveDateTime, TimeDelta};
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+/// Problem detected in a measurement's data
+#[derive(Clone, Debug, PartialEq)]
+pub enum Issue {
+    /// A sample iteration count or value is NaN or infinite
+    NonFiniteSample {
+        /// Name of the sample series (`iterations`, `values` or `avg_values`)
+        series: &'static str,
+
+        /// Index of the sample within the series
+        index: usize,
+
+        /// Offending value
+        value: f64,
+    },
+
+    /// A sample iteration count or value is negative
+    NegativeSample {
+        /// Name of the sample series (`iterations`, `values` or `avg_values`)
+        series: &'static str,
+
+        /// Index of the sample within the series
+        index: usize,
+
+        /// Offending value
+        value: f64,
+    },
+
+    /// Sample iteration counts are not sorted in increasing order, which none
+    /// of Criterion's sampling modes can produce
+    UnsortedIterations {
+        /// Index of the first sample whose iteration count is smaller than
+        /// that of the previous sample
+        index: usize,
+    },
+
+    /// A statistical estimate has a non-finite point estimate, standard error
+    /// or confidence interval bound
+    NonFiniteEstimate {
+        /// Name of the estimate (e.g. `mean` or `changes.median`)
+        estimate: &'static str,
+    },
+
+    /// A confidence level does not lie strictly between 0 and 1
+    InvalidConfidenceLevel {
+        /// Name of the estimate (e.g. `mean` or `changes.median`)
+        estimate: &'static str,
+
+        /// Offending confidence level
+        level: f64,
+    },
+}
+//
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteSample {
+                series,
+                index,
+                value,
+            } => write!(f, "non-finite value {value} in {series}[{index}]"),
+            Self::NegativeSample {
+                series,
+                index,
+                value,
+            } => write!(f, "negative value {value} in {series}[{index}]"),
+            Self::UnsortedIterations { index } => {
+                write!(f, "iteration counts are not sorted at index {index}")
+            }
+            Self::NonFiniteEstimate { estimate } => {
+                write!(f, "non-finite {estimate} estimate")
+            }
+            Self::InvalidConfidenceLevel { estimate, level } => {
+                write!(
+                    f,
+                    "invalid confidence level {level} for {estimate} estimate"
+                )
+            }
+        }
+    }
+}
+
+/// Check a measurement's data for problems
+///
+/// Returns an empty list if no problem was found.
+pub fn measurement_issues(data: &MeasurementData) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let series = [
+        ("iterations", &data.iterations),
+        ("values", &data.values),
+        ("avg_values", &data.avg_values),
+    ];
+    for (series, samples) in series {
+        for (index, &value) in samples.iter().enumerate() {
+            if !value.is_finite() {
+                issues.push(Issue::NonFiniteSample {
+                    series,
+                    index,
+                    value,
+                });
+            } else if value < 0.0 {
+                issues.push(Issue::NegativeSample {
+                    series,
+                    index,
+                    value,
+                });
+            }
+        }
+    }
+    if let Some(index) = data
+        .iterations
+        .windows(2)
+        .position(|pair| pair[0] > pair[1])
+    {
+        issues.push(Issue::UnsortedIterations { index: index + 1 });
+    }
+    let estimates = &data.estimates;
+    let changes = data.changes.iter().flat_map(|changes| {
+        [
+            ("changes.mean", &changes.mean),
+            ("changes.median", &changes.median),
+        ]
+    });
+    let estimates = [
+        ("mean", &estimates.mean),
+        ("median", &estimates.median),
+        ("median_abs_dev", &estimates.median_abs_dev),
+        ("std_dev", &estimates.std_dev),
+    ]
+    .into_iter()
+    .chain(estimates.slope.as_ref().map(|slope| ("slope", slope)))
+    .chain(changes);
+    for (name, estimate) in estimates {
+        check_estimate(name, estimate, &mut issues);
+    }
+    issues
+}
+
+/// Check a statistical estimate for problems
+fn check_estimate(name: &'static str, estimate: &Estimate, issues: &mut Vec<Issue>) {
+    if !estimate.is_finite() {
+        issues.push(Issue::NonFiniteEstimate { estimate: name });
+    }
+    let level = estimate.confidence_interval.confidence_level;
+    if !(level > 0.0 && level < 1.0) {
+        issues.push(Issue::InvalidConfidenceLevel {
+            estimate: name,
+            level,
+        });
+    }
+}
+
+/// Check that benchmark data matches this library's model of cargo-criterion
+///
+/// Every metadata and measurement file of the search is decoded and checked.
+/// Files that cannot be decoded are reported as violations, whereas I/O errors
+/// are propagated. Unexpected directory layouts are handled according to the
+/// [invariant checks](Search::invariant_checks) of the search, and reported as
+/// violations when these checks report errors.
+pub fn check_model(search: Search) -> Result<ModelCheck, Error> {
+    let mut result = ModelCheck::default();
+    for bench in search.find_all() {
+        let bench = match bench {
+            Ok(bench) => bench,
+            Err(Error::NonUnicodeName { path }) => {
+                result.violations.push(Violation {
+                    path,
+                    kind: ModelViolation::NonUnicodeName,
+                });
+                continue;
+            }
+            Err(Error::UnexpectedLayout { path, message }) => {
+                result.violations.push(Violation {
+                    path,
+                    kind: ModelViolation::UnexpectedLayout(message),
+                });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        result.benchmarks += 1;
+        let mut violation = |path: PathBuf, kind: ModelViolation| {
+            result.violations.push(Violation { path, kind });
+        };
+        let metadata_path = bench.dir_path().join(layout::BENCHMARK_FILE_NAME);
+        let metadata = match bench.metadata() {
+            Ok(metadata) => metadata,
+            Err(Error::Decode { source, .. }) => {
+                violation(
+                    metadata_path,
+                    ModelViolation::Undecodable(source.to_string()),
+                );
+                result.measurements += bench.measurements().count();
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        match metadata.id.decode() {
+            Ok(_) => {}
+            Err(Error::ImpossibleBenchmarkId { message, .. }) => violation(
+                metadata_path.clone(),
+                ModelViolation::ImpossibleIdentifier(message.to_owned()),
+            ),
+            Err(e) => violation(
+                metadata_path.clone(),
+                ModelViolation::ImpossibleIdentifier(e.to_string()),
+            ),
+        }
+        let expected_dir = layout::benchmark_dir(&metadata.id);
+        if bench.path_from_data_root().as_path() != expected_dir {
+            violation(
+                metadata_path.clone(),
+                ModelViolation::UnexpectedDirectory(expected_dir),
+            );
+        }
+        let latest_measurement = bench.latest_measurement();
+        if metadata.latest_record.file_name() != latest_measurement.path().file_name() {
+            violation(
+                metadata_path,
+                ModelViolation::LatestRecordMismatch(metadata.latest_record.clone()),
+            );
+        }
+
+        for measurement in bench.measurements() {
+            result.measurements += 1;
+            let path = measurement.path();
+            let file_datetime = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(layout::parse_measurement_file_name);
+            let Some(file_datetime) = file_datetime else {
+                violation(path.to_owned(), ModelViolation::MalformedFileName);
+                continue;
+            };
+            let data = match measurement.data() {
+                Ok(data) => data,
+                Err(Error::Decode { source, .. }) => {
+                    violation(
+                        path.to_owned(),
+                        ModelViolation::Undecodable(source.to_string()),
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            for kind in measurement_violations(&data, file_datetime, &metadata) {
+                violation(path.to_owned(), kind);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Check a decoded measurement against the model of cargo-criterion
+fn measurement_violations(
+    data: &MeasurementData,
+    file_datetime: NaiveDateTime,
+    metadata: &BenchmarkMetadata,
+) -> Vec<ModelViolation> {
+    let mut violations = Vec::new();
+    // File names use the local time of the machine that ran the benchmark,
+    // whose time zone is unknown, so only gross mismatches are detected
+    if (data.datetime.naive_utc() - file_datetime).abs() > TimeDelta::days(1) {
+        violations.push(ModelViolation::DatetimeMismatch);
+    }
+    if data.iterations.iter().any(|iters| *iters != iters.trunc()) {
+        violations.push(ModelViolation::FractionalIterations);
+    }
+    let lengths = [
+        data.iterations.len(),
+        data.values.len(),
+        data.avg_values.len(),
+    ];
+    if lengths.iter().any(|&len| len != lengths[0]) {
+        violations.push(ModelViolation::SampleCountMismatch(lengths));
+    } else if !data.avg_values.iter().copied().eq(data
+        .iterations
+        .iter()
+        .zip(&data.values)
+        .map(|(&iters, &value)| value / iters))
+    {
+        violations.push(ModelViolation::InconsistentAverages);
+    }
+    if data.throughput != metadata.id.throughput {
+        violations.push(ModelViolation::ThroughputMismatch);
+    }
+    if data.changes.is_some() != data.change_direction.is_some() {
+        violations.push(ModelViolation::ChangesWithoutDirection);
+    }
+    violations
+}
+
+/// Outcome of [`check_model()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelCheck {
+    /// Number of benchmarks that were checked
+    pub benchmarks: usize,
+
+    /// Number of measurements that were checked
+    pub measurements: usize,
+
+    /// Violations of the model, in the order where they were found
+    pub violations: Vec<Violation>,
+}
+//
+impl ModelCheck {
+    /// Truth that no violation was found
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+//
+impl Display for ModelCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for violation in &self.violations {
+            writeln!(f, "{violation}")?;
+        }
+        write!(
+            f,
+            "Checked {} benchmarks and {} measurements, found {} violations",
+            self.benchmarks,
+            self.measurements,
+            self.violations.len()
+        )
+    }
+}
+
+/// Violation of the model of cargo-criterion, found in a certain file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Metadata or measurement file where the violation was found
+    pub path: PathBuf,
+
+    /// Nature of the violation
+    pub kind: ModelViolation,
+}
+//
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "VIOLATION in {}: {}", self.path.display(), self.kind)
+    }
+}
+
+/// Assumption about cargo-criterion that benchmark data does not satisfy
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModelViolation {
+    /// The file or directory name is not valid Unicode
+    NonUnicodeName,
+
+    /// The file or directory does not belong in a Criterion data directory,
+    /// for the specified reason
+    UnexpectedLayout(String),
+
+    /// The file could not be decoded, for the specified reason
+    Undecodable(String),
+
+    /// The benchmark identifier is not one that Criterion can produce, for the
+    /// specified reason
+    ImpossibleIdentifier(String),
+
+    /// The benchmark directory does not match the benchmark identifier, which
+    /// should have resulted in the specified relative path from the data root
+    UnexpectedDirectory(PathBuf),
+
+    /// The specified latest record of the metadata is not the latest
+    /// measurement of the benchmark
+    LatestRecordMismatch(PathBuf),
+
+    /// The measurement file name does not encode a date and time
+    MalformedFileName,
+
+    /// The measurement's date and time is more than a day away from that of
+    /// its file name
+    DatetimeMismatch,
+
+    /// Some iteration counts are not integers
+    FractionalIterations,
+
+    /// The `iterations`, `values` and `avg_values` series have the specified,
+    /// different lengths
+    SampleCountMismatch([usize; 3]),
+
+    /// `avg_values` is not the ratio of `values` to `iterations`
+    InconsistentAverages,
+
+    /// The measurement's throughput differs from that of the benchmark
+    ThroughputMismatch,
+
+    /// Only one of `changes` and `change_direction` is present
+    ChangesWithoutDirection,
+}
+//
+impl Display for ModelViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonUnicodeName => f.write_str("name is not valid Unicode"),
+            Self::UnexpectedLayout(reason) => f.write_str(reason),
+            Self::Undecodable(error) => write!(f, "failed to decode: {error}"),
+            Self::ImpossibleIdentifier(reason) => {
+                write!(f, "invalid benchmark identifier: {reason}")
+            }
+            Self::UnexpectedDirectory(expected) => write!(
+                f,
+                "benchmark identifier maps to directory {}",
+                expected.display()
+            ),
+            Self::LatestRecordMismatch(latest_record) => write!(
+                f,
+                "latest record {} is not the latest measurement",
+                latest_record.display()
+            ),
+            Self::MalformedFileName => f.write_str("file name does not encode a date and time"),
+            Self::DatetimeMismatch => {
+                f.write_str("recorded date and time does not match file name")
+            }
+            Self::FractionalIterations => f.write_str("some iteration counts are not integers"),
+            Self::SampleCountMismatch([iterations, values, avg_values]) => write!(
+                f,
+                "{iterations} iterations, {values} values and {avg_values} avg_values"
+            ),
+            Self::InconsistentAverages => f.write_str("avg_values is not values / iterations"),
+            Self::ThroughputMismatch => f.write_str("throughput differs from benchmark metadata"),
+            Self::ChangesWithoutDirection => {
+                f.write_str("only one of changes and change_direction is present")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfidenceInterval, Estimates, RawBenchmarkId};
+    use criterion::Throughput;
+
+    #[test]
+    fn check_model_reports_impossible_identifiers() {
+        let target_dir =
+            std::env::temp_dir().join(format!("criterion-cbor-check-model-{}", std::process::id()));
+        let id = RawBenchmarkId {
+            group_or_function_id: "parse".to_owned(),
+            function_id_in_group: None,
+            value_str: None,
+            throughput: Some(Throughput::Bytes(64)),
+        };
+        let bench_dir = layout::data_root(&target_dir).join(layout::benchmark_dir(&id));
+        std::fs::create_dir_all(&bench_dir).unwrap();
+        let datetime = "2026-01-01T00:00:00Z".parse().unwrap();
+        let file_name = layout::measurement_file_name(&datetime);
+        let metadata = BenchmarkMetadata {
+            latest_record: layout::benchmark_dir(&id).join(&file_name),
+            id,
+        };
+        let estimate = Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: 0.95,
+                lower_bound: 1.0,
+                upper_bound: 1.0,
+            },
+            point_estimate: 1.0,
+            standard_error: 0.0,
+        };
+        let data = MeasurementData {
+            datetime,
+            iterations: vec![1.0, 2.0],
+            values: vec![1.0, 2.0],
+            avg_values: vec![1.0, 1.0],
+            estimates: Estimates {
+                mean: estimate,
+                median: estimate,
+                median_abs_dev: estimate,
+                slope: Some(estimate),
+                std_dev: estimate,
+            },
+            throughput: Some(Throughput::Bytes(64)),
+            changes: None,
+            change_direction: None,
+            history_id: None,
+            history_description: None,
+        };
+        let write = |name: &str, bytes: Vec<u8>| std::fs::write(bench_dir.join(name), bytes);
+        write(
+            layout::BENCHMARK_FILE_NAME,
+            serde_cbor::to_vec(&metadata).unwrap(),
+        )
+        .unwrap();
+        write(&file_name, serde_cbor::to_vec(&data).unwrap()).unwrap();
+
+        let check = check_model(Search::in_target_dir(&target_dir));
+        std::fs::remove_dir_all(&target_dir).unwrap();
+        let check = check.unwrap();
+        assert_eq!(check.benchmarks, 1);
+        assert_eq!(check.measurements, 1);
+        assert_eq!(
+            check
+                .violations
+                .iter()
+                .map(|violation| &violation.kind)
+                .collect::<Vec<_>>(),
+            [&ModelViolation::ImpossibleIdentifier(
+                "throughput specified for a benchmark outside of a group".to_owned()
+            )]
+        );
+    }
+}
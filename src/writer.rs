@@ -0,0 +1,273 @@
+//! Modification of cargo-criterion's data files
+//!
+//! This library is mostly about reading benchmark data, but some workflows
+//! require amending data after the fact, like tagging old measurements with
+//! the name of the release that they were taken on. This module provides such
+//! modifications, taking care to preserve the data that it does not touch.
+//!
+//! Files that explain a measurement, like flamegraphs or profiler outputs, can
+//! be attached to it with [`attach_artifact()`].
+//!
+//! It can also record the [run configuration](crate::session::RunConfig) of
+//! a cargo-criterion session, which cargo-criterion does not save by itself.
+
+use crate::{
+    decode, layout,
+    session::{RunConfig, Session},
+    MeasurementData,
+};
+use chrono::{DateTime, Utc};
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_cbor::Value;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Set the user-provided identifier and/or description of a measurement
+///
+/// This rewrites the `history_id` and `history_description` fields of the
+/// measurement file at `measurement_path`, which can be obtained via
+/// [`Measurement::path()`](crate::Measurement::path). Fields for which `None`
+/// is specified are left unchanged.
+///
+/// All other fields of the measurement file are preserved, including fields
+/// that this library does not know about, and top-level fields are kept in
+/// their original order. The file is replaced atomically, so that a failure
+/// will not leave a partially written file behind. Note that this updates the
+/// file's modification time.
+///
+/// Files that are not valid measurement files are reported as errors of kind
+/// [`io::ErrorKind::InvalidData`], and left untouched.
+pub fn annotate(
+    measurement_path: impl AsRef<Path>,
+    history_id: Option<&str>,
+    description: Option<&str>,
+) -> io::Result<()> {
+    let path = measurement_path.as_ref();
+    let bytes = std::fs::read(path)?;
+    MeasurementData::from_slice(&bytes)?;
+    let OrderedMap(mut fields) = decode::from_slice(&bytes)?;
+    for (key, value) in [
+        ("history_id", history_id),
+        ("history_description", description),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+        let value = Value::Text(value.to_owned());
+        let key = Value::Text(key.to_owned());
+        match fields.iter_mut().find(|(field, _)| *field == key) {
+            Some((_, field_value)) => *field_value = value,
+            None => fields.push((key, value)),
+        }
+    }
+    let bytes = serde_cbor::to_vec(&OrderedMap(fields)).map_err(io::Error::other)?;
+    replace_file(path, &bytes)
+}
+
+/// Attach a file to a measurement as an artifact
+///
+/// The file at `artifact_path` is copied into the
+/// [artifact directory](layout::artifacts_dir) of the measurement file at
+/// `measurement_path`, keeping its file name, and the location of the copy is
+/// returned. An artifact with the same name that was previously attached to
+/// the measurement is replaced.
+///
+/// Measurement paths that do not follow the measurement file naming convention
+/// are reported as errors of kind [`io::ErrorKind::InvalidInput`].
+pub fn attach_artifact(
+    measurement_path: impl AsRef<Path>,
+    artifact_path: impl AsRef<Path>,
+) -> io::Result<PathBuf> {
+    let measurement_path = measurement_path.as_ref();
+    let artifact_path = artifact_path.as_ref();
+    let is_measurement = measurement_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(layout::is_measurement_file);
+    if !is_measurement {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a measurement file: {}", measurement_path.display()),
+        ));
+    }
+    let file_name = artifact_path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "artifact path has no file name: {}",
+                artifact_path.display()
+            ),
+        )
+    })?;
+    let artifacts_dir = layout::artifacts_dir(measurement_path);
+    std::fs::create_dir_all(&artifacts_dir)?;
+    let dest = artifacts_dir.join(file_name);
+    std::fs::copy(artifact_path, &dest)?;
+    Ok(dest)
+}
+
+/// Record the run configuration of a cargo-criterion session
+///
+/// The session is identified by its start time, which should be taken right
+/// before `cargo criterion` is started so that all measurements from the run
+/// are attributed to this session by
+/// [`session::session_of()`](crate::session::session_of). Any previously
+/// recorded configuration for the same session is replaced.
+pub fn write_run_config(
+    target_dir: impl AsRef<Path>,
+    start: DateTime<Utc>,
+    config: &RunConfig,
+) -> io::Result<Session> {
+    let session = Session::new(target_dir.as_ref(), start);
+    std::fs::create_dir_all(session.dir_path())?;
+    let bytes = serde_cbor::to_vec(config).map_err(io::Error::other)?;
+    replace_file(
+        &session.dir_path().join(layout::RUN_CONFIG_FILE_NAME),
+        &bytes,
+    )?;
+    Ok(session)
+}
+
+/// Record which cargo-criterion version produced the data of a `target`
+/// directory
+///
+/// cargo-criterion does not record its own version, so this should be called
+/// by tools that run it, e.g. with the output of `cargo criterion --version`.
+/// The recorded version is then reported by
+/// [`Benchmark::format_hint()`](crate::Benchmark::format_hint).
+pub fn record_format_version(target_dir: impl AsRef<Path>, version: &str) -> io::Result<()> {
+    let marker = layout::format_marker(&layout::data_root(target_dir));
+    std::fs::create_dir_all(marker.parent().expect("Format marker should have a parent"))?;
+    replace_file(&marker, version.as_bytes())
+}
+
+/// Atomically replace the contents of a file
+pub(crate) fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temp_name = path
+        .file_name()
+        .expect("Data file paths should have a file name")
+        .to_owned();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
+/// CBOR map that keeps its entries in their original order
+///
+/// [`Value::Map`] sorts its entries, which would move the `datetime` field away
+/// from the start of measurement files, where
+/// [`Measurement::precise_datetime()`](crate::Measurement::precise_datetime)
+/// expects it.
+struct OrderedMap(Vec<(Value, Value)>);
+//
+impl<'de> Deserialize<'de> for OrderedMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Visitor that collects map entries in order
+        struct OrderedMapVisitor;
+        //
+        impl<'de> Visitor<'de> for OrderedMapVisitor {
+            type Value = OrderedMap;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedMap, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedMap(entries))
+            }
+        }
+        deserializer.deserialize_map(OrderedMapVisitor)
+    }
+}
+//
+impl Serialize for OrderedMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{measurement, TempTarget};
+
+    /// Keys of the top-level map of a CBOR file, in order
+    fn keys(path: &Path) -> Vec<String> {
+        let OrderedMap(fields) = decode::read_cbor(path).unwrap();
+        fields
+            .into_iter()
+            .map(|(key, _)| match key {
+                Value::Text(key) => key,
+                other => panic!("unexpected key {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn annotate_preserves_other_fields() {
+        let target = TempTarget::new("annotate");
+        let path = target.path.join("measurement_260101000000.cbor");
+        let data = measurement("2026-01-01T00:00:00Z", &[1.0, 2.0]);
+        let OrderedMap(mut fields) =
+            decode::from_slice(&serde_cbor::to_vec(&data).unwrap()).unwrap();
+        fields.push((Value::Text("future_field".to_owned()), Value::Integer(42)));
+        std::fs::write(&path, serde_cbor::to_vec(&OrderedMap(fields)).unwrap()).unwrap();
+
+        annotate(&path, Some("v1.0"), None).unwrap();
+        let annotated = decode::read_cbor::<MeasurementData>(&path).unwrap();
+        assert_eq!(annotated.history_id.as_deref(), Some("v1.0"));
+        assert_eq!(annotated.history_description, None);
+        assert_eq!(annotated.values, data.values);
+        let annotated_keys = keys(&path);
+        assert_eq!(annotated_keys[0], "datetime");
+        assert!(annotated_keys.iter().any(|key| key == "future_field"));
+        let value: Value = decode::read_cbor(&path).unwrap();
+        let Value::Map(map) = value else {
+            panic!("measurement files should contain a map")
+        };
+        assert_eq!(
+            map.get(&Value::Text("future_field".to_owned())),
+            Some(&Value::Integer(42))
+        );
+
+        annotate(&path, None, Some("release build")).unwrap();
+        let annotated = decode::read_cbor::<MeasurementData>(&path).unwrap();
+        assert_eq!(annotated.history_id.as_deref(), Some("v1.0"));
+        assert_eq!(
+            annotated.history_description.as_deref(),
+            Some("release build")
+        );
+        annotate(&path, None, None).unwrap();
+        let unchanged = decode::read_cbor::<MeasurementData>(&path).unwrap();
+        assert_eq!(unchanged, annotated);
+        assert_eq!(keys(&path)[0], "datetime");
+    }
+
+    #[test]
+    fn annotate_rejects_invalid_files() {
+        let target = TempTarget::new("annotate-invalid");
+        let path = target.path.join("measurement_260101000000.cbor");
+        let bytes = serde_cbor::to_vec(&[1, 2, 3]).unwrap();
+        std::fs::write(&path, &bytes).unwrap();
+        let error = annotate(&path, Some("v1.0"), None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+}